@@ -652,8 +652,6 @@ mod tests {
         assert_eq!(pdep(pext(x, mask), mask), x & mask);
     }
 
-    #[test]
-    // ...existing code...
     #[test]
     fn test_simd_u64x4_zero_checks() {
         let zeros = SimdU64x4::new(0, 0, 0, 0);
@@ -10,6 +10,8 @@ pub mod mov;
 pub mod movegen;
 pub mod movelist;
 pub mod occupancy;
+pub mod packed;
+pub mod pawns;
 pub mod perft;
 pub mod piece;
 pub mod piecebitboards;
@@ -57,6 +57,43 @@ impl ChessMove {
         }
     }
 
+    /// Compact 16-bit encoding: bits 0-5 from, 6-11 to, 12-15 move type.
+    /// The null move encodes to 0.
+    pub const fn to_packed(&self) -> u16 {
+        let kind: u16 = match self.move_type {
+            MoveType::Quiet => 0,
+            MoveType::Capture => 1,
+            MoveType::EnPassant => 2,
+            MoveType::CastleKingside => 3,
+            MoveType::CastleQueenside => 4,
+            MoveType::Promotion(PieceKind::Knight) => 5,
+            MoveType::Promotion(PieceKind::Bishop) => 6,
+            MoveType::Promotion(PieceKind::Rook) => 7,
+            MoveType::Promotion(_) => 8,
+        };
+        (self.from as u16) | ((self.to as u16) << 6) | (kind << 12)
+    }
+
+    /// Inverse of `to_packed`. Returns `None` for an unknown move-type code.
+    pub const fn from_packed(bits: u16) -> Option<Self> {
+        let move_type = match bits >> 12 {
+            0 => MoveType::Quiet,
+            1 => MoveType::Capture,
+            2 => MoveType::EnPassant,
+            3 => MoveType::CastleKingside,
+            4 => MoveType::CastleQueenside,
+            5 => MoveType::Promotion(PieceKind::Knight),
+            6 => MoveType::Promotion(PieceKind::Bishop),
+            7 => MoveType::Promotion(PieceKind::Rook),
+            8 => MoveType::Promotion(PieceKind::Queen),
+            _ => return None,
+        };
+        // Both 6-bit fields are always in 0..64, so the lookups cannot fail.
+        let from = Square::try_from_index((bits & 0x3F) as u8).unwrap();
+        let to = Square::try_from_index(((bits >> 6) & 0x3F) as u8).unwrap();
+        Some(ChessMove::new(from, to, move_type))
+    }
+
     pub fn from_square(&self) -> String {
         square_to_string(self.from)
     }
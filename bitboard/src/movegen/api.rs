@@ -16,14 +16,67 @@ use crate::position::Position;
 #[derive(Clone, Copy)]
 pub struct SimpleMoveGen;
 
+/// Move generation and legality queries used by the search.
+///
+/// The engine is generic over this trait so an instrumented or reference
+/// generator can be swapped in without touching the search code.
 pub trait MoveGenerator {
+    /// Whether the side to move is in check.
     fn in_check(&self, pos: &Position) -> bool;
+
+    /// All pseudo-legal moves (may leave the own king in check).
+    fn pseudo_moves(&self, pos: &Position) -> MoveList;
+
+    /// All strictly legal moves.
+    fn legal_moves(&self, pos: &Position) -> MoveList;
+
+    /// Pseudo-legal captures and promotions, for quiescence search.
+    fn pseudo_captures(&self, pos: &Position) -> MoveList;
+
+    /// Whether `mv` could have been produced by `pseudo_moves` in `pos`.
+    fn is_pseudo_legal(&self, pos: &Position, mv: &ChessMove) -> bool;
+
+    /// Whether a pseudo-legal `mv` keeps the own king out of check.
+    fn is_legal(&self, pos: &Position, mv: &ChessMove) -> bool;
+
+    /// Whether a legal `mv` puts the opponent in check.
+    fn gives_check(&self, pos: &Position, mv: &ChessMove) -> bool {
+        let mut after = *pos;
+        pos.apply_move_into(mv, &mut after);
+        self.in_check(&after)
+    }
 }
 
 impl MoveGenerator for SimpleMoveGen {
+    #[inline]
     fn in_check(&self, pos: &Position) -> bool {
         crate::movegen::is_in_check(pos, pos.side_to_move)
     }
+
+    #[inline]
+    fn pseudo_moves(&self, pos: &Position) -> MoveList {
+        generate_pseudo_moves_fast(pos)
+    }
+
+    #[inline]
+    fn legal_moves(&self, pos: &Position) -> MoveList {
+        generate_legal_moves_fast(pos)
+    }
+
+    #[inline]
+    fn pseudo_captures(&self, pos: &Position) -> MoveList {
+        generate_pseudo_captures_fast(pos)
+    }
+
+    #[inline]
+    fn is_pseudo_legal(&self, pos: &Position, mv: &ChessMove) -> bool {
+        crate::movegen::is_pseudo_legal(pos, mv)
+    }
+
+    #[inline]
+    fn is_legal(&self, pos: &Position, mv: &ChessMove) -> bool {
+        crate::movegen::is_move_legal_without_making(pos, mv)
+    }
 }
 
 /// Fast zero-allocation move generation using stack-allocated `MoveList`
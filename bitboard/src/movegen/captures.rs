@@ -79,8 +79,8 @@ pub fn generate_pseudo_captures_fast(pos: &Position) -> MoveList {
     }
 
     // King captures
-    let king_bb = pos.pieces.get(Piece::from_parts(us, Some(PieceKind::King)));
-    if let Some(from) = king_bb.squares().next() {
+    if pos.count(Piece::from_parts(us, Some(PieceKind::King))) > 0 {
+        let from = pos.king_square(us);
         let attacks = king_attacks(from) & their_occ;
         for to in attacks.squares() {
             moves.push(ChessMove::new(from, to, MoveType::Capture));
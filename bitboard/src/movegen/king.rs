@@ -14,11 +14,8 @@ pub fn generate_pseudo_king_moves_fast(
     context: &MoveGenContext,
     moves: &mut MoveList,
 ) {
-    let king_bb = pos
-        .pieces
-        .get(Piece::from_parts(context.us, Some(PieceKind::King)));
-
-    if let Some(from) = king_bb.squares().next() {
+    if pos.count(Piece::from_parts(context.us, Some(PieceKind::King))) > 0 {
+        let from = pos.king_square(context.us);
         // Standard king moves
         let valid_moves = king_attacks(from).and(context.not_ours);
         crate::movegen::api::push_moves_from_valid_targets_fast(
@@ -53,11 +50,8 @@ pub fn generate_pseudo_king_moves(
     context: &MoveGenContext,
     moves: &mut Vec<ChessMove>,
 ) {
-    let king_bb = pos
-        .pieces
-        .get(Piece::from_parts(context.us, Some(PieceKind::King)));
-
-    if let Some(from) = king_bb.squares().next() {
+    if pos.count(Piece::from_parts(context.us, Some(PieceKind::King))) > 0 {
+        let from = pos.king_square(context.us);
         // Standard king moves
         let valid_moves = king_attacks(from).and(context.not_ours);
         crate::movegen::api::push_moves_from_valid_targets(pos, context, from, valid_moves, moves);
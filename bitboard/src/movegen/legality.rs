@@ -1,5 +1,6 @@
 use crate::BitBoardMask;
 use crate::Square;
+use crate::bitboard::PAWN_ATTACKS;
 use crate::bitboard::bishop_attacks_from;
 use crate::bitboard::king_attacks;
 use crate::bitboard::knight_attacks;
@@ -13,6 +14,135 @@ use crate::piece::Piece;
 use crate::piece::PieceKind;
 use crate::position::Position;
 
+/// Return true if `mv` is a move the pseudo-legal generator could produce in
+/// `pos`, without generating any moves.
+///
+/// Intended for moves from outside the current move list (TT, killers,
+/// counter-moves, book) before trusting them. The move type must match what
+/// the generator emits: `Capture` only onto an enemy piece, `Promotion` for
+/// every move to the last rank, and castling only when the generator would
+/// offer it. Checks against our own king are not tested here; pair with
+/// `is_move_legal_without_making` for full legality.
+pub fn is_pseudo_legal(pos: &Position, mv: &ChessMove) -> bool {
+    let us = pos.side_to_move;
+    let moving_piece = pos.piece_at_square(mv.from);
+    if mv.from == mv.to || moving_piece == Piece::None || moving_piece.color() != us {
+        return false;
+    }
+
+    let occ = pos.all_pieces();
+    let ours = pos.our_pieces(us);
+    let theirs = pos.their_pieces(us);
+    let to_mask = BitBoardMask::from_square(mv.to);
+    if (ours & to_mask).is_nonempty() {
+        return false;
+    }
+    let target_is_enemy = (theirs & to_mask).is_nonempty();
+
+    let kind = moving_piece.kind();
+    if kind == PieceKind::Pawn {
+        return is_pseudo_legal_pawn_move(pos, mv, us, target_is_enemy);
+    }
+
+    match mv.move_type {
+        MoveType::Quiet | MoveType::Capture => {
+            if (mv.move_type == MoveType::Capture) != target_is_enemy {
+                return false;
+            }
+            let attacks = match kind {
+                PieceKind::Knight => knight_attacks(mv.from),
+                PieceKind::Bishop => bishop_attacks_from(mv.from, occ),
+                PieceKind::Rook => rook_attacks_from(mv.from, occ),
+                PieceKind::Queen => {
+                    bishop_attacks_from(mv.from, occ) | rook_attacks_from(mv.from, occ)
+                }
+                PieceKind::King => king_attacks(mv.from),
+                PieceKind::Pawn => BitBoardMask::empty(),
+            };
+            (attacks & to_mask).is_nonempty()
+        }
+        MoveType::CastleKingside | MoveType::CastleQueenside => {
+            if kind != PieceKind::King {
+                return false;
+            }
+            let kingside = mv.move_type == MoveType::CastleKingside;
+            let (king_from, king_to, rook_from) = match (us, kingside) {
+                (Color::White, true) => (Square::E1, Square::G1, Square::H1),
+                (Color::White, false) => (Square::E1, Square::C1, Square::A1),
+                (Color::Black, true) => (Square::E8, Square::G8, Square::H8),
+                (Color::Black, false) => (Square::E8, Square::C8, Square::A8),
+            };
+            if mv.from != king_from
+                || mv.to != king_to
+                || pos.piece_at_square(rook_from) != Piece::from_parts(us, Some(PieceKind::Rook))
+            {
+                return false;
+            }
+            if kingside {
+                pos.can_castle_kingside(us)
+            } else {
+                pos.can_castle_queenside(us)
+            }
+        }
+        MoveType::Promotion(_) | MoveType::EnPassant => false,
+    }
+}
+
+fn is_pseudo_legal_pawn_move(
+    pos: &Position,
+    mv: &ChessMove,
+    us: Color,
+    target_is_enemy: bool,
+) -> bool {
+    let (push_dir, start_rank, promo_rank): (i8, u8, u8) = match us {
+        Color::White => (8, 1, 7),
+        Color::Black => (-8, 6, 0),
+    };
+    let to_mask = BitBoardMask::from_square(mv.to);
+    let is_push = mv.from.advance(push_dir) == Some(mv.to);
+    let is_double_push = mv.from.rank() == start_rank
+        && mv.from.advance(2 * push_dir) == Some(mv.to)
+        && mv
+            .from
+            .advance(push_dir)
+            .is_some_and(|mid| pos.piece_at_square(mid) == Piece::None);
+    // SAFETY: color index is 0 or 1 and `from` is in 0..64.
+    let attacks = unsafe {
+        *PAWN_ATTACKS
+            .get_unchecked(us as usize)
+            .get_unchecked(mv.from.index())
+    };
+    let is_diagonal = (attacks & to_mask).is_nonempty();
+    let to_empty = pos.piece_at_square(mv.to) == Piece::None;
+    let reaches_last_rank = mv.to.rank() == promo_rank;
+
+    match mv.move_type {
+        MoveType::Quiet => !reaches_last_rank && to_empty && (is_push || is_double_push),
+        MoveType::Capture => !reaches_last_rank && is_diagonal && target_is_enemy,
+        MoveType::Promotion(kind) => {
+            matches!(
+                kind,
+                PieceKind::Knight | PieceKind::Bishop | PieceKind::Rook | PieceKind::Queen
+            ) && reaches_last_rank
+                && ((is_push && to_empty) || (is_diagonal && target_is_enemy))
+        }
+        MoveType::EnPassant => {
+            if pos.ep_square != Some(mv.to) || !is_diagonal || !to_empty {
+                return false;
+            }
+            // The double-pushed enemy pawn sits directly behind the ep square.
+            let captured_sq = match us {
+                Color::White => mv.to.backward(1),
+                Color::Black => mv.to.forward(1),
+            };
+            captured_sq.is_some_and(|sq| {
+                pos.piece_at_square(sq) == Piece::from_parts(us.opposite(), Some(PieceKind::Pawn))
+            })
+        }
+        MoveType::CastleKingside | MoveType::CastleQueenside => false,
+    }
+}
+
 /// Return true if making `m` from `pos` leaves the side to move in check.
 pub fn is_legal(pos: &Position, m: &ChessMove) -> bool {
     // `apply_move_into` overwrites all board/state fields, so start from a
@@ -22,12 +152,7 @@ pub fn is_legal(pos: &Position, m: &ChessMove) -> bool {
 
     // Preserve prior contract: if the mover's king is missing after make-move,
     // this candidate is invalid and must be rejected.
-    if new_pos
-        .pieces
-        .get(Piece::from_parts(pos.side_to_move, Some(PieceKind::King)))
-        .first_square()
-        .is_none()
-    {
+    if new_pos.count(Piece::from_parts(pos.side_to_move, Some(PieceKind::King))) == 0 {
         return false;
     }
 
@@ -38,15 +163,8 @@ pub fn is_legal(pos: &Position, m: &ChessMove) -> bool {
 /// This avoids redundant position copies in tight loops
 pub fn is_legal_fast(original_pos: &Position, pos_after_move: &Position) -> bool {
     // Check if the mover's king is missing after make-move
-    if pos_after_move
-        .pieces
-        .get(Piece::from_parts(
-            original_pos.side_to_move,
-            Some(PieceKind::King),
-        ))
-        .first_square()
-        .is_none()
-    {
+    let king_piece = Piece::from_parts(original_pos.side_to_move, Some(PieceKind::King));
+    if pos_after_move.count(king_piece) == 0 {
         return false;
     }
 
@@ -55,12 +173,11 @@ pub fn is_legal_fast(original_pos: &Position, pos_after_move: &Position) -> bool
 
 pub fn is_in_check(pos: &Position, color: Color) -> bool {
     let king_piece = Piece::from_parts(color, Some(PieceKind::King));
-    let king_sq = match pos.pieces.get(king_piece).first_square() {
-        Some(sq) => sq,
-        None => return false,
-    };
+    if pos.count(king_piece) == 0 {
+        return false;
+    }
 
-    is_square_attacked_by(pos, king_sq, color.opposite())
+    is_square_attacked_by(pos, pos.king_square(color), color.opposite())
 }
 
 fn is_square_attacked_by(pos: &Position, sq: Square, attacker_color: Color) -> bool {
@@ -128,10 +245,10 @@ pub fn is_move_legal_without_making(pos: &Position, mv: &ChessMove) -> bool {
 
     // Find our king
     let king_piece = Piece::from_parts(us, Some(PieceKind::King));
-    let king_sq = match pos.pieces.get(king_piece).first_square() {
-        Some(sq) => sq,
-        None => return false, // No king = illegal position
-    };
+    if pos.count(king_piece) == 0 {
+        return false; // No king = illegal position
+    }
+    let king_sq = pos.king_square(us);
 
     match mv.move_type {
         MoveType::CastleKingside | MoveType::CastleQueenside => {
@@ -162,16 +279,11 @@ fn is_square_attacked_after_king_move(
     them: Color,
 ) -> bool {
     // Simulate occupancy with king on the new square
-    let from_mask = BitBoardMask::from_square(
-        pos.pieces
-            .get(Piece::from_parts(us, Some(PieceKind::King)))
-            .first_square()
-            .unwrap(),
-    );
+    let from_mask = BitBoardMask::from_square(pos.king_square(us));
     let to_mask = BitBoardMask::from_square(king_to);
     let new_occ = (pos.occupancy[OccupancyKind::Both] & !from_mask) | to_mask;
 
-    is_square_attacked_with_occupancy(pos, king_to, them, new_occ)
+    is_square_attacked_with_occupancy(pos, king_to, them, new_occ, to_mask)
 }
 
 /// Check legality for non-king moves by simulating the occupancy change.
@@ -201,8 +313,9 @@ fn check_legality_with_simulated_occupancy(
     // Simulate the move: remove from 'from', add to 'to', remove captured piece
     let new_occ = (pos.occupancy[OccupancyKind::Both] & !from_mask & !captured_sq_mask) | to_mask;
 
-    // Check if our king is attacked with the new occupancy
-    !is_square_attacked_with_occupancy(pos, king_sq, them, new_occ)
+    // Check if our king is attacked with the new occupancy; the captured piece
+    // no longer attacks anything.
+    !is_square_attacked_with_occupancy(pos, king_sq, them, new_occ, captured_sq_mask)
 }
 
 /// Check castling legality (king not in check, not castling through check, not
@@ -234,16 +347,20 @@ fn check_castling_legality(
 
 /// Check if a square is attacked by the opponent with a specific occupancy
 /// bitboard. This allows us to test attacks with simulated board changes
-/// without mutating the position.
+/// without mutating the position. Pieces on `captured` are taken by the move
+/// and no longer attack.
 fn is_square_attacked_with_occupancy(
     pos: &Position,
     sq: Square,
     attacker_color: Color,
     occ: BitBoardMask,
+    captured: BitBoardMask,
 ) -> bool {
-    let attacker_pawns = pos
-        .pieces
-        .get(Piece::from_parts(attacker_color, Some(PieceKind::Pawn)));
+    let live = !captured;
+    let attacker_pawns = live
+        & pos
+            .pieces
+            .get(Piece::from_parts(attacker_color, Some(PieceKind::Pawn)));
 
     // Pawn attacks (independent of occupancy)
     if pawn_attacks_to(sq, attacker_color)
@@ -254,20 +371,23 @@ fn is_square_attacked_with_occupancy(
     }
 
     // Knight attacks (independent of occupancy)
-    let attacker_knights = pos
-        .pieces
-        .get(Piece::from_parts(attacker_color, Some(PieceKind::Knight)));
+    let attacker_knights = live
+        & pos
+            .pieces
+            .get(Piece::from_parts(attacker_color, Some(PieceKind::Knight)));
     if knight_attacks(sq).and(attacker_knights).is_nonempty() {
         return true;
     }
 
     // Bishop/Queen attacks (depend on occupancy)
-    let attacker_bishops = pos
-        .pieces
-        .get(Piece::from_parts(attacker_color, Some(PieceKind::Bishop)));
-    let attacker_queens = pos
-        .pieces
-        .get(Piece::from_parts(attacker_color, Some(PieceKind::Queen)));
+    let attacker_bishops = live
+        & pos
+            .pieces
+            .get(Piece::from_parts(attacker_color, Some(PieceKind::Bishop)));
+    let attacker_queens = live
+        & pos
+            .pieces
+            .get(Piece::from_parts(attacker_color, Some(PieceKind::Queen)));
     if bishop_attacks_from(sq, occ)
         .and(attacker_bishops | attacker_queens)
         .is_nonempty()
@@ -276,9 +396,10 @@ fn is_square_attacked_with_occupancy(
     }
 
     // Rook/Queen attacks (depend on occupancy)
-    let attacker_rooks = pos
-        .pieces
-        .get(Piece::from_parts(attacker_color, Some(PieceKind::Rook)));
+    let attacker_rooks = live
+        & pos
+            .pieces
+            .get(Piece::from_parts(attacker_color, Some(PieceKind::Rook)));
     if rook_attacks_from(sq, occ)
         .and(attacker_rooks | attacker_queens)
         .is_nonempty()
@@ -287,8 +408,9 @@ fn is_square_attacked_with_occupancy(
     }
 
     // King attacks (independent of occupancy)
-    let attacker_king = pos
-        .pieces
-        .get(Piece::from_parts(attacker_color, Some(PieceKind::King)));
+    let attacker_king = live
+        & pos
+            .pieces
+            .get(Piece::from_parts(attacker_color, Some(PieceKind::King)));
     king_attacks(sq).and(attacker_king).is_nonempty()
 }
@@ -0,0 +1,394 @@
+// bitboard/src/packed.rs
+//
+// Compact binary encoding of `Position` for large training/tuning datasets.
+//
+// Layout of a `PackedPosition` (32 bytes, little-endian):
+//   [0..8)   occupancy bitboard
+//   [8..24)  4-bit piece codes, one per occupied square in ascending square
+//            order, low nibble first (piece discriminant 0..=11)
+//   [24]     bit 0: side to move (1 = black), bits 1-4: castling KQkq
+//   [25]     en passant square index, or 64 when absent
+//   [26]     halfmove clock
+//   [27..29) fullmove number
+//   [29..32) reserved, always zero
+//
+// A `PackedRecord` appends a fixed payload to the position (40 bytes total):
+//   [32..34) score (i16, side-to-move perspective)
+//   [34..36) best move (`ChessMove::to_packed`)
+//   [36]     game result (`GameResult`)
+//   [37..40) reserved, always zero
+
+use crate::BitBoardMask;
+use crate::Square;
+use crate::castling::CastlingRights;
+use crate::mov::ChessMove;
+use crate::piece::Color;
+use crate::piece::Piece;
+use crate::position::Position;
+use std::io;
+use std::io::Read;
+use std::io::Write;
+
+pub const PACKED_POSITION_BYTES: usize = 32;
+pub const PACKED_RECORD_BYTES: usize = 40;
+
+const MAX_PACKED_PIECES: u32 = 32;
+const NO_EP_SQUARE: u8 = 64;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PackedPosition {
+    bytes: [u8; PACKED_POSITION_BYTES],
+}
+
+impl PackedPosition {
+    /// Encode a position. Returns `None` if more than 32 pieces are on the
+    /// board, which cannot happen in positions reachable from a legal game.
+    pub fn encode(pos: &Position) -> Option<Self> {
+        let occupancy = pos.all_pieces();
+        if occupancy.count_ones() > MAX_PACKED_PIECES {
+            return None;
+        }
+
+        let mut bytes = [0u8; PACKED_POSITION_BYTES];
+        bytes[0..8].copy_from_slice(&occupancy.0.to_le_bytes());
+
+        for (i, sq) in occupancy.squares().enumerate() {
+            let code = pos.piece_at_square(sq) as u8;
+            bytes[8 + i / 2] |= code << ((i % 2) * 4);
+        }
+
+        let rights = pos.castling_rights;
+        bytes[24] = (pos.side_to_move == Color::Black) as u8
+            | (rights.kingside(Color::White) as u8) << 1
+            | (rights.queenside(Color::White) as u8) << 2
+            | (rights.kingside(Color::Black) as u8) << 3
+            | (rights.queenside(Color::Black) as u8) << 4;
+        bytes[25] = pos.ep_square.map_or(NO_EP_SQUARE, |sq| sq as u8);
+        bytes[26] = pos.halfmove_clock;
+        bytes[27..29].copy_from_slice(&pos.fullmove_number.to_le_bytes());
+
+        Some(Self { bytes })
+    }
+
+    /// Decode back into a full `Position`. Returns `None` if the bytes are
+    /// not a valid encoding (bad piece code, ep square or flag bits).
+    pub fn decode(&self) -> Option<Position> {
+        let b = &self.bytes;
+        let occupancy = BitBoardMask(u64::from_le_bytes(b[0..8].try_into().ok()?));
+        if occupancy.count_ones() > MAX_PACKED_PIECES
+            || b[24] >> 5 != 0
+            || b[25] > NO_EP_SQUARE
+            || b[29..32] != [0, 0, 0]
+        {
+            return None;
+        }
+
+        let mut pos = Position::empty();
+        for (i, sq) in occupancy.squares().enumerate() {
+            let code = (b[8 + i / 2] >> ((i % 2) * 4)) & 0x0F;
+            pos.set_piece(sq, piece_from_code(code)?);
+        }
+
+        pos.side_to_move = if b[24] & 1 != 0 {
+            Color::Black
+        } else {
+            Color::White
+        };
+        pos.castling_rights = CastlingRights {
+            white_kingside: b[24] & (1 << 1) != 0,
+            white_queenside: b[24] & (1 << 2) != 0,
+            black_kingside: b[24] & (1 << 3) != 0,
+            black_queenside: b[24] & (1 << 4) != 0,
+        };
+        pos.ep_square = Square::try_from_index(b[25]);
+        pos.halfmove_clock = b[26];
+        pos.fullmove_number = u16::from_le_bytes([b[27], b[28]]);
+
+        Some(pos)
+    }
+
+    pub const fn from_bytes(bytes: [u8; PACKED_POSITION_BYTES]) -> Self {
+        Self { bytes }
+    }
+
+    pub const fn as_bytes(&self) -> &[u8; PACKED_POSITION_BYTES] {
+        &self.bytes
+    }
+}
+
+const fn piece_from_code(code: u8) -> Option<Piece> {
+    match code {
+        0 => Some(Piece::WhitePawn),
+        1 => Some(Piece::WhiteKnight),
+        2 => Some(Piece::WhiteBishop),
+        3 => Some(Piece::WhiteRook),
+        4 => Some(Piece::WhiteQueen),
+        5 => Some(Piece::WhiteKing),
+        6 => Some(Piece::BlackPawn),
+        7 => Some(Piece::BlackKnight),
+        8 => Some(Piece::BlackBishop),
+        9 => Some(Piece::BlackRook),
+        10 => Some(Piece::BlackQueen),
+        11 => Some(Piece::BlackKing),
+        _ => None,
+    }
+}
+
+/// Final game outcome from White's point of view.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameResult {
+    BlackWin = 0,
+    Draw = 1,
+    WhiteWin = 2,
+}
+
+impl GameResult {
+    pub const fn from_code(code: u8) -> Option<Self> {
+        match code {
+            0 => Some(GameResult::BlackWin),
+            1 => Some(GameResult::Draw),
+            2 => Some(GameResult::WhiteWin),
+            _ => None,
+        }
+    }
+}
+
+/// One labeled training sample: a packed position plus its search payload.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PackedRecord {
+    pub position: PackedPosition,
+    /// Search score in centipawns from the side to move's perspective.
+    pub score: i16,
+    pub result: GameResult,
+    pub best_move: ChessMove,
+}
+
+impl PackedRecord {
+    pub fn to_bytes(&self) -> [u8; PACKED_RECORD_BYTES] {
+        let mut bytes = [0u8; PACKED_RECORD_BYTES];
+        bytes[..PACKED_POSITION_BYTES].copy_from_slice(self.position.as_bytes());
+        bytes[32..34].copy_from_slice(&self.score.to_le_bytes());
+        bytes[34..36].copy_from_slice(&self.best_move.to_packed().to_le_bytes());
+        bytes[36] = self.result as u8;
+        bytes
+    }
+
+    /// Returns `None` if the position, move or result fields are invalid.
+    pub fn from_bytes(bytes: &[u8; PACKED_RECORD_BYTES]) -> Option<Self> {
+        let position = PackedPosition::from_bytes(bytes[..PACKED_POSITION_BYTES].try_into().ok()?);
+        position.decode()?;
+        if bytes[37..40] != [0, 0, 0] {
+            return None;
+        }
+
+        Some(Self {
+            position,
+            score: i16::from_le_bytes([bytes[32], bytes[33]]),
+            best_move: ChessMove::from_packed(u16::from_le_bytes([bytes[34], bytes[35]]))?,
+            result: GameResult::from_code(bytes[36])?,
+        })
+    }
+}
+
+/// Streaming writer for fixed-size `PackedRecord`s.
+///
+/// Wrap the sink in a `BufWriter` when writing to a file; every record is a
+/// single small `write_all`.
+pub struct PackedWriter<W: Write> {
+    inner: W,
+    written: u64,
+}
+
+impl<W: Write> PackedWriter<W> {
+    pub const fn new(inner: W) -> Self {
+        Self { inner, written: 0 }
+    }
+
+    pub fn write_record(&mut self, record: &PackedRecord) -> io::Result<()> {
+        self.inner.write_all(&record.to_bytes())?;
+        self.written += 1;
+        Ok(())
+    }
+
+    /// Number of records written so far.
+    pub const fn records_written(&self) -> u64 {
+        self.written
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+/// Streaming reader for files produced by `PackedWriter`.
+///
+/// Iterates over `io::Result<PackedRecord>`. A clean end of input ends the
+/// iteration; a truncated trailing record or invalid record data is reported
+/// as an `InvalidData`/`UnexpectedEof` error.
+pub struct PackedReader<R: Read> {
+    inner: R,
+}
+
+impl<R: Read> PackedReader<R> {
+    pub const fn new(inner: R) -> Self {
+        Self { inner }
+    }
+
+    /// Read the next record, or `Ok(None)` at a clean end of input.
+    pub fn read_record(&mut self) -> io::Result<Option<PackedRecord>> {
+        let mut buf = [0u8; PACKED_RECORD_BYTES];
+        let mut filled = 0;
+        while filled < PACKED_RECORD_BYTES {
+            match self.inner.read(&mut buf[filled..]) {
+                Ok(0) => break,
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        if filled == 0 {
+            return Ok(None);
+        }
+        if filled < PACKED_RECORD_BYTES {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("truncated packed record: {filled} of {PACKED_RECORD_BYTES} bytes"),
+            ));
+        }
+
+        PackedRecord::from_bytes(&buf)
+            .map(Some)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid packed record"))
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Iterator for PackedReader<R> {
+    type Item = io::Result<PackedRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_record().transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::movegen::generate_legal_moves;
+    use std::io::Cursor;
+
+    const FENS: [&str; 6] = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR w Kq d6 0 3",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 b - - 37 112",
+        "4k3/8/8/8/8/8/8/4K3 w - - 99 300",
+        "8/8/8/8/8/8/8/8 b - - 0 1",
+    ];
+
+    #[test]
+    fn test_packed_position_roundtrip_matches_fen() {
+        for fen in FENS {
+            let pos = Position::from_fen(fen);
+            let packed = PackedPosition::encode(&pos).expect("encodable position");
+            let decoded = packed.decode().expect("decodable position");
+            assert_eq!(decoded.to_fen(), fen);
+            assert_eq!(decoded.zobrist_hash(), pos.zobrist_hash());
+            assert_eq!(
+                decoded.occupancy.get_both(),
+                pos.occupancy.get_both(),
+                "occupancy cache must be rebuilt for {fen}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_packed_position_roundtrip_after_every_legal_move() {
+        let pos = Position::from_fen(FENS[1]);
+        for mv in generate_legal_moves(&pos) {
+            let mut child = pos;
+            child.make_move(&mv);
+            let packed = PackedPosition::encode(&child).unwrap();
+            assert_eq!(packed.decode().unwrap().to_fen(), child.to_fen());
+        }
+    }
+
+    #[test]
+    fn test_packed_position_rejects_corrupt_bytes() {
+        let pos = Position::default();
+        let mut bytes = *PackedPosition::encode(&pos).unwrap().as_bytes();
+        bytes[8] = 0x0F; // piece code 15 is not a piece
+        assert!(PackedPosition::from_bytes(bytes).decode().is_none());
+
+        let mut bytes = *PackedPosition::encode(&pos).unwrap().as_bytes();
+        bytes[25] = 65;
+        assert!(PackedPosition::from_bytes(bytes).decode().is_none());
+    }
+
+    #[test]
+    fn test_move_packing_roundtrip() {
+        for fen in FENS {
+            let pos = Position::from_fen(fen);
+            for mv in generate_legal_moves(&pos) {
+                assert_eq!(ChessMove::from_packed(mv.to_packed()), Some(mv));
+            }
+        }
+        assert_eq!(ChessMove::null().to_packed(), 0);
+        assert!(ChessMove::from_packed(0xF000).is_none());
+    }
+
+    #[test]
+    fn test_record_stream_roundtrip() {
+        let mut writer = PackedWriter::new(Vec::new());
+        let mut expected = Vec::new();
+        for (i, fen) in FENS.iter().enumerate() {
+            let pos = Position::from_fen(fen);
+            let best_move = generate_legal_moves(&pos)
+                .first()
+                .copied()
+                .unwrap_or_else(ChessMove::null);
+            let record = PackedRecord {
+                position: PackedPosition::encode(&pos).unwrap(),
+                score: (i as i16 - 3) * 117,
+                result: GameResult::from_code((i % 3) as u8).unwrap(),
+                best_move,
+            };
+            writer.write_record(&record).unwrap();
+            expected.push(record);
+        }
+        assert_eq!(writer.records_written(), FENS.len() as u64);
+
+        let bytes = writer.into_inner();
+        assert_eq!(bytes.len(), FENS.len() * PACKED_RECORD_BYTES);
+
+        let records: Vec<PackedRecord> = PackedReader::new(Cursor::new(bytes))
+            .collect::<io::Result<_>>()
+            .unwrap();
+        assert_eq!(records, expected);
+        for (record, fen) in records.iter().zip(FENS) {
+            assert_eq!(record.position.decode().unwrap().to_fen(), fen);
+        }
+    }
+
+    #[test]
+    fn test_reader_reports_truncated_record() {
+        let record = PackedRecord {
+            position: PackedPosition::encode(&Position::default()).unwrap(),
+            score: 0,
+            result: GameResult::Draw,
+            best_move: ChessMove::null(),
+        };
+        let bytes = record.to_bytes();
+        let mut reader = PackedReader::new(Cursor::new(bytes[..20].to_vec()));
+        let err = reader.next().unwrap().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+}
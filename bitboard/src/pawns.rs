@@ -0,0 +1,281 @@
+// bitboard/src/pawns.rs
+//
+// Set-wise pawn-structure helpers: fills, spans, passed-pawn masks and
+// outposts. Everything operates on whole bitboards so evaluation terms can be
+// written as a handful of bit operations instead of file/rank loops.
+
+use crate::BitBoardMask;
+use crate::constants::BOARD_SIZE;
+use crate::constants::NUM_SQUARES;
+use crate::piece::Color;
+use crate::tables::file_masks::FILE_MASKS;
+use crate::tables::file_masks::NOT_FILE_A;
+use crate::tables::file_masks::NOT_FILE_H;
+use crate::tables::rank_masks::RANK_3;
+use crate::tables::rank_masks::RANK_4;
+use crate::tables::rank_masks::RANK_5;
+use crate::tables::rank_masks::RANK_6;
+
+/// Smear every set bit towards rank 8 (the bits themselves are included).
+#[must_use]
+pub const fn north_fill(bb: BitBoardMask) -> BitBoardMask {
+    let mut b = bb.0;
+    b |= b << 8;
+    b |= b << 16;
+    b |= b << 32;
+    BitBoardMask(b)
+}
+
+/// Smear every set bit towards rank 1 (the bits themselves are included).
+#[must_use]
+pub const fn south_fill(bb: BitBoardMask) -> BitBoardMask {
+    let mut b = bb.0;
+    b |= b >> 8;
+    b |= b >> 16;
+    b |= b >> 32;
+    BitBoardMask(b)
+}
+
+/// Every file that contains at least one set bit.
+#[must_use]
+pub const fn file_fill(bb: BitBoardMask) -> BitBoardMask {
+    BitBoardMask(north_fill(bb).0 | south_fill(bb).0)
+}
+
+const fn shift_east(bb: BitBoardMask) -> BitBoardMask {
+    BitBoardMask((bb.0 << 1) & NOT_FILE_A.0)
+}
+
+const fn shift_west(bb: BitBoardMask) -> BitBoardMask {
+    BitBoardMask((bb.0 >> 1) & NOT_FILE_H.0)
+}
+
+/// Squares strictly in front of the pawns (from `color`'s point of view) on
+/// their own files.
+#[must_use]
+pub const fn front_span(pawns: BitBoardMask, color: Color) -> BitBoardMask {
+    match color {
+        Color::White => north_fill(BitBoardMask(pawns.0 << 8)),
+        Color::Black => south_fill(BitBoardMask(pawns.0 >> 8)),
+    }
+}
+
+/// Squares strictly behind the pawns (from `color`'s point of view) on their
+/// own files.
+#[must_use]
+pub const fn rear_span(pawns: BitBoardMask, color: Color) -> BitBoardMask {
+    front_span(pawns, color.opposite())
+}
+
+/// Every square the pawns could ever attack while advancing: the front spans
+/// of the adjacent files, starting one rank ahead.
+#[must_use]
+pub const fn attack_span(pawns: BitBoardMask, color: Color) -> BitBoardMask {
+    let front = front_span(pawns, color);
+    BitBoardMask(shift_east(front).0 | shift_west(front).0)
+}
+
+/// Squares attacked by at least one of the pawns.
+#[must_use]
+pub const fn pawn_attacks(pawns: BitBoardMask, color: Color) -> BitBoardMask {
+    let pushed = match color {
+        Color::White => BitBoardMask(pawns.0 << 8),
+        Color::Black => BitBoardMask(pawns.0 >> 8),
+    };
+    BitBoardMask(shift_east(pushed).0 | shift_west(pushed).0)
+}
+
+/// Squares attacked by two pawns at once.
+#[must_use]
+pub const fn pawn_double_attacks(pawns: BitBoardMask, color: Color) -> BitBoardMask {
+    let pushed = match color {
+        Color::White => BitBoardMask(pawns.0 << 8),
+        Color::Black => BitBoardMask(pawns.0 >> 8),
+    };
+    BitBoardMask(shift_east(pushed).0 & shift_west(pushed).0)
+}
+
+const fn generate_adjacent_files() -> [BitBoardMask; BOARD_SIZE] {
+    let mut masks = [BitBoardMask::empty(); BOARD_SIZE];
+    let mut file = 0;
+    while file < BOARD_SIZE {
+        let f = FILE_MASKS[file];
+        masks[file] = BitBoardMask(shift_east(f).0 | shift_west(f).0);
+        file += 1;
+    }
+    masks
+}
+
+/// Files directly left and right of each file (the file itself excluded).
+pub const ADJACENT_FILE_MASKS: [BitBoardMask; BOARD_SIZE] = generate_adjacent_files();
+
+#[derive(Clone, Copy)]
+enum Span {
+    Front,
+    Attack,
+    Passed,
+}
+
+const fn generate_square_table(color: Color, span: Span) -> [BitBoardMask; NUM_SQUARES] {
+    let mut table = [BitBoardMask::empty(); NUM_SQUARES];
+    let mut sq = 0;
+    while sq < NUM_SQUARES {
+        let pawn = BitBoardMask(1u64 << sq);
+        table[sq] = match span {
+            Span::Front => front_span(pawn, color),
+            Span::Attack => attack_span(pawn, color),
+            Span::Passed => BitBoardMask(front_span(pawn, color).0 | attack_span(pawn, color).0),
+        };
+        sq += 1;
+    }
+    table
+}
+
+/// `FRONT_SPAN_MASKS[color][sq]`: squares ahead of a pawn on `sq`, same file.
+pub const FRONT_SPAN_MASKS: [[BitBoardMask; NUM_SQUARES]; 2] = [
+    generate_square_table(Color::White, Span::Front),
+    generate_square_table(Color::Black, Span::Front),
+];
+
+/// `ATTACK_SPAN_MASKS[color][sq]`: squares ahead of a pawn on `sq`, adjacent
+/// files.
+pub const ATTACK_SPAN_MASKS: [[BitBoardMask; NUM_SQUARES]; 2] = [
+    generate_square_table(Color::White, Span::Attack),
+    generate_square_table(Color::Black, Span::Attack),
+];
+
+/// `PASSED_PAWN_MASKS[color][sq]`: a pawn on `sq` is passed when no enemy pawn
+/// stands on any of these squares.
+pub const PASSED_PAWN_MASKS: [[BitBoardMask; NUM_SQUARES]; 2] = [
+    generate_square_table(Color::White, Span::Passed),
+    generate_square_table(Color::Black, Span::Passed),
+];
+
+/// Pawns with no enemy pawn in front of them on their own or adjacent files.
+#[must_use]
+pub const fn passed_pawns(ours: BitBoardMask, theirs: BitBoardMask, color: Color) -> BitBoardMask {
+    let them = color.opposite();
+    let blocked = front_span(theirs, them).0 | attack_span(theirs, them).0;
+    BitBoardMask(ours.0 & !blocked)
+}
+
+/// Pawns with no friendly pawn on an adjacent file.
+#[must_use]
+pub const fn isolated_pawns(pawns: BitBoardMask) -> BitBoardMask {
+    let files = file_fill(pawns);
+    BitBoardMask(pawns.0 & !(shift_east(files).0 | shift_west(files).0))
+}
+
+/// Pawns that share their file with at least one other friendly pawn.
+#[must_use]
+pub const fn doubled_pawns(pawns: BitBoardMask) -> BitBoardMask {
+    let others = front_span(pawns, Color::White).0 | rear_span(pawns, Color::White).0;
+    BitBoardMask(pawns.0 & others)
+}
+
+/// Outpost candidates for `color`: squares on the 4th-6th relative ranks that
+/// are defended by one of our pawns and can never be attacked by an enemy
+/// pawn.
+#[must_use]
+pub const fn outpost_squares(
+    ours: BitBoardMask,
+    theirs: BitBoardMask,
+    color: Color,
+) -> BitBoardMask {
+    let ranks = match color {
+        Color::White => RANK_4.0 | RANK_5.0 | RANK_6.0,
+        Color::Black => RANK_3.0 | RANK_4.0 | RANK_5.0,
+    };
+    let defended = pawn_attacks(ours, color).0;
+    let contested = attack_span(theirs, color.opposite()).0;
+    BitBoardMask(ranks & defended & !contested)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Square;
+
+    fn naive_passed_mask(sq: Square, color: Color) -> u64 {
+        let file = sq.file() as i32;
+        let rank = sq.rank() as i32;
+        let mut mask = 0u64;
+        for f in (file - 1).max(0)..=(file + 1).min(7) {
+            for r in 0..8 {
+                let ahead = match color {
+                    Color::White => r > rank,
+                    Color::Black => r < rank,
+                };
+                if ahead {
+                    mask |= 1u64 << (r * 8 + f);
+                }
+            }
+        }
+        mask
+    }
+
+    #[test]
+    fn test_passed_pawn_masks_match_naive() {
+        for sq in Square::all_array() {
+            for color in [Color::White, Color::Black] {
+                assert_eq!(
+                    PASSED_PAWN_MASKS[color as usize][sq.index()].0,
+                    naive_passed_mask(sq, color),
+                    "{sq:?} {color:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_fills_and_spans() {
+        let e4 = Square::E4.bitboard();
+        assert_eq!(north_fill(e4).0, 0x1010_1010_1000_0000);
+        assert_eq!(south_fill(e4).0, 0x0000_0000_1010_1010);
+        assert_eq!(file_fill(e4), FILE_MASKS[4]);
+        assert_eq!(front_span(e4, Color::White).0, 0x1010_1010_0000_0000);
+        assert_eq!(front_span(e4, Color::Black).0, 0x0000_0000_0010_1010);
+        assert_eq!(attack_span(e4, Color::White).0, 0x2828_2828_0000_0000);
+        assert_eq!(ADJACENT_FILE_MASKS[0], FILE_MASKS[1]);
+        assert_eq!(ADJACENT_FILE_MASKS[7], FILE_MASKS[6]);
+    }
+
+    #[test]
+    fn test_pawn_attacks_match_per_square_table() {
+        let pawns = BitBoardMask(0x0000_0000_2C81_4200);
+        for color in [Color::White, Color::Black] {
+            let mut expected = 0u64;
+            for sq in pawns.squares() {
+                expected |= crate::bitboard::PAWN_ATTACKS[color as usize][sq.index()].0;
+            }
+            assert_eq!(pawn_attacks(pawns, color).0, expected, "{color:?}");
+        }
+    }
+
+    #[test]
+    fn test_structure_sets() {
+        // White: a2, c3, c4, e5; black: d7, f7.
+        let white = BitBoardMask::from_squares(&[Square::A2, Square::C3, Square::C4, Square::E5]);
+        let black = BitBoardMask::from_squares(&[Square::D7, Square::F7]);
+
+        assert_eq!(
+            isolated_pawns(white),
+            BitBoardMask::from_squares(&[Square::A2, Square::C3, Square::C4, Square::E5])
+        );
+        assert_eq!(
+            doubled_pawns(white),
+            BitBoardMask::from_squares(&[Square::C3, Square::C4])
+        );
+        assert_eq!(
+            passed_pawns(white, black, Color::White),
+            BitBoardMask::from_squares(&[Square::A2])
+        );
+        assert_eq!(
+            passed_pawns(black, white, Color::Black),
+            BitBoardMask::empty()
+        );
+        // d5 is defended by c4 and no black pawn can ever attack it.
+        assert!(outpost_squares(white, black, Color::White).contains(Square::D5));
+        assert!(!outpost_squares(white, black, Color::White).contains(Square::B3));
+    }
+}
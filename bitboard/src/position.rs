@@ -37,6 +37,15 @@ pub struct MoveUndo {
     pub prev_fullmove_number: u16,
 }
 
+/// Undo information for `unmake_null_move`. A null move only touches the
+/// side to move, the en passant square and the clocks.
+#[derive(Clone, Copy, Debug)]
+pub struct NullUndo {
+    pub prev_ep_square: Option<Square>,
+    pub prev_halfmove_clock: u8,
+    pub prev_fullmove_number: u16,
+}
+
 #[derive(Clone, Copy, Debug)]
 #[repr(align(64))]
 pub struct Position {
@@ -48,6 +57,10 @@ pub struct Position {
     pub ep_square: Option<Square>,
     pub halfmove_clock: u8,
     pub fullmove_number: u16,
+    /// Cached king square per color, indexed by `Color as usize`.
+    pub king_squares: [Square; 2],
+    /// Cached number of pieces per `Piece::index()`.
+    pub piece_counts: [u8; 12],
 }
 
 impl Default for Position {
@@ -137,10 +150,14 @@ impl Position {
         self.our_pieces(us.opposite())
     }
 
-    fn set_piece(&mut self, sq: Square, piece: Piece) {
+    pub(crate) fn set_piece(&mut self, sq: Square, piece: Piece) {
         let bit = BitBoardMask::from_square(sq);
         *self.pieces.get_mut(piece) |= bit;
         self.piece_on[sq.index()] = piece;
+        self.piece_counts[piece.index()] += 1;
+        if piece.kind() == PieceKind::King {
+            self.king_squares[piece.color() as usize] = sq;
+        }
 
         let color_occupancy = match piece.color() {
             Color::White => OccupancyKind::White,
@@ -151,7 +168,7 @@ impl Position {
         self.occupancy.or_in(OccupancyKind::Both, bit);
     }
 
-    const fn empty() -> Self {
+    pub(crate) const fn empty() -> Self {
         Self {
             pieces: PieceBitboards::new(),
             piece_on: [Piece::None; 64],
@@ -161,9 +178,23 @@ impl Position {
             ep_square: None,
             halfmove_clock: 0,
             fullmove_number: 1,
+            king_squares: [Square::E1, Square::E8],
+            piece_counts: [0; 12],
         }
     }
 
+    /// Square of `color`'s king. O(1): kept up to date by every move.
+    #[inline]
+    pub const fn king_square(&self, color: Color) -> Square {
+        self.king_squares[color as usize]
+    }
+
+    /// Number of `piece`s on the board. O(1): kept up to date by every move.
+    #[inline]
+    pub const fn count(&self, piece: Piece) -> u8 {
+        self.piece_counts[piece.index()]
+    }
+
     pub fn piece_at(&self, sq: Square) -> Option<Piece> {
         let piece = self.piece_on[sq.index()];
         if piece == Piece::None {
@@ -291,6 +322,7 @@ impl Position {
                 // ...removed debug output...
                 *out.pieces.get_mut(captured_piece) &= !cap_mask;
                 out.piece_on[capture_sq.index()] = Piece::None;
+                out.piece_counts[captured_piece.index()] -= 1;
             }
         }
 
@@ -335,6 +367,12 @@ impl Position {
         let bb = out.pieces.get_mut(final_piece);
         *bb |= to_mask;
         out.piece_on[mv.to.index()] = final_piece;
+        if final_piece != moving_piece {
+            out.piece_counts[moving_piece.index()] -= 1;
+            out.piece_counts[final_piece.index()] += 1;
+        } else if moving_piece.kind() == PieceKind::King {
+            out.king_squares[us as usize] = mv.to;
+        }
         // ...removed debug output...
 
         // Update occupancy
@@ -420,6 +458,7 @@ impl Position {
                 let cap_mask = BitBoardMask::from_square(undo.captured_square);
                 *self.pieces.get_mut(captured_piece) &= !cap_mask;
                 self.piece_on[undo.captured_square.index()] = Piece::None;
+                self.piece_counts[captured_piece.index()] -= 1;
             }
         }
 
@@ -460,6 +499,12 @@ impl Position {
         };
         *self.pieces.get_mut(final_piece) |= to_mask;
         self.piece_on[mv.to.index()] = final_piece;
+        if final_piece != moving_piece {
+            self.piece_counts[moving_piece.index()] -= 1;
+            self.piece_counts[final_piece.index()] += 1;
+        } else if moving_piece.kind() == PieceKind::King {
+            self.king_squares[us as usize] = mv.to;
+        }
 
         // Update occupancy
         let white_occupancy = or_color(&self.pieces, Color::White);
@@ -533,12 +578,19 @@ impl Position {
         let from_mask = BitBoardMask::from_square(mv.from);
         *self.pieces.get_mut(moving_piece) |= from_mask;
         self.piece_on[mv.from.index()] = moving_piece;
+        if final_piece != moving_piece {
+            self.piece_counts[final_piece.index()] -= 1;
+            self.piece_counts[moving_piece.index()] += 1;
+        } else if moving_piece.kind() == PieceKind::King {
+            self.king_squares[us as usize] = mv.from;
+        }
 
         // Restore captured piece if any
         if undo.captured_piece != Piece::None {
             let cap_mask = BitBoardMask::from_square(undo.captured_square);
             *self.pieces.get_mut(undo.captured_piece) |= cap_mask;
             self.piece_on[undo.captured_square.index()] = undo.captured_piece;
+            self.piece_counts[undo.captured_piece.index()] += 1;
         }
 
         // Undo castling rook moves
@@ -578,6 +630,41 @@ impl Position {
         self.occupancy[OccupancyKind::Both] = white_occupancy | black_occupancy;
     }
 
+    /// Pass the turn without moving a piece (for null-move pruning).
+    ///
+    /// Flips the side to move, clears the en passant square and advances the
+    /// clocks exactly as a reversible move would, so `zobrist_hash` and FEN of
+    /// the result are consistent. Must not be called while in check.
+    pub fn make_null_move(&mut self) -> NullUndo {
+        debug_assert!(
+            !crate::movegen::is_in_check(self, self.side_to_move),
+            "null move made while in check"
+        );
+
+        let undo = NullUndo {
+            prev_ep_square: self.ep_square,
+            prev_halfmove_clock: self.halfmove_clock,
+            prev_fullmove_number: self.fullmove_number,
+        };
+
+        self.ep_square = None;
+        self.halfmove_clock = self.halfmove_clock.saturating_add(1);
+        if self.side_to_move == Color::Black {
+            self.fullmove_number = self.fullmove_number.saturating_add(1);
+        }
+        self.side_to_move = self.side_to_move.opposite();
+
+        undo
+    }
+
+    /// Unmake a null move using the undo information from `make_null_move`.
+    pub const fn unmake_null_move(&mut self, undo: &NullUndo) {
+        self.side_to_move = self.side_to_move.opposite();
+        self.ep_square = undo.prev_ep_square;
+        self.halfmove_clock = undo.prev_halfmove_clock;
+        self.fullmove_number = undo.prev_fullmove_number;
+    }
+
     const fn update_castling_rights(&mut self, from: Square, to: Square) {
         const fn clear_for_square(rights: &mut CastlingRights, sq: Square) {
             use Square::*;
@@ -733,8 +820,10 @@ impl Position {
         // Find the king square of the side that just moved
         let moving_side = self.side_to_move;
         let king_piece = Piece::from_parts(moving_side, Some(PieceKind::King));
-        let king_bb = tmp_pos.pieces.get(king_piece);
-        let king_sq = king_bb.squares().next()?;
+        if tmp_pos.count(king_piece) == 0 {
+            return None;
+        }
+        let king_sq = tmp_pos.king_square(moving_side);
 
         // Check if the king is now attacked by the opponent
         let board_state = tmp_pos.to_board_state();
@@ -768,6 +857,12 @@ impl Position {
         }
     }
 
+    /// Cheap check that `mv` could have been produced by the pseudo-legal
+    /// move generator for this position. See `movegen::is_pseudo_legal`.
+    pub fn is_pseudo_legal(&self, mv: &ChessMove) -> bool {
+        crate::movegen::is_pseudo_legal(self, mv)
+    }
+
     /// Compute a 64-bit Zobrist hash for this position.
     pub fn zobrist_hash(&self) -> u64 {
         crate::zobrist::compute_zobrist(self)
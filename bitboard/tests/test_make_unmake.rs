@@ -122,3 +122,111 @@ fn test_make_unmake_promotion() {
         assert_eq!(pos.to_fen(), original.to_fen());
     }
 }
+
+#[test]
+fn test_make_unmake_null_move() {
+    let original =
+        Position::from_fen("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3");
+
+    let mut pos = original;
+    let undo = pos.make_null_move();
+    assert_eq!(
+        pos.to_fen(),
+        "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR b KQkq - 1 3"
+    );
+    // The hash must match a position reached without the null move.
+    assert_eq!(
+        pos.zobrist_hash(),
+        Position::from_fen(&pos.to_fen()).zobrist_hash()
+    );
+    assert!(!pos.is_pseudo_legal(&bitboard::mov::ChessMove::null()));
+
+    pos.unmake_null_move(&undo);
+    assert_eq!(pos.to_fen(), original.to_fen());
+    assert_eq!(pos.zobrist_hash(), original.zobrist_hash());
+}
+
+#[test]
+fn test_null_move_as_black_advances_fullmove() {
+    let original = Position::from_fen("4k3/8/8/8/8/8/8/4K3 b - - 7 40");
+
+    let mut pos = original;
+    let undo = pos.make_null_move();
+    assert_eq!(pos.to_fen(), "4k3/8/8/8/8/8/8/4K3 w - - 8 41");
+
+    pos.unmake_null_move(&undo);
+    assert_eq!(pos.to_fen(), original.to_fen());
+}
+
+fn assert_cache_consistent(pos: &Position, context: &str) {
+    use bitboard::piece::Color;
+    use bitboard::piece::Piece;
+    use bitboard::piece::PieceKind;
+
+    for color in [Color::White, Color::Black] {
+        let king = Piece::from_parts(color, Some(PieceKind::King));
+        if let Some(sq) = pos.pieces.get(king).first_square() {
+            assert_eq!(
+                pos.king_square(color),
+                sq,
+                "{color:?} king square {context}"
+            );
+        }
+        for kind in [
+            PieceKind::Pawn,
+            PieceKind::Knight,
+            PieceKind::Bishop,
+            PieceKind::Rook,
+            PieceKind::Queen,
+            PieceKind::King,
+        ] {
+            let piece = Piece::from_parts(color, Some(kind));
+            assert_eq!(
+                pos.count(piece) as u32,
+                pos.pieces.get(piece).count_ones(),
+                "{piece:?} count {context}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_king_squares_and_counts_follow_moves() {
+    let fens = [
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+        "8/P7/8/8/8/8/8/k6K w - - 0 1",
+    ];
+
+    for fen in fens {
+        let original = Position::from_fen(fen);
+        assert_cache_consistent(&original, fen);
+
+        for mv in generate_legal_moves(&original) {
+            let context = format!("after {mv} in {fen}");
+
+            let mut pos = original;
+            let undo = pos.make_move(&mv);
+            assert_cache_consistent(&pos, &context);
+
+            let mut into = Position::default();
+            original.apply_move_into(&mv, &mut into);
+            assert_cache_consistent(&into, &context);
+
+            // One more ply so captures of promoted pieces etc. are covered.
+            for reply in generate_legal_moves(&pos) {
+                let mut child = pos;
+                let reply_undo = child.make_move(&reply);
+                assert_cache_consistent(&child, &context);
+                child.unmake_move(&reply, &reply_undo);
+                assert_cache_consistent(&child, &context);
+            }
+
+            pos.unmake_move(&mv, &undo);
+            assert_cache_consistent(&pos, &context);
+            assert_eq!(pos.king_squares, original.king_squares);
+            assert_eq!(pos.piece_counts, original.piece_counts);
+        }
+    }
+}
@@ -8,6 +8,9 @@ fn test_optimized_legality_check_matches_make_unmake() {
         "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
         "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
         "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        // Capturing the checking piece with a non-king piece.
+        "5r1k/6pp/6N1/8/2Q5/8/8/6K1 b - - 1 1",
+        "4k3/8/8/8/8/8/4r3/3QK3 w - - 0 1",
     ];
 
     for fen in test_positions {
@@ -120,3 +123,14 @@ fn test_optimized_legality_check_pins() {
         }
     }
 }
+
+#[test]
+fn test_optimized_legality_allows_capturing_the_checker() {
+    let pos = Position::from_fen("5r1k/6pp/6N1/8/2Q5/8/8/6K1 b - - 1 1");
+    let capture = pos.parse_uci_move("h7g6").unwrap();
+    assert!(is_move_legal_without_making(&pos, &capture));
+
+    let pos = Position::from_fen("4k3/8/8/8/8/8/4r3/3QK3 w - - 0 1");
+    let capture = pos.parse_uci_move("d1e2").unwrap();
+    assert!(is_move_legal_without_making(&pos, &capture));
+}
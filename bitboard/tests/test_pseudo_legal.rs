@@ -0,0 +1,89 @@
+use bitboard::Square;
+use bitboard::mov::ChessMove;
+use bitboard::mov::MoveType;
+use bitboard::movegen::generate_legal_moves;
+use bitboard::movegen::generate_pseudo_moves_fast;
+use bitboard::piece::PieceKind;
+use bitboard::position::Position;
+
+const TEST_POSITIONS: [&str; 8] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R b KQkq - 0 1",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+    "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+    "r3k2r/8/8/8/8/5q2/8/R3K2R w KQkq - 0 1",
+];
+
+fn all_move_types() -> [MoveType; 9] {
+    [
+        MoveType::Quiet,
+        MoveType::Capture,
+        MoveType::EnPassant,
+        MoveType::CastleKingside,
+        MoveType::CastleQueenside,
+        MoveType::Promotion(PieceKind::Knight),
+        MoveType::Promotion(PieceKind::Bishop),
+        MoveType::Promotion(PieceKind::Rook),
+        MoveType::Promotion(PieceKind::Queen),
+    ]
+}
+
+#[test]
+fn test_is_pseudo_legal_matches_generator_exhaustively() {
+    for fen in TEST_POSITIONS {
+        let pos = Position::from_fen(fen);
+        let generated = generate_pseudo_moves_fast(&pos);
+
+        for from in Square::all_array() {
+            for to in Square::all_array() {
+                for move_type in all_move_types() {
+                    let mv = ChessMove::new(from, to, move_type);
+                    let expected = generated.as_slice().contains(&mv);
+                    assert_eq!(
+                        pos.is_pseudo_legal(&mv),
+                        expected,
+                        "pseudo-legality mismatch for {mv:?} in {fen}"
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_is_pseudo_legal_after_moves() {
+    // Walk one ply deeper so ep squares and lost castling rights are covered.
+    for fen in TEST_POSITIONS {
+        let root = Position::from_fen(fen);
+        for mv in generate_legal_moves(&root) {
+            let mut pos = root;
+            pos.make_move(&mv);
+            let generated = generate_pseudo_moves_fast(&pos);
+            for reply in generated.as_slice() {
+                assert!(
+                    pos.is_pseudo_legal(reply),
+                    "{reply:?} rejected in {}",
+                    pos.to_fen()
+                );
+            }
+            // The parent's moves are almost never valid for the opponent.
+            for stale in generate_legal_moves(&root) {
+                assert_eq!(
+                    pos.is_pseudo_legal(&stale),
+                    generated.as_slice().contains(&stale),
+                    "stale move {stale:?} in {}",
+                    pos.to_fen()
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_is_pseudo_legal_rejects_null_move() {
+    let pos = Position::default();
+    assert!(!pos.is_pseudo_legal(&ChessMove::null()));
+}
@@ -2,8 +2,6 @@ use bitboard::movegen::SimpleMoveGen;
 use bitboard::position::Position;
 use engine::Engine;
 use engine::MaterialEvaluator;
-use engine::NODE_COUNT;
-use std::sync::atomic::Ordering;
 use std::time::Instant;
 
 const BENCH_DEPTH: usize = 6;
@@ -86,11 +84,10 @@ fn main() {
         println!("Position: {}/{} ({})", i + 1, bench_limit, fen);
         let pos = Position::from_fen(fen);
 
-        NODE_COUNT.store(0, Ordering::Relaxed);
         let start = Instant::now();
         let (_best_move, score) = engine.search(&pos, depth, None, None);
         let elapsed = start.elapsed();
-        let nodes = NODE_COUNT.load(Ordering::Relaxed);
+        let nodes = engine.node_count();
         total_nodes = total_nodes.saturating_add(nodes);
 
         println!(
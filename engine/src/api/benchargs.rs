@@ -0,0 +1,28 @@
+/// Depth used by a bare `bench`.
+pub const DEFAULT_BENCH_DEPTH: usize = 6;
+/// Hash size of a bench run in MB; fixed so node counts are comparable.
+pub const DEFAULT_BENCH_HASH_MB: usize = 16;
+
+/// Arguments of `bench [depth] [threads] [hash] [fen-file|default] [json]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BenchArgs {
+    pub depth: usize,
+    pub threads: usize,
+    pub hash_mb: usize,
+    /// FEN file with one position per line; `None` for the built-in set.
+    pub fen_file: Option<String>,
+    /// Print one JSON document instead of the human-readable report.
+    pub json: bool,
+}
+
+impl Default for BenchArgs {
+    fn default() -> Self {
+        Self {
+            depth: DEFAULT_BENCH_DEPTH,
+            threads: 1,
+            hash_mb: DEFAULT_BENCH_HASH_MB,
+            fen_file: None,
+            json: false,
+        }
+    }
+}
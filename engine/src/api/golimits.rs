@@ -1,4 +1,6 @@
-#[derive(Debug, Clone, Copy, Default)]
+use bitboard::mov::ChessMove;
+
+#[derive(Debug, Clone, Default)]
 pub struct GoLimits {
     pub depth: Option<usize>,
     pub movetime_ms: Option<u64>,
@@ -6,6 +8,11 @@ pub struct GoLimits {
     pub btime_ms: Option<u64>,
     pub winc_ms: Option<u64>,
     pub binc_ms: Option<u64>,
+    pub movestogo: Option<u32>,
+    pub nodes: Option<u64>,
+    pub mate: Option<u32>,
+    // root moves to consider; empty means all
+    pub searchmoves: Vec<ChessMove>,
     // flags
     pub ponder: bool,
     pub infinite: bool,
@@ -1,3 +1,4 @@
+pub mod benchargs;
 pub mod golimits;
 pub mod uciapi;
 #[cfg(test)]
@@ -1,10 +1,19 @@
-use crate::SUPPRESS_UCI_INFO;
-use crate::VERBOSE;
+use crate::api::benchargs::BenchArgs;
 use crate::api::golimits::GoLimits;
+use crate::search::MATE_SCORE;
 use crate::search::engine::Engine;
-use crate::search::evaluator::MaterialEvaluator;
-use crate::search::load_node_count;
-use crate::search::reset_node_count;
+use crate::search::engine::MAX_MULTI_PV;
+use crate::search::engine::MAX_THREADS;
+use crate::search::limits::DEFAULT_MAX_DEPTH;
+use crate::search::limits::SearchLimits;
+use crate::search::listener::SearchListener;
+use crate::search::listener::SearchUpdate;
+use crate::search::pv::pv_to_string;
+use crate::search::stats::SearchStats;
+use crate::search::tablebase::MAX_TB_PIECES;
+use crate::search::timeman::DEFAULT_MOVE_OVERHEAD_MS;
+use crate::search::timeman::MAX_MOVE_OVERHEAD_MS;
+use crate::search::timeman::TimeControl;
 use crate::test_data::TEST_CASES;
 use crate::test_data::TestCase;
 use bitboard::movegen::SimpleMoveGen;
@@ -17,21 +26,97 @@ use std::io::BufRead;
 use std::io::Write;
 use std::io::{self};
 use std::sync::Arc;
+use std::sync::Mutex;
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering;
-use std::sync::mpsc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// Writer a search thread sends its `bestmove` to.
+pub type SharedOutput = Arc<Mutex<dyn Write + Send>>;
 
 pub struct CodyApi<E: crate::search::evaluator::Evaluator + Clone + Send + Sync + 'static> {
-    engine: Engine<SimpleMoveGen, E>,
+    /// `None` while the search thread owns it; reach it through `engine()`.
+    engine: Option<Engine<SimpleMoveGen, E>>,
+    /// The search started by the last `go`; hands the engine back on join.
+    search: Option<JoinHandle<Engine<SimpleMoveGen, E>>>,
+    /// Where searches started by the UCI loop send `bestmove`.
+    search_output: SharedOutput,
+    /// `setoption` commands received during a search, applied after it.
+    deferred_options: Vec<String>,
     pub current_pos: Position,
+    /// Keys of the positions before `current_pos` since the last capture or
+    /// pawn move, oldest first.
+    game_history: Vec<u64>,
     limits: GoLimits,
     ponder_enabled: bool,
+    /// `Verbose` option: debug output and full logging.
+    verbose: bool,
+    /// The engine's ponder flag: set for `go ponder`, cleared on `ponderhit`.
     pondering_active: Arc<AtomicBool>,
-    stop: Arc<AtomicBool>, // for future: stop support
+    /// The engine's stop flag, raised by `stop` and `quit`.
+    stop: Arc<AtomicBool>,
+    /// `Threads` and `Hash` as configured, restored after `bench`.
+    threads: usize,
+    hash_mb: usize,
     // Optional log file for UCI diagnostics (IN/OUT)
     log: Option<File>,
 }
 
+/// The UCI front end of a search: prints `info` lines for its updates.
+struct UciListener {
+    out: SharedOutput,
+    log: Option<File>,
+    /// Log `info` lines too, not only `bestmove`.
+    verbose: bool,
+}
+
+/// A `go` running on the search thread: everything it needs besides the
+/// engine.
+struct SearchJob {
+    pos: Position,
+    limits: SearchLimits,
+    stop: Arc<AtomicBool>,
+    pondering: Arc<AtomicBool>,
+    listener: UciListener,
+    verbose: bool,
+}
+
+/// One position's outcome in a bench run.
+struct BenchResult<'a> {
+    fen: &'a str,
+    nodes: u64,
+    time_ms: u64,
+    best_move: String,
+    score: i32,
+}
+
+impl BenchResult<'_> {
+    fn to_json(&self) -> String {
+        format!(
+            "{{\"fen\":\"{}\",\"nodes\":{},\"time_ms\":{},\"bestmove\":\"{}\",\"score\":{}}}",
+            json_escape(self.fen),
+            self.nodes,
+            self.time_ms,
+            json_escape(&self.best_move),
+            self.score
+        )
+    }
+}
+
+fn json_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
 // Default implementation removed: CodyApi::new now requires an evaluator
 // argument.
 
@@ -39,14 +124,13 @@ impl<E: crate::search::evaluator::Evaluator + Clone + Send + Sync + 'static> Cod
     pub fn new(evaluator: E) -> Self {
         let mut engine = Engine::new(65_536, SimpleMoveGen, evaluator);
 
-        // Optimize for multi-core systems: use all available threads (up to 8)
-        let num_threads = std::thread::available_parallelism()
-            .map(|p| std::cmp::min(p.get(), 8))
-            .unwrap_or(1);
-        engine.set_num_threads(num_threads);
+        // Optimize for multi-core systems: one Lazy SMP thread per core
+        let threads = Self::default_threads();
+        engine.set_num_threads(threads);
 
-        // Allocate 256 MB hash table for better cache efficiency on 8-thread search
-        engine.set_hash_size_mb(256);
+        // Allocate 256 MB hash table shared by all search threads
+        let hash_mb = 256;
+        engine.set_hash_size_mb(hash_mb);
 
         // Try to open a log file in append mode; non-fatal if it fails.
         let log = OpenOptions::new()
@@ -55,55 +139,46 @@ impl<E: crate::search::evaluator::Evaluator + Clone + Send + Sync + 'static> Cod
             .open("cody_uci.log")
             .ok();
 
+        let pondering_active = engine.ponder_flag();
+        let stop = engine.stop_flag();
         Self {
-            engine,
+            engine: Some(engine),
+            search: None,
+            search_output: Arc::new(Mutex::new(io::stdout())),
+            deferred_options: Vec::new(),
             current_pos: Position::default(),
+            game_history: Vec::new(),
             limits: GoLimits::default(),
             ponder_enabled: false,
-            pondering_active: Arc::new(AtomicBool::new(false)),
-            stop: Arc::new(AtomicBool::new(false)),
+            verbose: false,
+            pondering_active,
+            stop,
+            threads,
+            hash_mb,
             log,
         }
     }
 
+    fn default_threads() -> usize {
+        std::thread::available_parallelism()
+            .map(|p| p.get().min(MAX_THREADS))
+            .unwrap_or(1)
+    }
+
     // Use crate util for consistent millisecond-precision ISO timestamps.
     fn iso_stamp() -> String {
         crate::util::iso_stamp_ms()
     }
 
-    pub fn run(self) {
+    /// The UCI loop. Searches run on their own thread, so every command,
+    /// `isready` included, is answered while the engine thinks.
+    pub fn run(mut self) {
         let stdin = io::stdin();
-        let stop_flag = self.stop.clone();
-        let pondering_active = self.pondering_active.clone();
-        let (tx, rx) = mpsc::channel::<String>();
-        let worker = std::thread::spawn(move || {
-            self.run_worker(rx);
-        });
+        let mut stdout = io::stdout();
         for line in stdin.lock().lines() {
-            let cmd = match line {
-                Ok(cmd) => cmd,
-                Err(_) => break,
-            };
-            if cmd == "stop" || cmd == "quit" {
-                stop_flag.store(true, Ordering::Relaxed);
-            }
-            if cmd == "ponderhit" && pondering_active.load(Ordering::Relaxed) {
-                stop_flag.store(true, Ordering::Relaxed);
-            }
-            if tx.send(cmd.clone()).is_err() {
-                break;
-            }
-            if cmd == "quit" {
+            let Ok(cmd) = line else {
                 break;
-            }
-        }
-        drop(tx);
-        let _ = worker.join();
-    }
-
-    fn run_worker(mut self, rx: mpsc::Receiver<String>) {
-        let mut stdout = io::stdout();
-        for cmd in rx {
+            };
             // Log incoming command
             self.log_in(&cmd);
             let should_quit = self.dispatch_command(&cmd, &mut stdout);
@@ -112,6 +187,7 @@ impl<E: crate::search::evaluator::Evaluator + Clone + Send + Sync + 'static> Cod
                 break;
             }
         }
+        self.handle_stop();
     }
 
     pub(crate) fn dispatch_command(&mut self, cmd: &str, out: &mut impl Write) -> bool {
@@ -122,20 +198,18 @@ impl<E: crate::search::evaluator::Evaluator + Clone + Send + Sync + 'static> Cod
             Some("isready") => self.handle_isready(out),
             Some("ucinewgame") => self.handle_newgame(out),
             Some("position") => self.handle_position(cmd, out),
-            Some("go") => self.handle_go(cmd, out),
+            Some("go") => {
+                let search_output = Arc::clone(&self.search_output);
+                self.start_go(cmd, search_output);
+            }
             Some("help") => self.handle_help(out),
             Some("ponderhit") => {
                 // Transition from pondering to normal search. Clear the stop flag so
                 // a future "go" (with actual time controls) can proceed.
                 self.stop.store(false, Ordering::Relaxed);
-                // Also clear any lingering ponder/infinite flags in limits so that
-                // subsequent go parsing starts from a clean slate.
-                self.limits.ponder = false;
-                self.limits.infinite = false;
-            }
-            Some("stop") => {
-                self.stop.store(true, Ordering::Relaxed);
+                self.handle_ponderhit();
             }
+            Some("stop") => self.handle_stop(),
             Some("register")
                 // UCI 'register' is used by some GUIs for license management.
                 // Cody does not require registration, so we acknowledge and ignore.
@@ -144,7 +218,10 @@ impl<E: crate::search::evaluator::Evaluator + Clone + Send + Sync + 'static> Cod
                 self.writeln_and_log(out, "info string registration not required");
             }
             Some("bench") => self.handle_bench(cmd, out),
-            Some("quit") => return true,
+            Some("quit") => {
+                self.handle_stop();
+                return true;
+            }
             Some(_) => {
                 self.writeln_and_log(
                     out,
@@ -175,8 +252,18 @@ impl<E: crate::search::evaluator::Evaluator + Clone + Send + Sync + 'static> Cod
     }
 
     #[cfg(test)]
-    pub(crate) const fn current_limits(&self) -> GoLimits {
-        self.limits
+    pub(crate) const fn verbose(&self) -> bool {
+        self.verbose
+    }
+
+    #[cfg(test)]
+    pub(crate) fn tablebase_config(&mut self) -> crate::search::tablebase::TbConfig {
+        self.engine().tablebase_config()
+    }
+
+    #[cfg(test)]
+    pub(crate) fn current_limits(&self) -> GoLimits {
+        self.limits.clone()
     }
 
     fn log_in(&mut self, cmd: &str) {
@@ -198,10 +285,7 @@ impl<E: crate::search::evaluator::Evaluator + Clone + Send + Sync + 'static> Cod
         self.writeln_and_log(out, "");
 
         // Advertise options
-        let max_threads = std::thread::available_parallelism()
-            .map(|n| std::cmp::min(n.get(), 8))
-            .unwrap_or(1);
-        let default_threads = max_threads;
+        let default_threads = Self::default_threads();
 
         self.writeln_and_log(out, "option name Hash type spin default 256 min 1 max 1024");
         self.writeln_and_log(out, "option name Clear Hash type button");
@@ -209,12 +293,32 @@ impl<E: crate::search::evaluator::Evaluator + Clone + Send + Sync + 'static> Cod
             out,
             &format!(
                 "option name Threads type spin default {} min 1 max {}",
-                default_threads, max_threads
+                default_threads, MAX_THREADS
+            ),
+        );
+        self.writeln_and_log(
+            out,
+            &format!("option name MultiPV type spin default 1 min 1 max {MAX_MULTI_PV}"),
+        );
+        self.writeln_and_log(
+            out,
+            &format!(
+                "option name Move Overhead type spin default {DEFAULT_MOVE_OVERHEAD_MS} min 0 max \
+                 {MAX_MOVE_OVERHEAD_MS}"
             ),
         );
         self.writeln_and_log(out, "option name Ponder type check default false");
         self.writeln_and_log(out, "option name Verbose type check default false");
         self.writeln_and_log(out, "option name SyzygyPath type string default");
+        self.writeln_and_log(
+            out,
+            "option name SyzygyProbeDepth type spin default 1 min 1 max 100",
+        );
+        self.writeln_and_log(
+            out,
+            &format!("option name SyzygyProbeLimit type spin default {MAX_TB_PIECES} min 0 max {MAX_TB_PIECES}"),
+        );
+        self.writeln_and_log(out, "option name Syzygy50MoveRule type check default true");
 
         self.writeln_and_log(out, "uciok");
     }
@@ -227,17 +331,27 @@ impl<E: crate::search::evaluator::Evaluator + Clone + Send + Sync + 'static> Cod
         self.writeln_and_log(out, "  position [startpos|fen ...] [moves ...]");
         self.writeln_and_log(
             out,
-            "  go [depth N|movetime MS|wtime|btime|winc|binc|ponder|infinite]",
+            "  go [depth N|movetime MS|nodes N|mate N|wtime|btime|winc|binc|movestogo|\
+             searchmoves ...|ponder|infinite]",
         );
         self.writeln_and_log(out, "  stop");
         self.writeln_and_log(out, "  setoption name <name> [value <value>]");
         self.writeln_and_log(out, "  register [later]");
-        self.writeln_and_log(out, "  bench");
+        self.writeln_and_log(
+            out,
+            "  bench [depth] [threads] [hash] [fen-file|default] [json]",
+        );
         self.writeln_and_log(out, "  quit");
         self.writeln_and_log(out, "  help");
     }
 
+    /// Apply `setoption`. During a search the option is queued and applied
+    /// once the search has ended.
     pub fn handle_setoption(&mut self, cmd: &str) {
+        if self.is_searching() {
+            self.deferred_options.push(cmd.to_string());
+            return;
+        }
         // Parse: setoption name <name...> value <value>
         let parts: Vec<&str> = cmd.split_whitespace().skip(1).collect();
         let mut name_idx: Option<usize> = None;
@@ -261,26 +375,47 @@ impl<E: crate::search::evaluator::Evaluator + Clone + Send + Sync + 'static> Cod
             };
             if name.eq_ignore_ascii_case("hash") {
                 if let Ok(n) = value.parse::<usize>() {
-                    self.engine.set_hash_size_mb(n);
+                    self.hash_mb = n;
+                    self.engine().set_hash_size_mb(n);
                 }
             } else if name.eq_ignore_ascii_case("threads") {
                 if let Ok(n) = value.parse::<usize>() {
-                    self.engine.set_num_threads(n.max(1));
+                    let threads = n.clamp(1, MAX_THREADS);
+                    self.threads = threads;
+                    self.engine().set_num_threads(threads);
+                }
+            } else if name.eq_ignore_ascii_case("move overhead") {
+                if let Ok(ms) = value.parse::<u64>() {
+                    self.engine().set_move_overhead_ms(ms);
+                }
+            } else if name.eq_ignore_ascii_case("multipv") {
+                if let Ok(n) = value.parse::<usize>() {
+                    self.engine().set_multi_pv(n);
                 }
             } else if name.eq_ignore_ascii_case("ponder") {
                 let enable = value.eq_ignore_ascii_case("true");
                 self.ponder_enabled = enable;
             } else if name.eq_ignore_ascii_case("verbose") {
-                let enable = value.eq_ignore_ascii_case("true");
-                VERBOSE.store(enable, Ordering::Relaxed);
+                self.set_verbose(value.eq_ignore_ascii_case("true"));
             } else if name.eq_ignore_ascii_case("syzygypath") {
-                let _ = self.engine.set_tablebase_path(&value);
+                let _ = self.engine().set_tablebase_path(&value);
+            } else if name.eq_ignore_ascii_case("syzygyprobedepth") {
+                if let Ok(depth) = value.parse::<usize>() {
+                    self.engine().set_syzygy_probe_depth(depth);
+                }
+            } else if name.eq_ignore_ascii_case("syzygyprobelimit") {
+                if let Ok(pieces) = value.parse::<usize>() {
+                    self.engine().set_syzygy_probe_limit(pieces);
+                }
+            } else if name.eq_ignore_ascii_case("syzygy50moverule") {
+                self.engine()
+                    .set_syzygy_50_move_rule(value.eq_ignore_ascii_case("true"));
             }
         } else if let Some(ni) = name_idx {
             // Handle buttons (no value field)
             let name = parts[ni + 1..].join(" ");
             if name.eq_ignore_ascii_case("clear hash") {
-                self.engine.clear_state();
+                self.engine().clear_state();
             }
         }
     }
@@ -317,6 +452,7 @@ impl<E: crate::search::evaluator::Evaluator + Clone + Send + Sync + 'static> Cod
         }
         // Always sync self.current_pos to the parsed position before applying moves
         self.current_pos = pos;
+        self.game_history.clear();
 
         // Apply subsequent moves if present
         if let Some(&"moves") = tokens.peek() {
@@ -326,6 +462,10 @@ impl<E: crate::search::evaluator::Evaluator + Clone + Send + Sync + 'static> Cod
                     Some(chess_move) => {
                         let mut new_pos = Position::default();
                         pos.apply_move_into(&chess_move, &mut new_pos);
+                        self.game_history.push(pos.zobrist_hash());
+                        if new_pos.halfmove_clock == 0 {
+                            self.game_history.clear();
+                        }
                         pos = new_pos;
                     }
                     None => {
@@ -334,7 +474,7 @@ impl<E: crate::search::evaluator::Evaluator + Clone + Send + Sync + 'static> Cod
                             mv,
                             pos.to_fen()
                         );
-                        if VERBOSE.load(Ordering::Relaxed) {
+                        if self.verbose {
                             let text =
                                 format!("Failed to parse UCI move {} for pos {}", mv, pos.to_fen());
                             self.writeln_and_log(out, &text);
@@ -351,7 +491,22 @@ impl<E: crate::search::evaluator::Evaluator + Clone + Send + Sync + 'static> Cod
         self.current_pos = pos;
     }
 
+    /// `go`, waiting for the search to end; `bestmove` is written to `out`.
+    /// A ponder search only ends on `stop` or `ponderhit`, which need
+    /// `start_go` instead.
     pub fn handle_go(&mut self, cmd: &str, out: &mut impl Write) {
+        let captured = Arc::new(Mutex::new(Vec::<u8>::new()));
+        self.start_go(cmd, captured.clone());
+        self.wait_for_search();
+        let text = std::mem::take(&mut *captured.lock().expect("search output lock"));
+        out.write_all(&text).unwrap();
+    }
+
+    /// `go`: start the search on its own thread and return at once. The
+    /// search thread writes `bestmove` to `out` when it ends; a ponder search
+    /// holds it back until `stop` or `ponderhit`.
+    pub fn start_go(&mut self, cmd: &str, out: SharedOutput) {
+        self.wait_for_search();
         self.stop.store(false, Ordering::Relaxed);
         self.limits = self.parse_go_limits(cmd);
         // Respect runtime ponder option for "go ponder" handling.
@@ -360,67 +515,86 @@ impl<E: crate::search::evaluator::Evaluator + Clone + Send + Sync + 'static> Cod
         }
         self.pondering_active
             .store(self.limits.ponder, Ordering::Relaxed);
+
+        let mut job = SearchJob {
+            pos: self.current_pos,
+            limits: self.search_limits(&self.limits),
+            stop: Arc::clone(&self.stop),
+            pondering: Arc::clone(&self.pondering_active),
+            listener: UciListener {
+                out,
+                log: self.log.as_ref().and_then(|f| f.try_clone().ok()),
+                verbose: self.verbose,
+            },
+            verbose: self.verbose,
+        };
         // Debug trace: announce parsed limits so UIs / logs can see we've started
         // handling go
-        if VERBOSE.load(Ordering::Relaxed) {
-            self.writeln_and_log(out, &format!("debug: handle_go limits: {:?}", self.limits));
-            out.flush().ok();
+        if self.verbose {
+            job.listener
+                .emit(&format!("debug: handle_go limits: {:?}", self.limits));
         }
 
-        // If the full root move set is tablebase-probeable, trust TB and return
-        // immediately with the exact best move for solved endgames.
-        if let Some(tb_move) = crate::search::tablebase::probe_root_best_move(&self.current_pos) {
-            self.pondering_active.store(false, Ordering::Relaxed);
-            self.writeln_and_log(out, &format!("bestmove {}", tb_move));
-            return;
-        }
+        let mut engine = self
+            .engine
+            .take()
+            .expect("engine returned by the last search");
+        self.search = Some(std::thread::spawn(move || {
+            job.run(&mut engine);
+            engine
+        }));
+    }
 
-        // Use engine's iterative deepening search. We forward the move time limit
-        // and a stop flag so the engine can honor them between depths.
-        let max_depth = self.limits.depth.unwrap_or(64);
-        reset_node_count();
+    /// `stop`: end the search and wait until it has sent its `bestmove`.
+    pub fn handle_stop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        self.wait_for_search();
+    }
 
-        let time_budget = if self.limits.ponder {
-            None
-        } else {
-            self.limits.movetime_ms
-        };
+    /// Turn debug output and full logging on or off; the `Verbose` option.
+    pub fn set_verbose(&mut self, verbose: bool) {
+        self.verbose = verbose;
+        self.engine().set_verbose(verbose);
+    }
 
-        let (bm, _sc) =
-            self.engine
-                .search(&self.current_pos, max_depth, time_budget, Some(&*self.stop));
+    /// `ponderhit`: the expected move was played. The search keeps running,
+    /// now against the clock sent with `go ponder`.
+    pub fn handle_ponderhit(&mut self) {
         self.pondering_active.store(false, Ordering::Relaxed);
+        // Also clear any lingering ponder/infinite flags in limits so that
+        // subsequent go parsing starts from a clean slate.
+        self.limits.ponder = false;
+        self.limits.infinite = false;
+    }
 
-        // Defensive UCI boundary check: never emit an illegal bestmove.
-        // Search should already return legal moves, but this keeps protocol
-        // output safe even if a bug slips through in deeper search code.
-        let legal_moves = generate_legal_moves(&self.current_pos);
-        let selected_bestmove = if bm.is_null() {
-            legal_moves
-                .first()
-                .copied()
-                .unwrap_or_else(bitboard::mov::ChessMove::null)
-        } else if legal_moves.contains(&bm) {
-            bm
-        } else if let Some(fallback) = legal_moves.first().copied() {
-            self.writeln_and_log(
-                out,
-                &format!(
-                    "info string warning: search returned illegal move {}, falling back to {}",
-                    bm, fallback
-                ),
-            );
-            fallback
-        } else {
-            bitboard::mov::ChessMove::null()
-        };
+    /// Whether a search is still running. A finished one is joined.
+    pub fn is_searching(&mut self) -> bool {
+        if self.search.as_ref().is_some_and(JoinHandle::is_finished) {
+            self.wait_for_search();
+        }
+        self.search.is_some()
+    }
 
-        let bm_str = if selected_bestmove.is_null() {
-            "0000".to_string()
-        } else {
-            selected_bestmove.to_string()
-        };
-        self.writeln_and_log(out, &format!("bestmove {}", bm_str));
+    /// Join the search thread, if any, and apply options sent during it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the search thread panicked.
+    pub fn wait_for_search(&mut self) {
+        if let Some(search) = self.search.take() {
+            self.engine = Some(search.join().expect("search thread panicked"));
+            for cmd in std::mem::take(&mut self.deferred_options) {
+                self.handle_setoption(&cmd);
+            }
+        }
+    }
+
+    /// The engine, once any running search has handed it back.
+    fn engine(&mut self) -> &mut Engine<SimpleMoveGen, E> {
+        self.wait_for_search();
+        self.engine
+            .as_mut()
+            .expect("engine returned by the last search")
     }
 
     pub(crate) fn parse_go_limits(&self, cmd: &str) -> GoLimits {
@@ -435,38 +609,41 @@ impl<E: crate::search::evaluator::Evaluator + Clone + Send + Sync + 'static> Cod
                 "btime" => limits.btime_ms = it.next().and_then(|s| s.parse().ok()),
                 "winc" => limits.winc_ms = it.next().and_then(|s| s.parse().ok()),
                 "binc" => limits.binc_ms = it.next().and_then(|s| s.parse().ok()),
+                "movestogo" => limits.movestogo = it.next().and_then(|s| s.parse().ok()),
+                "nodes" => limits.nodes = it.next().and_then(|s| s.parse().ok()),
+                "mate" => limits.mate = it.next().and_then(|s| s.parse().ok()),
+                "searchmoves" => {
+                    // Consume every following token that is a legal move here.
+                    let legal = generate_legal_moves(&self.current_pos);
+                    while let Some(&mv) = it.peek() {
+                        let Some(m) = legal.iter().find(|m| m.to_string() == mv) else {
+                            break;
+                        };
+                        limits.searchmoves.push(*m);
+                        it.next();
+                    }
+                }
                 "ponder" => limits.ponder = true,
                 "infinite" => limits.infinite = true,
                 _ => {}
             }
         }
 
-        // Optional: if no depth/movetime but clocks are provided, allocate a sane
-        // per-move budget. Example heuristic (very rough):
-        if limits.depth.is_none()
+        // In ponder mode with no explicit depth/movetime/clock, think until stop.
+        if limits.ponder
+            && limits.depth.is_none()
             && limits.movetime_ms.is_none()
-            && let (Some(wt), Some(bt)) = (limits.wtime_ms, limits.btime_ms)
+            && self.clock_for_side_to_move(&limits).is_none()
         {
-            // Pick side to move from the current position to decide which clock applies.
-            // Roughly spend 1/30th of the remaining time + increment.
-            let stm_white = self.current_pos.side_to_move == Color::White;
-            let (my_time, my_inc) = if stm_white {
-                (wt, limits.winc_ms.unwrap_or(0))
-            } else {
-                (bt, limits.binc_ms.unwrap_or(0))
-            };
-            let budget = (my_time / 30).max(5) + my_inc.min(100); // cap inc use a bit
-            limits.movetime_ms = Some(budget);
-        }
-
-        // In ponder mode with no explicit depth/movetime, think until stop/ponderhit.
-        if limits.ponder && limits.depth.is_none() && limits.movetime_ms.is_none() {
             limits.infinite = true;
         }
 
         // Fallback: if still no limits and not infinite/ponder, use a sensible default
         if limits.depth.is_none()
             && limits.movetime_ms.is_none()
+            && limits.nodes.is_none()
+            && limits.mate.is_none()
+            && self.clock_for_side_to_move(&limits).is_none()
             && !limits.infinite
             && !limits.ponder
         {
@@ -476,67 +653,169 @@ impl<E: crate::search::evaluator::Evaluator + Clone + Send + Sync + 'static> Cod
         limits
     }
 
-    fn parse_bench_depth(cmd: &str) -> Option<usize> {
-        let toks: Vec<&str> = cmd.split_whitespace().collect();
-        if toks.len() < 2 {
-            return None;
+    /// The side to move's clock from `go`, if it was sent. The opponent's
+    /// clock is not needed.
+    pub(crate) fn clock_for_side_to_move(&self, limits: &GoLimits) -> Option<TimeControl> {
+        let (time, inc) = if self.current_pos.side_to_move == Color::White {
+            (limits.wtime_ms, limits.winc_ms)
+        } else {
+            (limits.btime_ms, limits.binc_ms)
+        };
+        time.map(|remaining_ms| TimeControl {
+            remaining_ms,
+            increment_ms: inc.unwrap_or(0),
+            moves_to_go: limits.movestogo,
+        })
+    }
+
+    /// Translate parsed `go` limits into engine limits. Infinite searches run
+    /// without deadlines until `stop`; ponder searches keep their clock,
+    /// which the engine only starts on `ponderhit`.
+    pub(crate) fn search_limits(&self, limits: &GoLimits) -> SearchLimits {
+        SearchLimits {
+            depth: limits.depth.unwrap_or(DEFAULT_MAX_DEPTH),
+            movetime_ms: limits.movetime_ms,
+            clock: self
+                .clock_for_side_to_move(limits)
+                .filter(|_| !limits.infinite),
+            nodes: limits.nodes,
+            mate: limits.mate,
+            search_moves: limits.searchmoves.clone(),
+            game_history: self.game_history.clone(),
+            ponder: limits.ponder,
         }
+    }
 
-        // Support both "bench 8" and "bench depth 8".
-        if toks[1].eq_ignore_ascii_case("depth") {
-            return toks.get(2).and_then(|s| s.parse::<usize>().ok());
+    /// Parse `bench [depth] [threads] [hash] [fen-file|default] [json]`.
+    /// Numbers fill depth, threads and hash in that order; the first other
+    /// word names the positions. `bench depth N` is accepted as well.
+    pub(crate) fn parse_bench_args(cmd: &str) -> BenchArgs {
+        let mut args = BenchArgs::default();
+        let mut numbers = 0;
+        let mut source_seen = false;
+        for tok in cmd.split_whitespace().skip(1) {
+            if tok.eq_ignore_ascii_case("json") {
+                args.json = true;
+            } else if tok.eq_ignore_ascii_case("depth") && numbers == 0 {
+                continue;
+            } else if let (Ok(n), false) = (tok.parse::<usize>(), source_seen) {
+                match numbers {
+                    0 => args.depth = n,
+                    1 => args.threads = n.clamp(1, MAX_THREADS),
+                    2 => args.hash_mb = n.max(1),
+                    _ => {}
+                }
+                numbers += 1;
+            } else if !source_seen {
+                source_seen = true;
+                if !tok.eq_ignore_ascii_case("default") {
+                    args.fen_file = Some(tok.to_string());
+                }
+            }
         }
+        args
+    }
 
-        toks.get(1).and_then(|s| s.parse::<usize>().ok())
+    /// Bench positions: the built-in set sorted by name, or every non-empty,
+    /// non-comment line of `fen_file`.
+    fn bench_positions(fen_file: Option<&str>) -> io::Result<Vec<String>> {
+        let Some(path) = fen_file else {
+            let mut cases: Vec<&TestCase> = TEST_CASES.iter().collect();
+            cases.sort_by(|a, b| a.name.cmp(b.name));
+            return Ok(cases.iter().map(|c| c.fen.to_string()).collect());
+        };
+        Ok(std::fs::read_to_string(path)?
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::to_string)
+            .collect())
     }
 
+    /// Search a fixed set of positions to a fixed depth and report the node
+    /// total. Single-threaded runs with the default hash are deterministic,
+    /// so the final `Nodes searched` line serves as a search signature.
     pub fn handle_bench(&mut self, cmd: &str, out: &mut impl Write) {
-        let depth = Self::parse_bench_depth(cmd).unwrap_or(6);
-        let prev_verbose = VERBOSE.load(Ordering::Relaxed);
-        let prev_suppress = SUPPRESS_UCI_INFO.load(Ordering::Relaxed);
-        VERBOSE.store(false, Ordering::Relaxed);
-        SUPPRESS_UCI_INFO.store(true, Ordering::Relaxed);
-
-        self.writeln_and_log(out, &format!("Running bench at depth {depth}"));
-
-        // Clone into a Vec so we can sort
-        let mut cases: Vec<&TestCase> = TEST_CASES.iter().collect();
-        cases.sort_by(|a, b| a.name.cmp(b.name)); // alphabetical by name
-        let total_cases = cases.len();
-
-        let mut total_nodes = 0u64;
-        let start_all = std::time::Instant::now();
-
-        for (idx, pos) in cases.into_iter().enumerate() {
-            if idx > 0 {
-                self.writeln_and_log(out, "");
+        let args = Self::parse_bench_args(cmd);
+        let fens = match Self::bench_positions(args.fen_file.as_deref()) {
+            Ok(fens) => fens,
+            Err(e) => {
+                let path = args.fen_file.as_deref().unwrap_or_default();
+                self.writeln_and_log(out, &format!("info string bench: cannot read {path}: {e}"));
+                return;
             }
+        };
+        let engine = self.engine();
+        engine.set_verbose(false);
+        engine.set_num_threads(args.threads);
+        engine.set_hash_size_mb(args.hash_mb);
 
+        if !args.json {
             self.writeln_and_log(
                 out,
-                &format!("Position: {}/{} ({})", idx + 1, total_cases, pos.fen),
+                &format!(
+                    "Running bench: depth {}, threads {}, hash {} MB, {} positions",
+                    args.depth,
+                    args.threads,
+                    args.hash_mb,
+                    fens.len()
+                ),
             );
+        }
 
-            // Isolate benchmark positions so TT/history from prior cases do not
-            // distort timing or node counts on later positions.
-            self.engine.clear_state();
+        let mut results = Vec::with_capacity(fens.len());
+        let mut total_stats = SearchStats::default();
+        let start_all = std::time::Instant::now();
 
-            reset_node_count();
+        for (idx, fen) in fens.iter().enumerate() {
+            if !args.json {
+                if idx > 0 {
+                    self.writeln_and_log(out, "");
+                }
+                self.writeln_and_log(
+                    out,
+                    &format!("Position: {}/{} ({})", idx + 1, fens.len(), fen),
+                );
+            }
 
-            let (bm, _score) = self.engine.search(&pos.position(), depth, None, None);
-            let nodes = load_node_count();
-            total_nodes += nodes;
+            // Isolate benchmark positions so TT/history from prior cases do not
+            // distort timing or node counts on later positions.
+            self.engine().clear_state();
+
+            let start = std::time::Instant::now();
+            let (bm, score) =
+                self.engine()
+                    .search(&Position::from_fen(fen), args.depth, None, None);
+            #[allow(clippy::cast_possible_truncation)]
+            let time_ms = start.elapsed().as_millis() as u64;
+            // Counted per engine, so concurrent searches elsewhere in the
+            // process cannot leak into the signature.
+            let stats = self.engine().search_stats();
+            total_stats.merge(&stats);
 
             let bm_str = if bm.is_null() {
                 "(none)".to_string()
             } else {
                 bm.to_string()
             };
-            self.writeln_and_log(out, &format!("bestmove {}", bm_str));
+            if !args.json {
+                self.writeln_and_log(
+                    out,
+                    &format!("bestmove {bm_str} score {score} nodes {}", stats.main_nodes),
+                );
+            }
+            results.push(BenchResult {
+                fen,
+                nodes: stats.main_nodes,
+                time_ms,
+                best_move: bm_str,
+                score,
+            });
 
             out.flush().unwrap();
         }
 
+        let total_nodes = total_stats.main_nodes;
         let total_time_ms = (start_all.elapsed().as_secs_f64() * 1000.0) as u64;
         let total_nps = if total_time_ms == 0 {
             total_nodes
@@ -544,15 +823,37 @@ impl<E: crate::search::evaluator::Evaluator + Clone + Send + Sync + 'static> Cod
             (total_nodes as f64 / (total_time_ms as f64 / 1000.0)) as u64
         };
 
-        self.writeln_and_log(out, "");
-        self.writeln_and_log(out, "===========================");
-        self.writeln_and_log(out, &format!("Total time (ms) : {total_time_ms}"));
-        self.writeln_and_log(out, &format!("Nodes searched  : {total_nodes}"));
-        self.writeln_and_log(out, &format!("Nodes/second    : {total_nps}"));
+        if args.json {
+            let positions: Vec<String> = results.iter().map(BenchResult::to_json).collect();
+            self.writeln_and_log(
+                out,
+                &format!(
+                    "{{\"depth\":{},\"threads\":{},\"hash_mb\":{},\"nodes\":{},\
+                     \"time_ms\":{},\"nps\":{},\"positions\":[{}]}}",
+                    args.depth,
+                    args.threads,
+                    args.hash_mb,
+                    total_nodes,
+                    total_time_ms,
+                    total_nps,
+                    positions.join(",")
+                ),
+            );
+        } else {
+            self.writeln_and_log(out, "");
+            self.writeln_and_log(out, &format!("info string stats {total_stats}"));
+            self.writeln_and_log(out, "===========================");
+            self.writeln_and_log(out, &format!("Total time (ms) : {total_time_ms}"));
+            self.writeln_and_log(out, &format!("Nodes/second    : {total_nps}"));
+            self.writeln_and_log(out, &format!("Nodes searched  : {total_nodes}"));
+        }
         out.flush().unwrap();
 
-        VERBOSE.store(prev_verbose, Ordering::Relaxed);
-        SUPPRESS_UCI_INFO.store(prev_suppress, Ordering::Relaxed);
+        let (threads, hash_mb, verbose) = (self.threads, self.hash_mb, self.verbose);
+        let engine = self.engine();
+        engine.set_num_threads(threads);
+        engine.set_hash_size_mb(hash_mb);
+        engine.set_verbose(verbose);
     }
 
     pub fn handle_newgame(&mut self, _out: &mut impl Write) {
@@ -561,6 +862,128 @@ impl<E: crate::search::evaluator::Evaluator + Clone + Send + Sync + 'static> Cod
         self.pondering_active.store(false, Ordering::Relaxed);
         self.stop.store(false, Ordering::Relaxed);
         // If your engine has a TT/history, clear them:
-        self.engine.clear_state();
+        self.engine().clear_state();
+    }
+}
+
+/// `info` line for one search update.
+pub(crate) fn uci_info_line(update: &SearchUpdate<'_>) -> String {
+    let score = if update.score.abs() > MATE_SCORE - 100 {
+        let mate_in = if update.score > 0 {
+            (MATE_SCORE - update.score + 1) / 2
+        } else {
+            -(MATE_SCORE + update.score) / 2
+        };
+        format!("mate {mate_in}")
+    } else {
+        format!("cp {}", update.score)
+    };
+    let nps = (u128::from(update.nodes) * 1000)
+        .checked_div(u128::from(update.elapsed_ms))
+        .unwrap_or(0);
+    format!(
+        "info depth {} seldepth {} multipv {} score {}{} nodes {} nps {} hashfull {} \
+         tbhits {} time {} pv {}",
+        update.depth,
+        update.seldepth,
+        update.multipv,
+        score,
+        update.bound.uci_suffix(),
+        update.nodes,
+        nps,
+        update.hashfull,
+        update.tbhits,
+        update.elapsed_ms,
+        pv_to_string(update.pv)
+    )
+}
+
+impl UciListener {
+    fn emit(&mut self, text: &str) {
+        self.write(text);
+        self.log(text);
+    }
+
+    fn write(&self, text: &str) {
+        let mut out = self.out.lock().expect("search output lock");
+        writeln!(out, "{}", text).unwrap();
+        out.flush().ok();
+    }
+
+    fn log(&mut self, text: &str) {
+        if let Some(f) = &mut self.log {
+            let _ = writeln!(f, "{} OUT: {}", crate::util::iso_stamp_ms(), text);
+        }
+    }
+}
+
+impl SearchListener for UciListener {
+    fn on_update(&mut self, update: &SearchUpdate<'_>) {
+        let line = uci_info_line(update);
+        self.write(&line);
+        // File logging is expensive in hot paths; keep it for verbose sessions.
+        if self.verbose {
+            self.log(&line);
+        }
+    }
+}
+
+impl SearchJob {
+    fn run<E>(&mut self, engine: &mut Engine<SimpleMoveGen, E>)
+    where
+        E: crate::search::evaluator::Evaluator + Clone + Send + Sync + 'static,
+    {
+        // Use engine's iterative deepening search; the time limits travel in
+        // the limits and `stop` is the engine's own stop flag.
+        let result = engine.analyse(&self.pos, self.limits.clone(), &mut self.listener);
+        let bm = result.best_move;
+
+        // Defensive UCI boundary check: never emit an illegal bestmove.
+        // Search should already return legal moves, but this keeps protocol
+        // output safe even if a bug slips through in deeper search code.
+        let legal_moves = generate_legal_moves(&self.pos);
+        let selected_bestmove = if bm.is_null() {
+            legal_moves
+                .first()
+                .copied()
+                .unwrap_or_else(bitboard::mov::ChessMove::null)
+        } else if legal_moves.contains(&bm) {
+            bm
+        } else if let Some(fallback) = legal_moves.first().copied() {
+            self.listener.emit(&format!(
+                "info string warning: search returned illegal move {}, falling back to {}",
+                bm, fallback
+            ));
+            fallback
+        } else {
+            bitboard::mov::ChessMove::null()
+        };
+
+        if self.verbose {
+            self.listener
+                .emit(&format!("info string stats {}", engine.search_stats()));
+        }
+
+        let line = match result.ponder_move {
+            _ if selected_bestmove.is_null() => "bestmove 0000".to_string(),
+            Some(ponder) if selected_bestmove == bm => {
+                format!("bestmove {selected_bestmove} ponder {ponder}")
+            }
+            _ => format!("bestmove {selected_bestmove}"),
+        };
+        self.finish(&line);
+    }
+
+    /// Send `bestmove`, first waiting out a ponder search that ended before
+    /// `stop` or `ponderhit`; the protocol forbids sending it earlier.
+    fn finish(&mut self, line: &str) {
+        while self.limits.ponder
+            && self.pondering.load(Ordering::Relaxed)
+            && !self.stop.load(Ordering::Relaxed)
+        {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        self.pondering.store(false, Ordering::Relaxed);
+        self.listener.emit(line);
     }
 }
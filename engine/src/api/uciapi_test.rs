@@ -1,13 +1,18 @@
 // ...existing code...
-use crate::VERBOSE;
+use crate::api::benchargs::BenchArgs;
 use crate::api::uciapi::CodyApi;
+use crate::api::uciapi::uci_info_line;
+use crate::search::MATE_SCORE;
+use crate::search::ScoreBound;
 use crate::search::evaluator::MaterialEvaluator;
+use crate::search::listener::SearchUpdate;
+use crate::search::timeman::TimeControl;
 use bitboard::Square;
-use std::sync::atomic::Ordering;
+use bitboard::position::Position;
 
 #[test]
 fn test_parse_go_limits_ponder_is_infinite_without_time_or_depth() {
-    let api = CodyApi::new(MaterialEvaluator::default());
+    let api = CodyApi::new(MaterialEvaluator);
     let limits = api.parse_go_limits("go ponder");
 
     assert!(limits.ponder);
@@ -18,7 +23,7 @@ fn test_parse_go_limits_ponder_is_infinite_without_time_or_depth() {
 
 #[test]
 fn test_parse_go_limits_bare_go_keeps_default_movetime() {
-    let api = CodyApi::new(MaterialEvaluator::default());
+    let api = CodyApi::new(MaterialEvaluator);
     let limits = api.parse_go_limits("go");
 
     assert_eq!(limits.movetime_ms, Some(1000));
@@ -47,7 +52,7 @@ fn test_command_keyword_uses_exact_first_token() {
 
 #[test]
 fn test_handle_help_lists_allowed_commands() {
-    let api = &mut CodyApi::new(MaterialEvaluator::default());
+    let api = &mut CodyApi::new(MaterialEvaluator);
     let mut out = Vec::<u8>::new();
 
     api.handle_help(&mut out);
@@ -55,12 +60,15 @@ fn test_handle_help_lists_allowed_commands() {
 
     assert!(text.contains("Allowed commands:"));
     assert!(text.contains("  help"));
-    assert!(text.contains("  go [depth N|movetime MS|wtime|btime|winc|binc|ponder|infinite]"));
+    assert!(text.contains(
+        "  go [depth N|movetime MS|nodes N|mate N|wtime|btime|winc|binc|movestogo|\
+         searchmoves ...|ponder|infinite]"
+    ));
 }
 
 #[test]
 fn test_dispatch_register_and_register_later_acknowledged() {
-    let api = &mut CodyApi::new(MaterialEvaluator::default());
+    let api = &mut CodyApi::new(MaterialEvaluator);
     let mut out = Vec::<u8>::new();
 
     let should_quit = api.dispatch_command("register", &mut out);
@@ -78,7 +86,7 @@ fn test_dispatch_register_and_register_later_acknowledged() {
 
 #[test]
 fn test_dispatch_unknown_command_emits_message() {
-    let api = &mut CodyApi::new(MaterialEvaluator::default());
+    let api = &mut CodyApi::new(MaterialEvaluator);
     let mut out = Vec::<u8>::new();
 
     let should_quit = api.dispatch_command("not_a_real_command", &mut out);
@@ -91,7 +99,7 @@ fn test_dispatch_unknown_command_emits_message() {
 
 #[test]
 fn test_dispatch_quit_returns_true() {
-    let api = &mut CodyApi::new(MaterialEvaluator::default());
+    let api = &mut CodyApi::new(MaterialEvaluator);
     let mut out = Vec::<u8>::new();
 
     let should_quit = api.dispatch_command("quit", &mut out);
@@ -101,7 +109,7 @@ fn test_dispatch_quit_returns_true() {
 
 #[test]
 fn test_dispatch_stop_sets_stop_flag() {
-    let api = &mut CodyApi::new(MaterialEvaluator::default());
+    let api = &mut CodyApi::new(MaterialEvaluator);
     let mut out = Vec::<u8>::new();
 
     assert!(!api.stop_requested());
@@ -114,7 +122,7 @@ fn test_dispatch_stop_sets_stop_flag() {
 
 #[test]
 fn test_dispatch_ponderhit_clears_stop_and_ponder_flags() {
-    let api = &mut CodyApi::new(MaterialEvaluator::default());
+    let api = &mut CodyApi::new(MaterialEvaluator);
     let mut out = Vec::<u8>::new();
 
     // Seed internal state so we can validate the reset behavior.
@@ -132,31 +140,111 @@ fn test_dispatch_ponderhit_clears_stop_and_ponder_flags() {
 }
 
 #[test]
-fn test_parse_go_limits_uses_white_time_budget_when_white_to_move() {
-    let api = CodyApi::new(MaterialEvaluator::default());
+fn test_parse_go_limits_uses_white_clock_when_white_to_move() {
+    let api = CodyApi::new(MaterialEvaluator);
     let limits = api.parse_go_limits("go wtime 60000 btime 30000 winc 1000 binc 500");
 
-    assert_eq!(limits.movetime_ms, Some(2100));
+    assert_eq!(limits.movetime_ms, None);
     assert_eq!(limits.wtime_ms, Some(60000));
     assert_eq!(limits.btime_ms, Some(30000));
+    assert_eq!(
+        api.search_limits(&limits).clock,
+        Some(TimeControl {
+            remaining_ms: 60000,
+            increment_ms: 1000,
+            moves_to_go: None,
+        })
+    );
 }
 
 #[test]
-fn test_parse_go_limits_uses_black_time_budget_when_black_to_move() {
-    let api = &mut CodyApi::new(MaterialEvaluator::default());
+fn test_parse_go_limits_uses_black_clock_when_black_to_move() {
+    let api = &mut CodyApi::new(MaterialEvaluator);
     let mut out = std::io::sink();
 
     api.handle_position("position startpos moves e2e4", &mut out);
     let limits = api.parse_go_limits("go wtime 60000 btime 30000 winc 1000 binc 500");
 
-    assert_eq!(limits.movetime_ms, Some(1100));
-    assert_eq!(limits.wtime_ms, Some(60000));
-    assert_eq!(limits.btime_ms, Some(30000));
+    assert_eq!(limits.movetime_ms, None);
+    assert_eq!(
+        api.search_limits(&limits).clock,
+        Some(TimeControl {
+            remaining_ms: 30000,
+            increment_ms: 500,
+            moves_to_go: None,
+        })
+    );
+}
+
+#[test]
+fn test_parse_go_limits_own_clock_is_enough() {
+    let api = CodyApi::new(MaterialEvaluator);
+
+    // Only white's clock: no default movetime, the time manager takes over.
+    let limits = api.parse_go_limits("go wtime 5000");
+    assert_eq!(limits.movetime_ms, None);
+    assert_eq!(
+        api.search_limits(&limits).clock.map(|c| c.remaining_ms),
+        Some(5000)
+    );
+
+    // Only the opponent's clock: fall back to the bare-go default.
+    let limits = api.parse_go_limits("go btime 5000");
+    assert_eq!(limits.movetime_ms, Some(1000));
+    assert_eq!(api.search_limits(&limits).clock, None);
+}
+
+#[test]
+fn test_search_limits_keep_clock_while_pondering() {
+    let api = CodyApi::new(MaterialEvaluator);
+    let limits = api.parse_go_limits("go ponder wtime 60000 btime 60000");
+
+    // The clock is armed on ponderhit, so it must reach the engine.
+    let search = api.search_limits(&limits);
+    assert!(search.ponder);
+    assert_eq!(search.clock.map(|c| c.remaining_ms), Some(60_000));
+    assert_eq!(search.movetime_ms, None);
+}
+
+#[test]
+fn test_parse_go_limits_movestogo_reaches_time_control() {
+    let api = CodyApi::new(MaterialEvaluator);
+    let limits = api.parse_go_limits("go wtime 60000 btime 60000 movestogo 10");
+
+    assert_eq!(limits.movestogo, Some(10));
+    assert_eq!(
+        api.search_limits(&limits).clock.and_then(|c| c.moves_to_go),
+        Some(10)
+    );
+}
+
+#[test]
+fn test_parse_go_limits_nodes_and_mate_replace_default_movetime() {
+    let api = CodyApi::new(MaterialEvaluator);
+
+    let limits = api.parse_go_limits("go nodes 5000");
+    assert_eq!(limits.nodes, Some(5000));
+    assert_eq!(limits.movetime_ms, None);
+
+    let limits = api.parse_go_limits("go mate 3");
+    assert_eq!(limits.mate, Some(3));
+    assert_eq!(limits.movetime_ms, None);
+}
+
+#[test]
+fn test_parse_go_limits_searchmoves_stops_at_next_keyword() {
+    let api = CodyApi::new(MaterialEvaluator);
+    let limits = api.parse_go_limits("go searchmoves e2e4 d2d4 e7e5 depth 3");
+
+    let moves: Vec<String> = limits.searchmoves.iter().map(|m| m.to_string()).collect();
+    assert_eq!(moves, vec!["e2e4".to_string(), "d2d4".to_string()]);
+    // e7e5 is not legal for white: it ends the list and the rest still parses.
+    assert_eq!(limits.depth, Some(3));
 }
 
 #[test]
 fn test_parse_go_limits_infinite_keeps_no_movetime() {
-    let api = CodyApi::new(MaterialEvaluator::default());
+    let api = CodyApi::new(MaterialEvaluator);
     let limits = api.parse_go_limits("go infinite");
 
     assert!(limits.infinite);
@@ -166,7 +254,7 @@ fn test_parse_go_limits_infinite_keeps_no_movetime() {
 
 #[test]
 fn test_parse_go_limits_ponder_with_movetime_not_forced_infinite() {
-    let api = CodyApi::new(MaterialEvaluator::default());
+    let api = CodyApi::new(MaterialEvaluator);
     let limits = api.parse_go_limits("go ponder movetime 50");
 
     assert!(limits.ponder);
@@ -175,19 +263,19 @@ fn test_parse_go_limits_ponder_with_movetime_not_forced_infinite() {
 }
 
 #[test]
-fn test_handle_setoption_verbose_toggles_global_flag() {
-    let api = &mut CodyApi::new(MaterialEvaluator::default());
+fn test_handle_setoption_verbose_toggles_runtime_option() {
+    let api = &mut CodyApi::new(MaterialEvaluator);
 
     api.handle_setoption("setoption name Verbose value true");
-    assert!(VERBOSE.load(Ordering::Relaxed));
+    assert!(api.verbose());
 
     api.handle_setoption("setoption name Verbose value false");
-    assert!(!VERBOSE.load(Ordering::Relaxed));
+    assert!(!api.verbose());
 }
 
 #[test]
 fn test_handle_setoption_ponder_toggles_runtime_option() {
-    let api = &mut CodyApi::new(MaterialEvaluator::default());
+    let api = &mut CodyApi::new(MaterialEvaluator);
 
     assert!(!api.ponder_enabled());
     api.handle_setoption("setoption name Ponder value true");
@@ -199,17 +287,37 @@ fn test_handle_setoption_ponder_toggles_runtime_option() {
 
 #[test]
 fn test_handle_setoption_syzygypath_is_accepted() {
-    let api = &mut CodyApi::new(MaterialEvaluator::default());
+    let api = &mut CodyApi::new(MaterialEvaluator);
 
     // Path may be invalid on CI/dev boxes; this test only validates parsing and
     // command handling stability.
     api.handle_setoption("setoption name SyzygyPath value C:\\tb");
 }
 
+#[test]
+fn test_handle_setoption_syzygy_probe_options() {
+    let api = &mut CodyApi::new(MaterialEvaluator);
+
+    api.handle_setoption("setoption name SyzygyProbeDepth value 4");
+    api.handle_setoption("setoption name SyzygyProbeLimit value 9");
+    api.handle_setoption("setoption name Syzygy50MoveRule value false");
+    let config = api.tablebase_config();
+    assert_eq!(config.probe_depth, 4);
+    // Clamped to the largest tables there are.
+    assert_eq!(config.probe_limit, 7);
+    assert!(!config.rule50);
+
+    api.handle_setoption("setoption name SyzygyProbeDepth value 0");
+    api.handle_setoption("setoption name Syzygy50MoveRule value true");
+    let config = api.tablebase_config();
+    assert_eq!(config.probe_depth, 1);
+    assert!(config.rule50);
+}
+
 #[allow(clippy::collapsible_if)]
 #[test]
 fn test_uci_position_moves_c3d5_state_consistency() {
-    let api = &mut CodyApi::new(MaterialEvaluator::default());
+    let api = &mut CodyApi::new(MaterialEvaluator);
     let mut out = std::io::sink();
     // Simulate: position fen ... moves c3d5
     let fen = "r2q1rk1/1p2bppp/p1npbn2/4p3/P3P3/1NN5/1PP1BPPP/R1BQ1R1K w - - 0 1";
@@ -269,3 +377,78 @@ fn test_uci_position_moves_c3d5_state_consistency() {
     // Some(10), None); assert!(bm.from() != Square::C3, "Engine search
     // should not return a move from C3");
 }
+
+#[test]
+fn test_position_moves_keep_game_history_since_last_irreversible_move() {
+    let mut api = CodyApi::new(MaterialEvaluator);
+    let mut sink = Vec::new();
+    api.handle_position(
+        "position startpos moves e2e3 g8f6 g1f3 f6g8 f3g1",
+        &mut sink,
+    );
+    let history = api
+        .search_limits(&api.parse_go_limits("go depth 1"))
+        .game_history;
+
+    // e2e3 resets the history; the four knight moves follow it.
+    assert_eq!(history.len(), 4);
+    // The knights are back home, so the root repeats the position after e2e3.
+    assert_eq!(history[0], api.current_pos.zobrist_hash());
+    assert!(!history.contains(&Position::default().zobrist_hash()));
+}
+
+#[test]
+fn test_parse_bench_args_defaults_to_single_threaded_builtin_set() {
+    let args = CodyApi::<MaterialEvaluator>::parse_bench_args("bench");
+    assert_eq!(args, BenchArgs::default());
+    assert_eq!(args.threads, 1);
+    assert!(args.fen_file.is_none());
+    assert!(!args.json);
+}
+
+#[test]
+fn test_parse_bench_args_positional_and_legacy_forms() {
+    let args = CodyApi::<MaterialEvaluator>::parse_bench_args("bench 9 2 64 positions.fen json");
+    assert_eq!(args.depth, 9);
+    assert_eq!(args.threads, 2);
+    assert_eq!(args.hash_mb, 64);
+    assert_eq!(args.fen_file.as_deref(), Some("positions.fen"));
+    assert!(args.json);
+
+    let args = CodyApi::<MaterialEvaluator>::parse_bench_args("bench 7 default");
+    assert_eq!(args.depth, 7);
+    assert_eq!(args.threads, 1);
+    assert!(args.fen_file.is_none());
+
+    let args = CodyApi::<MaterialEvaluator>::parse_bench_args("bench depth 5");
+    assert_eq!(args.depth, 5);
+}
+
+#[test]
+fn test_uci_info_line_formats_mates_and_bounds() {
+    let pv = [Position::default().parse_uci_move("e2e4").unwrap()];
+    let mut update = SearchUpdate {
+        depth: 5,
+        seldepth: 7,
+        multipv: 1,
+        score: MATE_SCORE - 3,
+        bound: ScoreBound::Exact,
+        pv: &pv,
+        nodes: 2000,
+        elapsed_ms: 1000,
+        hashfull: 3,
+        tbhits: 0,
+    };
+    let line = uci_info_line(&update);
+    assert!(line.starts_with("info depth 5 seldepth 7 multipv 1 score mate 2 nodes 2000 nps 2000"));
+    assert!(line.ends_with("pv e2e4"));
+
+    update.score = 35;
+    update.bound = ScoreBound::Lower;
+    update.elapsed_ms = 0;
+    let line = uci_info_line(&update);
+    assert!(
+        line.contains("score cp 35 lowerbound nodes 2000 nps 0 "),
+        "{line}"
+    );
+}
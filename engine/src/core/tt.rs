@@ -1,4 +1,9 @@
+use crate::search::MATE_SCORE;
+use crate::search::MAX_SEARCH_PLY;
 use bitboard::mov::ChessMove;
+use std::sync::atomic::AtomicU8;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
 
 #[derive(Clone, Copy, Debug)]
 pub enum TTFlag {
@@ -7,11 +12,14 @@ pub enum TTFlag {
     Upper = 2,
 }
 
+/// Decoded table entry. `value` is relative to the probing node (see
+/// [`score_to_tt`]); `static_eval` is `None` when the storing node did not
+/// compute one.
 #[derive(Clone, Copy, Debug)]
 #[repr(align(32))]
 pub struct TTEntry {
-    pub key: u64,
     pub value: i32,
+    pub static_eval: Option<i32>,
     pub depth: i8,
     pub flag: u8,
     pub best_move: ChessMove,
@@ -20,8 +28,8 @@ pub struct TTEntry {
 impl Default for TTEntry {
     fn default() -> Self {
         TTEntry {
-            key: 0,
             value: 0,
+            static_eval: None,
             depth: -1,
             flag: TTFlag::Upper as u8,
             best_move: ChessMove::null(),
@@ -29,40 +37,187 @@ impl Default for TTEntry {
     }
 }
 
+/// Scores at least this large are mates found within the search horizon.
+const MATE_IN_MAX_PLY: i32 = MATE_SCORE - MAX_SEARCH_PLY as i32;
+
+/// Convert a mate score from "mate in N plies from the root" to "mate in N
+/// plies from this node" before storing it, so the entry stays correct when
+/// the position is reached again at a different ply.
+#[must_use]
+pub const fn score_to_tt(score: i32, ply: usize) -> i32 {
+    if score >= MATE_IN_MAX_PLY {
+        score + ply as i32
+    } else if score <= -MATE_IN_MAX_PLY {
+        score - ply as i32
+    } else {
+        score
+    }
+}
+
+/// Inverse of [`score_to_tt`] for an entry probed at `ply`.
+#[must_use]
+pub const fn score_from_tt(score: i32, ply: usize) -> i32 {
+    if score >= MATE_IN_MAX_PLY {
+        score - ply as i32
+    } else if score <= -MATE_IN_MAX_PLY {
+        score + ply as i32
+    } else {
+        score
+    }
+}
+
+/// One table slot. `data` packs the entry; `check` holds `key ^ data`.
+/// Readers accept an entry only when `check ^ data == key`, so a torn write
+/// by another thread shows up as a miss instead of a corrupt hit, without any
+/// locking.
+#[derive(Default)]
+struct Slot {
+    check: AtomicU64,
+    data: AtomicU64,
+}
+
+/// Slots sharing one cache line; a key may live in any of them.
+const BUCKET_SLOTS: usize = 4;
+
+#[derive(Default)]
+#[repr(align(64))]
+struct Bucket {
+    slots: [Slot; BUCKET_SLOTS],
+}
+
+// `data` layout: bits 0-15 move, 16-23 depth, 24-25 flag + 1 (never zero for
+// an occupied slot), 26-31 generation, 32-47 value, 48-63 static eval.
+const GENERATION_BITS: u32 = 6;
+const GENERATION_MASK: u8 = (1 << GENERATION_BITS) - 1;
+const NO_EVAL: i16 = i16::MIN;
+
+/// Replacement: a stored entry is worth its depth minus this much per search
+/// it has survived.
+const AGE_PENALTY: i32 = 8;
+/// A same-position store may replace an entry up to this much deeper from
+/// the current search, since the newer bound is usually more relevant.
+const SAME_KEY_DEPTH_MARGIN: i32 = 3;
+
+#[allow(clippy::cast_possible_truncation)]
+const fn clamp_i16(v: i32) -> i16 {
+    if v > i16::MAX as i32 {
+        i16::MAX
+    } else if v <= i16::MIN as i32 {
+        i16::MIN + 1
+    } else {
+        v as i16
+    }
+}
+
+#[allow(clippy::cast_sign_loss)]
+fn pack(entry: &TTEntry, generation: u8) -> u64 {
+    let eval = entry.static_eval.map_or(NO_EVAL, clamp_i16);
+    u64::from(entry.best_move.to_packed())
+        | (u64::from(entry.depth as u8) << 16)
+        | (u64::from((entry.flag & 0x3) + 1) << 24)
+        | (u64::from(generation & GENERATION_MASK) << 26)
+        | (u64::from(clamp_i16(entry.value) as u16) << 32)
+        | (u64::from(eval as u16) << 48)
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+fn unpack(data: u64) -> TTEntry {
+    let eval = (data >> 48) as u16 as i16;
+    TTEntry {
+        value: i32::from((data >> 32) as u16 as i16),
+        static_eval: (eval != NO_EVAL).then_some(i32::from(eval)),
+        depth: (data >> 16) as u8 as i8,
+        flag: (((data >> 24) as u8) & 0x3).wrapping_sub(1),
+        best_move: ChessMove::from_packed(data as u16).unwrap_or_else(ChessMove::null),
+    }
+}
+
+#[allow(clippy::cast_possible_truncation)]
+const fn generation_of(data: u64) -> u8 {
+    ((data >> 26) as u8) & GENERATION_MASK
+}
+
+/// Shared transposition table of cache-line buckets. All operations take
+/// `&self` and are lock-free, so every search thread can probe and store
+/// concurrently. Entries are stamped with the generation of the search that
+/// wrote them; older generations are replaced first.
 pub struct TranspositionTable {
-    pub entries: Vec<TTEntry>,
+    buckets: Vec<Bucket>,
     mask: usize,
+    generation: AtomicU8,
 }
 
 impl TranspositionTable {
+    /// Bytes used per entry, for sizing the table from a hash option.
+    pub const ENTRY_BYTES: usize = std::mem::size_of::<Slot>();
+
+    /// Table holding `2^size_pow2` entries (at least one bucket).
     pub fn new(size_pow2: usize) -> Self {
-        let cap = 1usize << size_pow2;
+        let buckets = ((1usize << size_pow2) / BUCKET_SLOTS).max(1);
         TranspositionTable {
-            entries: vec![TTEntry::default(); cap],
-            mask: cap - 1,
+            buckets: (0..buckets).map(|_| Bucket::default()).collect(),
+            mask: buckets - 1,
+            generation: AtomicU8::new(0),
         }
     }
 
-    pub fn clear(&mut self) {
-        for e in &mut self.entries {
-            *e = TTEntry::default();
+    pub fn clear(&self) {
+        for slot in self.buckets.iter().flat_map(|b| &b.slots) {
+            slot.check.store(0, Ordering::Relaxed);
+            slot.data.store(0, Ordering::Relaxed);
         }
+        self.generation.store(0, Ordering::Relaxed);
     }
 
-    #[must_use]
-    pub fn probe(&self, key: u64, depth: i8, alpha: i32, beta: i32) -> Option<TTEntry> {
+    /// Start a new search: entries written from now on are current, and
+    /// everything older ages towards replacement.
+    pub fn new_search(&self) {
+        let next = (self.generation.load(Ordering::Relaxed) + 1) & GENERATION_MASK;
+        self.generation.store(next, Ordering::Relaxed);
+    }
+
+    #[inline]
+    fn current_generation(&self) -> u8 {
+        self.generation.load(Ordering::Relaxed)
+    }
+
+    #[inline]
+    fn bucket(&self, key: u64) -> &Bucket {
         #[allow(clippy::cast_possible_truncation)]
         let idx = (key as usize) & self.mask;
-        let e = self.entries[idx];
+        &self.buckets[idx]
+    }
 
-        if e.key != key {
-            return None;
-        }
+    /// Slot and packed data holding `key`, if any.
+    #[inline]
+    fn find(&self, key: u64) -> Option<(&Slot, u64)> {
+        self.bucket(key).slots.iter().find_map(|slot| {
+            let data = slot.data.load(Ordering::Relaxed);
+            let check = slot.check.load(Ordering::Relaxed);
+            (data != 0 && check ^ data == key).then_some((slot, data))
+        })
+    }
+
+    /// Entry stored for `key` regardless of depth and bounds, with its value
+    /// converted for a node at `ply`. Used for move ordering and the cached
+    /// static eval when the entry cannot cut off.
+    #[must_use]
+    pub fn entry(&self, key: u64, ply: usize) -> Option<TTEntry> {
+        let (_, data) = self.find(key)?;
+        let mut e = unpack(data);
+        e.value = score_from_tt(e.value, ply);
+        Some(e)
+    }
+
+    /// Entry for `key` whose depth and bound allow a cutoff in the window
+    /// `(alpha, beta)` at `ply`.
+    #[must_use]
+    pub fn probe(&self, key: u64, depth: i8, alpha: i32, beta: i32, ply: usize) -> Option<TTEntry> {
+        let e = self.entry(key, ply)?;
         if e.depth < depth {
             return None; // stored value from shallower search; ignore
         }
 
-        // Optimize flag checks with direct equality comparisons
         let flag = e.flag;
         if flag == TTFlag::Exact as u8 {
             return Some(e);
@@ -76,95 +231,297 @@ impl TranspositionTable {
         None
     }
 
-    pub fn store(&mut self, key: u64, value: i32, depth: i8, flag: TTFlag, best_move: ChessMove) {
-        #[allow(clippy::cast_possible_truncation)]
-        let idx = (key as usize) & self.mask;
-        let e = &mut self.entries[idx];
-        // Replacement: prefer deeper entries
-        if e.depth > depth {
-            // keep existing deeper entry
-            return;
+    /// Store the result of a search at `ply`. A mate value in `entry` is
+    /// relative to the root and converted with [`score_to_tt`].
+    ///
+    /// An existing entry for the same position is overwritten unless it is
+    /// from this search and clearly deeper; its move is kept when the new
+    /// result has none. Otherwise the bucket's empty slot or the slot with
+    /// the lowest depth-minus-age is replaced.
+    pub fn store(&self, key: u64, ply: usize, entry: &TTEntry) {
+        let generation = self.current_generation();
+        let mut entry = *entry;
+        entry.value = score_to_tt(entry.value, ply);
+
+        let slot = if let Some((slot, old)) = self.find(key) {
+            let prev = unpack(old);
+            if generation_of(old) == generation
+                && i32::from(prev.depth) > i32::from(entry.depth) + SAME_KEY_DEPTH_MARGIN
+            {
+                return;
+            }
+            if entry.best_move.is_null() {
+                entry.best_move = prev.best_move;
+            }
+            if entry.static_eval.is_none() {
+                entry.static_eval = prev.static_eval;
+            }
+            slot
+        } else {
+            self.victim(key, generation)
+        };
+
+        let data = pack(&entry, generation);
+        slot.data.store(data, Ordering::Relaxed);
+        slot.check.store(key ^ data, Ordering::Relaxed);
+    }
+
+    /// Slot to overwrite for a new position: the first empty one, else the
+    /// one with the lowest depth after the age penalty.
+    fn victim(&self, key: u64, generation: u8) -> &Slot {
+        let slots = &self.bucket(key).slots;
+        slots
+            .iter()
+            .min_by_key(|slot| {
+                let data = slot.data.load(Ordering::Relaxed);
+                if data == 0 {
+                    return i32::MIN;
+                }
+                let age = generation.wrapping_sub(generation_of(data)) & GENERATION_MASK;
+                i32::from(unpack(data).depth) - AGE_PENALTY * i32::from(age)
+            })
+            .unwrap_or(&slots[0])
+    }
+
+    /// Best move of the exact entry stored for `key`, regardless of depth.
+    /// Used to extend a principal variation past where the search stopped
+    /// tracking it; callers must still verify the move is legal.
+    #[must_use]
+    pub fn exact_move(&self, key: u64) -> Option<ChessMove> {
+        let (_, data) = self.find(key)?;
+        let e = unpack(data);
+        if e.flag == TTFlag::Exact as u8 && !e.best_move.is_null() {
+            Some(e.best_move)
+        } else {
+            None
         }
-        e.key = key;
-        e.value = value;
-        e.depth = depth;
-        e.flag = flag as u8;
-        e.best_move = best_move;
     }
 
-    /// Approximate hash occupancy in per-mille, similar to UCI `hashfull`.
+    /// Approximate hash occupancy in per-mille, as UCI `hashfull`: the share
+    /// of entries written by the current search.
     ///
     /// We sample up to the first 1000 entries to keep this cheap enough for
     /// periodic info reporting.
     #[must_use]
     pub fn hashfull_per_mille(&self) -> u16 {
-        let sample_size = self.entries.len().min(1000);
-        if sample_size == 0 {
+        let generation = self.current_generation();
+        let sample = self
+            .buckets
+            .iter()
+            .take(1000 / BUCKET_SLOTS)
+            .flat_map(|b| &b.slots);
+
+        let (mut used, mut total) = (0usize, 0usize);
+        for slot in sample {
+            let data = slot.data.load(Ordering::Relaxed);
+            total += 1;
+            if data != 0 && generation_of(data) == generation {
+                used += 1;
+            }
+        }
+        if total == 0 {
             return 0;
         }
 
-        let used = self
-            .entries
-            .iter()
-            .take(sample_size)
-            .filter(|e| e.key != 0)
-            .count();
-
         #[allow(clippy::cast_possible_truncation)]
-        let result = ((used * 1000) / sample_size) as u16;
+        let result = ((used * 1000) / total) as u16;
         result
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::TTEntry;
     use super::TTFlag;
     use super::TranspositionTable;
+    use crate::search::MATE_SCORE;
     use bitboard::mov::ChessMove;
 
+    fn entry(value: i32, depth: i8, flag: TTFlag) -> TTEntry {
+        TTEntry {
+            value,
+            static_eval: None,
+            depth,
+            flag: flag as u8,
+            best_move: ChessMove::null(),
+        }
+    }
+
     #[test]
     fn test_probe_exact_returns_entry() {
-        let mut tt = TranspositionTable::new(4);
+        let tt = TranspositionTable::new(4);
         let key = 0x1234_5678_9abc_def0;
-        let bm = ChessMove::null();
-        tt.store(key, 42, 6, TTFlag::Exact, bm);
+        tt.store(key, 0, &entry(42, 6, TTFlag::Exact));
 
-        let hit = tt.probe(key, 4, -100, 100).expect("expected exact hit");
+        let hit = tt.probe(key, 4, -100, 100, 0).expect("expected exact hit");
         assert_eq!(hit.value, 42);
         assert_eq!(hit.flag, TTFlag::Exact as u8);
     }
 
     #[test]
     fn test_probe_lower_respects_beta_cutoff() {
-        let mut tt = TranspositionTable::new(4);
+        let tt = TranspositionTable::new(4);
         let key = 0x1111_2222_3333_4444;
-        tt.store(key, 250, 5, TTFlag::Lower, ChessMove::null());
+        tt.store(key, 0, &entry(250, 5, TTFlag::Lower));
 
-        assert!(tt.probe(key, 5, -100, 200).is_some());
-        assert!(tt.probe(key, 5, -100, 300).is_none());
+        assert!(tt.probe(key, 5, -100, 200, 0).is_some());
+        assert!(tt.probe(key, 5, -100, 300, 0).is_none());
     }
 
     #[test]
     fn test_probe_upper_respects_alpha_cutoff() {
-        let mut tt = TranspositionTable::new(4);
+        let tt = TranspositionTable::new(4);
         let key = 0x9999_aaaa_bbbb_cccc;
-        tt.store(key, -180, 5, TTFlag::Upper, ChessMove::null());
+        tt.store(key, 0, &entry(-180, 5, TTFlag::Upper));
 
-        assert!(tt.probe(key, 5, -100, 100).is_some());
-        assert!(tt.probe(key, 5, -250, 100).is_none());
+        assert!(tt.probe(key, 5, -100, 100, 0).is_some());
+        assert!(tt.probe(key, 5, -250, 100, 0).is_none());
     }
 
     #[test]
     fn test_store_keeps_deeper_entry() {
-        let mut tt = TranspositionTable::new(2);
+        let tt = TranspositionTable::new(2);
         let key = 0x55aa_55aa_55aa_55aa;
-        tt.store(key, 10, 8, TTFlag::Exact, ChessMove::null());
-        tt.store(key, 99, 4, TTFlag::Exact, ChessMove::null());
+        tt.store(key, 0, &entry(10, 8, TTFlag::Exact));
+        tt.store(key, 0, &entry(99, 4, TTFlag::Exact));
 
         let hit = tt
-            .probe(key, 8, -1_000, 1_000)
+            .probe(key, 8, -1_000, 1_000, 0)
             .expect("expected stored entry");
         assert_eq!(hit.value, 10);
         assert_eq!(hit.depth, 8);
     }
+
+    #[test]
+    fn test_entry_round_trips_move_eval_and_negative_values() {
+        use bitboard::Square;
+        use bitboard::mov::MoveType;
+        use bitboard::piece::PieceKind;
+
+        let tt = TranspositionTable::new(4);
+        let key = 0x0123_4567_89ab_cdef;
+        let mv = ChessMove::new(
+            Square::B7,
+            Square::A8,
+            MoveType::Promotion(PieceKind::Knight),
+        );
+        let stored = TTEntry {
+            static_eval: Some(-417),
+            best_move: mv,
+            ..entry(-29_990, -1, TTFlag::Lower)
+        };
+        tt.store(key, 0, &stored);
+
+        let e = tt
+            .probe(key, -1, -30_000, -30_000, 0)
+            .expect("stored entry");
+        assert_eq!(e.value, -29_990);
+        assert_eq!(e.static_eval, Some(-417));
+        assert_eq!(e.depth, -1);
+        assert_eq!(e.flag, TTFlag::Lower as u8);
+        assert_eq!(e.best_move, mv);
+        assert!(tt.probe(key ^ 1, -1, -30_000, 30_000, 0).is_none());
+    }
+
+    #[test]
+    fn test_mate_scores_are_relative_to_the_storing_node() {
+        let tt = TranspositionTable::new(4);
+        let key = 0xdead_beef_0000_0001;
+        // Mate in 5 plies from the root, found at ply 3: mate in 2 from here.
+        tt.store(key, 3, &entry(MATE_SCORE - 5, 4, TTFlag::Exact));
+        assert_eq!(tt.entry(key, 3).unwrap().value, MATE_SCORE - 5);
+        // Reached again at ply 7, the same mate is 9 plies from the root.
+        assert_eq!(tt.entry(key, 7).unwrap().value, MATE_SCORE - 9);
+
+        let loss_key = key ^ 0xff00;
+        tt.store(loss_key, 2, &entry(-(MATE_SCORE - 6), 4, TTFlag::Exact));
+        assert_eq!(tt.entry(loss_key, 0).unwrap().value, -(MATE_SCORE - 4));
+
+        // Ordinary scores are untouched.
+        tt.store(key, 9, &entry(123, 4, TTFlag::Exact));
+        assert_eq!(tt.entry(key, 1).unwrap().value, 123);
+    }
+
+    #[test]
+    fn test_bucket_keeps_several_positions() {
+        // One bucket: keys colliding on the index must not evict each other.
+        let tt = TranspositionTable::new(2);
+        let keys = [0x10u64 << 32, 0x20 << 32, 0x30 << 32, 0x40 << 32];
+        for (i, &key) in keys.iter().enumerate() {
+            #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+            tt.store(key, 0, &entry(i as i32, 3, TTFlag::Exact));
+        }
+        for (i, &key) in keys.iter().enumerate() {
+            #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+            let expected = i as i32;
+            assert_eq!(tt.entry(key, 0).map(|e| e.value), Some(expected));
+        }
+    }
+
+    #[test]
+    fn test_stale_deep_entries_are_replaced_first() {
+        let tt = TranspositionTable::new(2);
+        let old_deep = 0x1u64 << 40;
+        tt.store(old_deep, 0, &entry(1, 20, TTFlag::Exact));
+
+        // Several searches later the bucket fills with shallow fresh entries.
+        for _ in 0..4 {
+            tt.new_search();
+        }
+        for k in 2..=4u64 {
+            tt.store(k << 40, 0, &entry(0, 2, TTFlag::Exact));
+        }
+        tt.store(5u64 << 40, 0, &entry(0, 2, TTFlag::Exact));
+
+        assert!(tt.entry(old_deep, 0).is_none());
+        for k in 2..=5u64 {
+            assert!(tt.entry(k << 40, 0).is_some(), "key {k}");
+        }
+
+        // An old entry for the same position is overwritten even if deeper.
+        tt.store(2u64 << 40, 0, &entry(7, 9, TTFlag::Exact));
+        tt.new_search();
+        tt.store(2u64 << 40, 0, &entry(8, 1, TTFlag::Upper));
+        assert_eq!(tt.entry(2u64 << 40, 0).unwrap().value, 8);
+    }
+
+    #[test]
+    fn test_hashfull_counts_current_generation_only() {
+        let tt = TranspositionTable::new(10);
+        assert_eq!(tt.hashfull_per_mille(), 0);
+        for k in 0..1024u64 {
+            tt.store(k, 0, &entry(0, 1, TTFlag::Exact));
+        }
+        assert!(tt.hashfull_per_mille() > 900);
+
+        tt.new_search();
+        assert_eq!(tt.hashfull_per_mille(), 0);
+        for k in 0..128u64 {
+            tt.store(k, 0, &entry(0, 1, TTFlag::Exact));
+        }
+        let full = tt.hashfull_per_mille();
+        assert!((100..=150).contains(&full), "hashfull {full}");
+    }
+
+    #[test]
+    fn test_concurrent_stores_never_yield_foreign_entries() {
+        let tt = TranspositionTable::new(6);
+        std::thread::scope(|scope| {
+            for t in 0..4u64 {
+                let tt = &tt;
+                scope.spawn(move || {
+                    for i in 0..20_000u64 {
+                        // Value encodes the key so a torn read would be caught.
+                        let key = (i * 4 + t).wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1;
+                        #[allow(clippy::cast_possible_truncation)]
+                        let value = i32::from((key >> 52) as i16);
+                        tt.store(key, 0, &entry(value, 1, TTFlag::Exact));
+                        if let Some(e) = tt.probe(key, 0, -100_000, 100_000, 0) {
+                            assert_eq!(e.value, value);
+                        }
+                    }
+                });
+            }
+        });
+    }
 }
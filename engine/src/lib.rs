@@ -12,13 +12,6 @@ mod perft_integration_test;
 
 // ...existing code...
 pub use crate::search::engine::Engine;
-pub use crate::search::engine::NODE_COUNT;
 pub use crate::search::evaluator::MaterialEvaluator;
 pub use crate::test_data::TEST_CASES;
 pub use crate::test_data::TestCase;
-// Global runtime verbose flag. Can be toggled via UCI setoption or command-line --verbose.
-use std::sync::atomic::AtomicBool;
-// Removed unused Ordering import
-pub static VERBOSE: AtomicBool = AtomicBool::new(false);
-// Temporarily suppress per-depth UCI info during synthetic benchmarks.
-pub static SUPPRESS_UCI_INFO: AtomicBool = AtomicBool::new(false);
@@ -2,7 +2,6 @@
 #![allow(non_snake_case)]
 
 use bitboard::position::Position;
-use engine::VERBOSE;
 use engine::api::uciapi::CodyApi;
 use engine::util;
 use std::env;
@@ -32,14 +31,14 @@ fn main() {
         util::run_perft_benchmark(&pos, depth);
     } else {
         // UCI mode or handle flags
-        for a in &args {
-            if a == "--verbose" || a.eq_ignore_ascii_case("-v") {
-                VERBOSE.store(true, std::sync::atomic::Ordering::Relaxed);
-            }
-        }
-
         use engine::MaterialEvaluator;
-        let api = CodyApi::new(MaterialEvaluator::default());
+        let mut api = CodyApi::new(MaterialEvaluator);
+        if args
+            .iter()
+            .any(|a| a == "--verbose" || a.eq_ignore_ascii_case("-v"))
+        {
+            api.set_verbose(true);
+        }
         api.run();
     }
 }
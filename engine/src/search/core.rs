@@ -1,79 +1,128 @@
-use crate::VERBOSE;
 use crate::core::arena::Arena;
+use crate::core::tt::TTEntry;
 use crate::core::tt::TTFlag;
 use crate::core::tt::TranspositionTable;
+use crate::search::cuckoo::reversible_move_between;
 use crate::search::evaluator::Evaluator;
 use crate::search::evaluator::evaluate_for_side_to_move;
+use crate::search::ordering::SearchHeuristics;
+use crate::search::ordering::TriedMoves;
+use crate::search::ordering::pick_best_move;
+use crate::search::pv::PvTable;
 use crate::search::quiescence::quiescence_with_arena;
 use crate::search::see::compute_see;
-use crate::util;
-use bitboard::MoveList;
+use crate::search::stats::SearchStats;
+use crate::search::tablebase::TbProbe;
 use bitboard::mov::ChessMove;
 use bitboard::mov::MoveType;
 use bitboard::movegen::MoveGenerator;
-use bitboard::movegen::generate_pseudo_moves_fast;
-use bitboard::movegen::is_move_legal_without_making;
-use bitboard::piece::Color;
 use bitboard::piece::Piece;
-use bitboard::piece::PieceKind;
+use bitboard::position::Position;
 use std::cell::Cell;
-use std::fs::OpenOptions;
-use std::io::Write;
 use std::sync::atomic::AtomicU64;
-use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering;
 
-pub static NODE_COUNT: AtomicU64 = AtomicU64::new(0);
-pub static SELDEPTH_MAX: AtomicUsize = AtomicUsize::new(0);
-pub static TB_HITS: AtomicU64 = AtomicU64::new(0);
 const NODE_COUNT_FLUSH_BATCH: u64 = 1024;
 
 thread_local! {
+    // Nodes this thread has searched but not yet added to its engine's
+    // `SearchCounters`. A thread searches for one engine at a time and
+    // flushes before its search returns, so the batch always belongs to the
+    // counters it is flushed into.
     static LOCAL_NODE_COUNT: Cell<u64> = const { Cell::new(0) };
 }
 
-pub fn reset_node_count() {
-    NODE_COUNT.store(0, Ordering::Relaxed);
-    LOCAL_NODE_COUNT.with(|pending| pending.set(0));
+/// Counters shared by all threads of one engine's search. Nodes arrive in
+/// batches through a thread-local count, so the hot path stays off the
+/// shared cache line; the total lags each thread by at most one batch.
+#[derive(Debug, Default)]
+pub struct SearchCounters {
+    nodes: AtomicU64,
+    tb_hits: AtomicU64,
 }
 
-pub fn flush_local_node_count() {
-    LOCAL_NODE_COUNT.with(|pending| {
-        let local = pending.get();
-        if local > 0 {
-            NODE_COUNT.fetch_add(local, Ordering::Relaxed);
-            pending.set(0);
-        }
-    });
-}
+impl SearchCounters {
+    /// Zero the counters and the calling thread's pending batch.
+    pub fn reset(&self) {
+        self.nodes.store(0, Ordering::Relaxed);
+        self.tb_hits.store(0, Ordering::Relaxed);
+        LOCAL_NODE_COUNT.with(|pending| pending.set(0));
+    }
 
-pub fn increment_node_count() {
-    LOCAL_NODE_COUNT.with(|pending| {
-        let next = pending.get() + 1;
-        if next >= NODE_COUNT_FLUSH_BATCH {
-            NODE_COUNT.fetch_add(next, Ordering::Relaxed);
-            pending.set(0);
-        } else {
-            pending.set(next);
-        }
-    });
-}
+    pub fn increment_nodes(&self) {
+        LOCAL_NODE_COUNT.with(|pending| {
+            let next = pending.get() + 1;
+            if next >= NODE_COUNT_FLUSH_BATCH {
+                self.nodes.fetch_add(next, Ordering::Relaxed);
+                pending.set(0);
+            } else {
+                pending.set(next);
+            }
+        });
+    }
 
-pub fn load_node_count() -> u64 {
-    flush_local_node_count();
-    NODE_COUNT.load(Ordering::Relaxed)
-}
+    /// Add the calling thread's pending batch to the total.
+    pub fn flush_local_nodes(&self) {
+        LOCAL_NODE_COUNT.with(|pending| {
+            let local = pending.get();
+            if local > 0 {
+                self.nodes.fetch_add(local, Ordering::Relaxed);
+                pending.set(0);
+            }
+        });
+    }
+
+    /// Nodes flushed so far, without the threads' pending batches.
+    pub fn flushed_nodes(&self) -> u64 {
+        self.nodes.load(Ordering::Relaxed)
+    }
+
+    /// Nodes so far, including the calling thread's pending batch.
+    pub fn nodes(&self) -> u64 {
+        self.flush_local_nodes();
+        self.flushed_nodes()
+    }
 
-pub fn reset_seldepth(initial_ply: usize) {
-    SELDEPTH_MAX.store(initial_ply, Ordering::Relaxed);
+    pub fn add_tb_hits(&self, hits: u64) {
+        self.tb_hits.fetch_add(hits, Ordering::Relaxed);
+    }
+
+    pub fn tb_hits(&self) -> u64 {
+        self.tb_hits.load(Ordering::Relaxed)
+    }
 }
 
-pub fn update_seldepth(ply: usize) {
-    SELDEPTH_MAX.fetch_max(ply, Ordering::Relaxed);
+/// Whether the clock runs: not while `pondering` is still set.
+pub fn clock_running(pondering: Option<&std::sync::atomic::AtomicBool>) -> bool {
+    pondering.is_none_or(|flag| !flag.load(Ordering::Relaxed))
 }
 
-pub fn current_seldepth() -> usize {
-    SELDEPTH_MAX.load(Ordering::Relaxed)
+/// Whether an external stop, the time budget or the node budget ends the
+/// search. The time budget is ignored while `pondering` is set; the node
+/// budget is checked against `counters` without the pending batches.
+pub fn search_limit_reached(
+    stop: Option<&std::sync::atomic::AtomicBool>,
+    pondering: Option<&std::sync::atomic::AtomicBool>,
+    time_budget_ms: Option<u64>,
+    start_time: Option<&std::time::Instant>,
+    node_limit: Option<u64>,
+    counters: &SearchCounters,
+) -> bool {
+    if let Some(stopflag) = stop
+        && stopflag.load(Ordering::Relaxed)
+    {
+        return true;
+    }
+    if let (Some(mt), Some(start)) = (time_budget_ms, start_time)
+        && clock_running(pondering)
+    {
+        #[allow(clippy::cast_possible_truncation)]
+        let elapsed = start.elapsed().as_millis() as u64;
+        if elapsed >= mt {
+            return true;
+        }
+    }
+    node_limit.is_some_and(|limit| counters.flushed_nodes() >= limit)
 }
 
 // Positive large value used to detect mate scores. Keep consistent with UCI
@@ -89,287 +138,345 @@ const RFP_MARGIN_PER_DEPTH: i32 = 80;
 const FUTILITY_MARGIN_D1: i32 = 250;
 const FUTILITY_MARGIN_D2: i32 = 500;
 const FUTILITY_MARGIN_D3: i32 = 800;
-const MAX_SEARCH_PLY: usize = 128;
+// Extensions are only granted while ply < EXTENSION_PLY_FACTOR * root depth,
+// so checks and singular moves cannot deepen a line without bound.
+const EXTENSION_PLY_FACTOR: usize = 2;
+const CHECK_EXTENSION: i32 = 1;
+// Singular extension: the TT move is verified by searching the others at
+// reduced depth against a margin below its TT value.
+const SINGULAR_MIN_DEPTH: usize = 6;
+const SINGULAR_TT_DEPTH_MARGIN: usize = 3;
+const SINGULAR_MARGIN_PER_DEPTH: i32 = 2;
+const SINGULAR_EXTENSION: i32 = 1;
+// Negative extension for a TT move that is not singular but already fails high.
+const SINGULAR_NEGATIVE_EXTENSION: i32 = -1;
+// Internal iterative reduction: a node without a hash move is searched one
+// ply shallower, since its move ordering is poor. Internal iterative
+// deepening, which runs a reduced search to find a move first, is kept
+// behind USE_IID for comparison.
+const IIR_MIN_DEPTH: usize = 4;
+const USE_IID: bool = false;
+const IID_MIN_DEPTH: usize = 5;
+const IID_REDUCTION: usize = 2;
+// ProbCut: a capture that beats beta by PROBCUT_MARGIN in a qsearch and in
+// a search PROBCUT_REDUCTION plies shallower is trusted to cut at full depth.
+const PROBCUT_MIN_DEPTH: usize = 5;
+const PROBCUT_MARGIN: i32 = 200;
+const PROBCUT_REDUCTION: usize = 4;
+pub const MAX_SEARCH_PLY: usize = 128;
 pub const MAX_REPETITION_HISTORY: usize = MAX_SEARCH_PLY + 4;
 
-fn is_threefold_repetition(
-    key: u64,
-    repetition_history: &[u64; MAX_REPETITION_HISTORY],
-    repetition_len: usize,
-) -> bool {
-    let mut count = 0usize;
-    for seen in repetition_history.iter().take(repetition_len) {
-        if *seen == key {
-            count += 1;
-            if count >= 3 {
-                return true;
-            }
+/// Whether a reported score is exact or only a bound (aspiration fail).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScoreBound {
+    Exact,
+    /// Fail high: the true score is at least this value.
+    Lower,
+    /// Fail low: the true score is at most this value.
+    Upper,
+}
+
+impl ScoreBound {
+    /// Bound keyword of a UCI `score`, with its leading space.
+    pub const fn uci_suffix(self) -> &'static str {
+        match self {
+            ScoreBound::Exact => "",
+            ScoreBound::Lower => " lowerbound",
+            ScoreBound::Upper => " upperbound",
         }
     }
-    false
 }
 
-pub struct SearchHeuristics {
-    killer_moves: [[ChessMove; 2]; MAX_SEARCH_PLY],
-    history: [[i32; 64]; 64],
+// Context struct to reduce function parameter count
+pub struct SearchContext<'a, M: MoveGenerator, E: Evaluator> {
+    pub movegen: &'a M,
+    pub evaluator: &'a E,
+    pub tt: &'a TranspositionTable,
+    pub heuristics: &'a mut SearchHeuristics,
+    pub pv: &'a mut PvTable,
+    pub stats: &'a mut SearchStats,
+    pub stop: Option<&'a std::sync::atomic::AtomicBool>,
+    /// Set while pondering; the time budget applies once it is cleared.
+    pub pondering: Option<&'a std::sync::atomic::AtomicBool>,
+    pub time_budget_ms: Option<u64>,
+    pub start_time: Option<&'a std::time::Instant>,
+    pub node_limit: Option<u64>,
+    /// Node and tablebase-hit totals shared with the other search threads.
+    pub counters: &'a SearchCounters,
+    /// Syzygy probing inside the tree; `None` when off for this search.
+    pub tablebase: Option<TbProbe<'a>>,
+    /// Depth of the current root iteration; bounds extensions.
+    pub root_depth: usize,
 }
 
-impl Default for SearchHeuristics {
-    fn default() -> Self {
-        Self {
-            killer_moves: [[ChessMove::null(); 2]; MAX_SEARCH_PLY],
-            history: [[0; 64]; 64],
-        }
-    }
+/// Search window (alpha-beta bounds) for a single search node.
+pub struct SearchWindow {
+    pub alpha: i32,
+    pub beta: i32,
 }
 
-impl SearchHeuristics {
-    pub fn new() -> Self {
-        Self::default()
-    }
-
-    fn score_move(&self, pos: &bitboard::position::Position, mv: &ChessMove, ply: usize) -> i32 {
-        let mut score = 0;
-
-        // Tactical moves first.
-        score += match mv.move_type {
-            MoveType::Capture | MoveType::EnPassant => 100_000 + mvv_lva_score(pos, mv),
-            MoveType::Promotion(kind) => {
-                90_000
-                    + match kind {
-                        PieceKind::Queen => 900,
-                        PieceKind::Rook => 500,
-                        PieceKind::Bishop => 330,
-                        PieceKind::Knight => 320,
-                        _ => 0,
-                    }
-            }
-            _ => 0,
-        };
+/// Position keys from the last irreversible game move down to the current
+/// node. Children push their key before being searched and pop it after, so
+/// one buffer serves the whole tree.
+pub struct RepetitionState {
+    pub history: Vec<u64>,
+    /// Index of the search root in `history`; earlier keys are game history.
+    pub root: usize,
+    /// Index of the first entry played after the last null move on the path.
+    pub null_barrier: usize,
+}
 
-        // Then killer moves for quiet move ordering.
-        if ply < MAX_SEARCH_PLY {
-            if self.killer_moves[ply][0] == *mv {
-                score += 80_000;
-            } else if self.killer_moves[ply][1] == *mv {
-                score += 70_000;
-            }
+impl RepetitionState {
+    /// State for a search from the position with `root_key`, reached through
+    /// `game_history` (oldest first, excluding the root).
+    pub fn new(game_history: &[u64], root_key: u64) -> Self {
+        let mut history = Vec::with_capacity(game_history.len() + MAX_REPETITION_HISTORY);
+        history.extend_from_slice(game_history);
+        history.push(root_key);
+        Self {
+            history,
+            root: game_history.len(),
+            null_barrier: 0,
         }
+    }
 
-        // History heuristic for remaining quiet ordering.
-        score + self.history[mv.from.index()][mv.to.index()]
+    /// Plies we may look back from the current node: bounded by the last
+    /// irreversible move and the last null move on the path.
+    fn reversible_plies(&self, halfmove_clock: u8) -> usize {
+        let current = self.history.len().saturating_sub(1);
+        usize::from(halfmove_clock).min(current.saturating_sub(self.null_barrier))
     }
 
-    fn update_on_beta_cutoff(&mut self, ply: usize, mv: ChessMove, depth: usize) {
-        // Killer/history are most useful for quiet moves.
-        if matches!(
-            mv.move_type,
-            MoveType::Capture | MoveType::EnPassant | MoveType::Promotion(_)
-        ) {
-            return;
+    /// Whether the current node (the last key in `history`) is a draw by
+    /// repetition: a repeat of a position after the root, or the third
+    /// occurrence of a game position.
+    pub fn is_repetition(&self, halfmove_clock: u8) -> bool {
+        let Some((&key, earlier)) = self.history.split_last() else {
+            return false;
+        };
+        let current = earlier.len();
+        let mut count = 0usize;
+        for i in (4..=self.reversible_plies(halfmove_clock)).step_by(2) {
+            let idx = current - i;
+            if earlier[idx] == key {
+                count += 1;
+                if idx > self.root || count >= 2 {
+                    return true;
+                }
+            }
         }
+        false
+    }
 
-        if ply < MAX_SEARCH_PLY && self.killer_moves[ply][0] != mv {
-            self.killer_moves[ply][1] = self.killer_moves[ply][0];
-            self.killer_moves[ply][0] = mv;
+    /// Whether the side to move at `pos` (the last key in `history`, `ply`
+    /// plies from the root) can reach a repetition with one reversible move,
+    /// so the node is worth at least a draw.
+    pub fn has_upcoming_repetition(&self, pos: &Position, ply: usize) -> bool {
+        let Some((&key, earlier)) = self.history.split_last() else {
+            return false;
+        };
+        let current = earlier.len();
+        for i in (3..=self.reversible_plies(pos.halfmove_clock)).step_by(2) {
+            let idx = current - i;
+            let Some((s1, s2)) = reversible_move_between(pos, key, earlier[idx]) else {
+                continue;
+            };
+            if ply > i {
+                return true;
+            }
+            // The repeated position is the root or game history: the move
+            // must be ours, and the position must already have occurred
+            // twice for the repetition to be a draw.
+            let piece = if pos.piece_on[s1.index()] == Piece::None {
+                pos.piece_on[s2.index()]
+            } else {
+                pos.piece_on[s1.index()]
+            };
+            if piece.color() != pos.side_to_move {
+                continue;
+            }
+            let target = earlier[idx];
+            if earlier[..idx]
+                .iter()
+                .rev()
+                .skip(1)
+                .step_by(2)
+                .any(|&k| k == target)
+            {
+                return true;
+            }
         }
-
-        let bonus = (depth * depth) as i32;
-        let from = mv.from.index();
-        let to = mv.to.index();
-        self.history[from][to] = (self.history[from][to] + bonus).min(50_000);
+        false
     }
 }
 
-/// Instead of one big sort, we call this in a loop during search.
-/// It finds the best move from 'target_index' to the end and swaps it to
-/// 'target_index'.
-pub fn pick_best_move(
-    moves: &mut MoveList,
-    heuristics: &SearchHeuristics,
-    pos: &bitboard::position::Position,
+// Helper recursive search that operates on a provided arena and components.
+pub fn search_node_with_arena<M: MoveGenerator, E: Evaluator>(
+    ctx: &mut SearchContext<M, E>,
+    arena: &mut Arena,
     ply: usize,
-    target_index: usize,
-) {
-    if target_index >= moves.len() {
-        return;
-    }
-
-    let slice = moves.as_slice();
-    let mut best_score = i32::MIN;
-    let mut best_idx = target_index;
-
-    let tmp = target_index..moves.len();
-    for i in tmp {
-        let score = heuristics.score_move(pos, &slice[i], ply);
-        if score > best_score {
-            best_score = score;
-            best_idx = i;
-        }
-    }
-
-    moves.swap(target_index, best_idx);
+    remaining: usize,
+    window: &mut SearchWindow,
+    rep_state: &mut RepetitionState,
+) -> i32 {
+    search_node_excluding(
+        ctx,
+        arena,
+        ply,
+        remaining,
+        window,
+        rep_state,
+        ChessMove::null(),
+    )
 }
 
-const fn piece_value(kind: PieceKind) -> i32 {
-    match kind {
-        PieceKind::Pawn => 100,
-        PieceKind::Knight => 320,
-        PieceKind::Bishop => 330,
-        PieceKind::Rook => 500,
-        PieceKind::Queen => 900,
-        PieceKind::King => 10_000,
-    }
-}
+/// Try the captures of the node at `ply` whose SEE could lift `static_eval`
+/// past `probcut_beta`: each must beat it in a qsearch, then in a reduced
+/// null-window search. The first that does is stored as a lower bound and
+/// its score returned.
+fn probcut<M: MoveGenerator, E: Evaluator>(
+    ctx: &mut SearchContext<M, E>,
+    arena: &mut Arena,
+    ply: usize,
+    remaining: usize,
+    probcut_beta: i32,
+    static_eval: i32,
+    rep_state: &mut RepetitionState,
+) -> Option<i32> {
+    let pos = arena.get(ply).position;
+    let see_threshold = probcut_beta - static_eval;
+    let captures = ctx.movegen.pseudo_captures(&pos);
+    for m in captures.iter() {
+        // The list also holds quiet promotions; capture-promotions stay.
+        let is_capture =
+            matches!(m.move_type, MoveType::EnPassant) || pos.piece_at_square(m.to) != Piece::None;
+        if !is_capture
+            || compute_see(&pos, m.from, m.to) < see_threshold
+            || !ctx.movegen.is_legal(&pos, m)
+        {
+            continue;
+        }
 
-fn get_piece_on_square(pos: &bitboard::position::Position, sq: bitboard::Square) -> Piece {
-    pos.piece_at_square(sq)
-}
+        let mut child_pos = pos;
+        child_pos.make_move(m);
+        arena.get_mut(ply + 1).position = child_pos;
+        rep_state.history.push(child_pos.zobrist_hash());
+        ctx.heuristics.record_move(ply, &pos, m);
 
-fn mvv_lva_score(pos: &bitboard::position::Position, mv: &ChessMove) -> i32 {
-    let victim_piece = match mv.move_type {
-        MoveType::EnPassant => {
-            let us = pos.side_to_move;
-            let cap_sq = match us {
-                Color::White => mv.to.backward(1).unwrap(),
-                Color::Black => mv.to.forward(1).unwrap(),
+        let mut score = -quiescence_with_arena(
+            ctx.movegen,
+            ctx.evaluator,
+            arena,
+            ctx.stats,
+            ply + 1,
+            -probcut_beta,
+            -probcut_beta + 1,
+        );
+        if score >= probcut_beta {
+            let mut verify_window = SearchWindow {
+                alpha: -probcut_beta,
+                beta: -probcut_beta + 1,
             };
-            get_piece_on_square(pos, cap_sq)
+            score = -search_node_with_arena(
+                ctx,
+                arena,
+                ply + 1,
+                remaining - PROBCUT_REDUCTION,
+                &mut verify_window,
+                rep_state,
+            );
+        }
+        rep_state.history.pop();
+
+        if search_limit_reached(
+            ctx.stop,
+            ctx.pondering,
+            ctx.time_budget_ms,
+            ctx.start_time,
+            ctx.node_limit,
+            ctx.counters,
+        ) {
+            return None;
+        }
+        if score >= probcut_beta {
+            ctx.tt.store(
+                pos.zobrist_hash(),
+                ply,
+                &TTEntry {
+                    value: score,
+                    static_eval: Some(static_eval),
+                    depth: (remaining - PROBCUT_REDUCTION + 1) as i8,
+                    flag: TTFlag::Lower as u8,
+                    best_move: *m,
+                },
+            );
+            ctx.stats.probcut_cutoffs += 1;
+            return Some(score);
         }
-        _ => get_piece_on_square(pos, mv.to),
-    };
-
-    let victim_value = if victim_piece != Piece::None {
-        piece_value(victim_piece.kind())
-    } else {
-        0
-    };
-
-    let attacker_piece = get_piece_on_square(pos, mv.from);
-    let attacker_value = if attacker_piece != Piece::None {
-        piece_value(attacker_piece.kind())
-    } else {
-        0
-    };
-
-    victim_value * 100 - attacker_value
-}
-
-pub fn print_uci_info(
-    depth: usize,
-    seldepth: usize,
-    score: i32,
-    pv: &str, // principal variation as a space-separated string
-    elapsed_ms: u64,
-    hashfull: u16,
-) {
-    if crate::SUPPRESS_UCI_INFO.load(Ordering::Relaxed) {
-        return;
-    }
-
-    let nodes = load_node_count();
-    let tbhits = TB_HITS.load(Ordering::Relaxed);
-    let nps = elapsed_ms
-        .checked_div(1)
-        .map(|_| {
-            if elapsed_ms > 0 {
-                nodes as u128 * 1000 / elapsed_ms as u128
-            } else {
-                0
-            }
-        })
-        .unwrap_or(0);
-
-    // Build the UCI info string (with mate/centipawn formatting) and write to
-    // stdout and append the same line to cody_uci.log for traceability.
-    let info_line = if score.abs() > MATE_SCORE - 100 {
-        let mate_in = if score > 0 {
-            (MATE_SCORE - score + 1) / 2
-        } else {
-            -(MATE_SCORE + score) / 2
-        };
-        format!(
-            "info depth {} seldepth {} multipv 1 score mate {} nodes {} nps {} hashfull {} tbhits \
-             {} time {} pv {}",
-            depth, seldepth, mate_in, nodes, nps, hashfull, tbhits, elapsed_ms, pv
-        )
-    } else {
-        format!(
-            "info depth {} seldepth {} multipv 1 score cp {} nodes {} nps {} hashfull {} tbhits \
-             {} time {} pv {}",
-            depth, seldepth, score, nodes, nps, hashfull, tbhits, elapsed_ms, pv
-        )
-    };
-
-    // Write to stdout first
-    println!("{}", info_line);
-
-    // File logging is expensive in hot paths; keep it for verbose sessions.
-    if VERBOSE.load(Ordering::Relaxed)
-        && let Ok(mut f) = OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open("cody_uci.log")
-    {
-        let stamp = util::iso_stamp_ms();
-        let _ = writeln!(f, "{} OUT: {}", stamp, info_line);
     }
+    None
 }
 
-// Context struct to reduce function parameter count
-pub struct SearchContext<'a, M: MoveGenerator, E: Evaluator> {
-    pub movegen: &'a M,
-    pub evaluator: &'a E,
-    pub tt: &'a mut TranspositionTable,
-    pub heuristics: &'a mut SearchHeuristics,
-    pub stop: Option<&'a std::sync::atomic::AtomicBool>,
-    pub time_budget_ms: Option<u64>,
-    pub start_time: Option<&'a std::time::Instant>,
-}
-
-/// Search window (alpha-beta bounds) for a single search node.
-pub struct SearchWindow {
-    pub alpha: i32,
-    pub beta: i32,
-}
-
-/// Repetition history tracking for the current search path.
-pub struct RepetitionState {
-    pub history: [u64; MAX_REPETITION_HISTORY],
-    pub len: usize,
+/// Reduced-depth search of the node at `ply` to seed the TT with a move to
+/// try first. Returns the refreshed TT entry.
+fn internal_iterative_deepening<M: MoveGenerator, E: Evaluator>(
+    ctx: &mut SearchContext<M, E>,
+    arena: &mut Arena,
+    ply: usize,
+    remaining: usize,
+    window: &SearchWindow,
+    rep_state: &mut RepetitionState,
+) -> Option<TTEntry> {
+    let mut iid_window = SearchWindow {
+        alpha: window.alpha,
+        beta: window.beta,
+    };
+    search_node_with_arena(
+        ctx,
+        arena,
+        ply,
+        remaining - IID_REDUCTION,
+        &mut iid_window,
+        rep_state,
+    );
+    ctx.pv.clear(ply);
+    ctx.tt.entry(arena.get(ply).position.zobrist_hash(), ply)
 }
 
-// Helper recursive search that operates on a provided arena and components.
-pub fn search_node_with_arena<M: MoveGenerator, E: Evaluator>(
+/// Search a node without `excluded`, used by the singular-extension
+/// verification search. With a non-null `excluded` the TT is neither used
+/// for cutoffs nor written, since the result is not the node's true value.
+fn search_node_excluding<M: MoveGenerator, E: Evaluator>(
     ctx: &mut SearchContext<M, E>,
     arena: &mut Arena,
     ply: usize,
     remaining: usize,
     window: &mut SearchWindow,
     rep_state: &mut RepetitionState,
+    excluded: ChessMove,
 ) -> i32 {
-    increment_node_count();
-    update_seldepth(ply);
-    let original_alpha = window.alpha;
-    // Check stop flag and time budget at each node
-    if let Some(stopflag) = ctx.stop
-        && stopflag.load(Ordering::Relaxed)
-    {
+    ctx.counters.increment_nodes();
+    ctx.stats.main_nodes += 1;
+    ctx.stats.seldepth = ctx.stats.seldepth.max(ply);
+    ctx.pv.clear(ply);
+    // Check stop flag, time and node budgets at each node
+    if search_limit_reached(
+        ctx.stop,
+        ctx.pondering,
+        ctx.time_budget_ms,
+        ctx.start_time,
+        ctx.node_limit,
+        ctx.counters,
+    ) {
         return 0;
     }
 
-    if let (Some(mt), Some(start)) = (ctx.time_budget_ms, ctx.start_time) {
-        let elapsed = start.elapsed().as_millis() as u64;
-        if elapsed >= mt {
-            return 0;
-        }
-    }
-
     if remaining == 0 {
         return quiescence_with_arena(
             ctx.movegen,
             ctx.evaluator,
             arena,
+            ctx.stats,
             ply,
             window.alpha,
             window.beta,
@@ -380,56 +487,97 @@ pub fn search_node_with_arena<M: MoveGenerator, E: Evaluator>(
     let key = arena.get(ply).position.zobrist_hash();
 
     // Draw adjudication.
-    // 1) Threefold repetition from the current search path.
-    if is_threefold_repetition(key, &rep_state.history, rep_state.len) {
+    // 1) Repetition: two-fold inside the tree, three-fold with game history.
+    let halfmove_clock = arena.get(ply).position.halfmove_clock;
+    if rep_state.is_repetition(halfmove_clock) {
         return 0;
     }
 
     // 2) Fifty-move rule (claimable draw). We treat claimable draws as
     // immediate draws to avoid wasting search on objectively drawn lines.
-    if arena.get(ply).position.halfmove_clock >= 100 {
+    if halfmove_clock >= 100 {
         return 0;
     }
 
-    if let Some(tb_score) = crate::search::tablebase::probe_wdl_cp(&arena.get(ply).position) {
-        TB_HITS.fetch_add(1, Ordering::Relaxed);
-        return tb_score;
+    // 3) Upcoming repetition: if we can repeat with one reversible move the
+    // node is worth at least a draw.
+    if ply > 0
+        && window.alpha < 0
+        && rep_state.has_upcoming_repetition(&arena.get(ply).position, ply)
+    {
+        window.alpha = 0;
+        if window.alpha >= window.beta {
+            return 0;
+        }
     }
+    let original_alpha = window.alpha;
 
-    // Probe TT if provided (tt is always present in serial path; for parallel we
-    // pass a local dummy).
-    // - Exact entries with a non-null move are verified against the generated legal
-    //   move list before being trusted.
-    // - Lower/Upper entries returned by `probe` are already window-validated and
-    //   can be used as immediate cutoffs.
-    let mut tt_exact_needs_verify: Option<crate::core::tt::TTEntry> = None;
+    if ply > 0
+        && excluded.is_null()
+        && let Some(tb_score) = ctx
+            .tablebase
+            .and_then(|tb| tb.probe(&arena.get(ply).position, remaining))
     {
-        if let Some(e) = ctx
+        ctx.counters.add_tb_hits(1);
+        return tb_score;
+    }
+
+    // Probe TT.
+    // - Exact entries with a non-null move are verified directly against the
+    //   board before being trusted, so no move generation is needed.
+    // - Lower/Upper entries that pass the depth and window test can be used as
+    //   immediate cutoffs.
+    // - Any other hit still supplies a move to try first and a cached static
+    //   eval.
+    let mut tt_entry = ctx.tt.entry(key, ply);
+    ctx.stats.tt_probes += 1;
+    ctx.stats.tt_hits += u64::from(tt_entry.is_some());
+    if excluded.is_null()
+        && let Some(e) = ctx
             .tt
-            .probe(key, remaining as i8, window.alpha, window.beta)
+            .probe(key, remaining as i8, window.alpha, window.beta, ply)
+    {
+        if e.flag != TTFlag::Exact as u8 || e.best_move.is_null() {
+            ctx.stats.tt_cutoffs += 1;
+            return e.value;
+        }
+        let pos = &arena.get(ply).position;
+        if ctx.movegen.is_pseudo_legal(pos, &e.best_move) && ctx.movegen.is_legal(pos, &e.best_move)
         {
-            if e.flag == crate::core::tt::TTFlag::Exact as u8 {
-                if e.best_move.is_null() {
-                    return e.value;
-                }
-                tt_exact_needs_verify = Some(e);
-            } else {
-                return e.value;
-            }
+            ctx.stats.tt_cutoffs += 1;
+            return e.value;
         }
     }
 
+    let is_pv_node = window.beta - window.alpha > 1;
+    let has_tt_move = |e: Option<TTEntry>| e.is_some_and(|e| !e.best_move.is_null());
+    if USE_IID
+        && excluded.is_null()
+        && is_pv_node
+        && remaining >= IID_MIN_DEPTH
+        && !has_tt_move(tt_entry)
+    {
+        tt_entry = internal_iterative_deepening(ctx, arena, ply, remaining, window, rep_state);
+    }
+    let remaining = if excluded.is_null() && remaining >= IIR_MIN_DEPTH && !has_tt_move(tt_entry) {
+        remaining - 1
+    } else {
+        remaining
+    };
+
     // Compute static eval once for pruning decisions
     let pos_ref = arena.get(ply).position;
     let in_check = ctx.movegen.in_check(&pos_ref);
-    let static_eval = evaluate_for_side_to_move(ctx.evaluator, &pos_ref);
-    let is_pv_node = window.beta - window.alpha > 1;
+    let static_eval = tt_entry
+        .and_then(|e| e.static_eval)
+        .unwrap_or_else(|| evaluate_for_side_to_move(ctx.evaluator, &pos_ref));
 
     // Reverse Futility Pruning (RFP): if we're way above beta at shallow depths,
     // prune immediately without searching. Don't use when in check or at root.
     if ply > 0 && !in_check && !is_pv_node && remaining <= 6 && remaining > 0 {
         let rfp_margin = RFP_MARGIN_BASE + (remaining as i32 * RFP_MARGIN_PER_DEPTH);
         if static_eval >= window.beta + rfp_margin {
+            ctx.stats.rfp_prunes += 1;
             return static_eval;
         }
     }
@@ -439,11 +587,9 @@ pub fn search_node_with_arena<M: MoveGenerator, E: Evaluator>(
     // qsearch collision), (c) not root, (d) static eval is already close to
     // beta so a fail-high is plausible.
     let can_try_null = static_eval >= window.beta - NULL_MOVE_STATIC_MARGIN_CP;
-    if ply > 0 && remaining > 2 && !in_check && !is_pv_node && can_try_null {
-        // Make a null move (pass)
+    if ply > 0 && remaining > 2 && !in_check && !is_pv_node && can_try_null && excluded.is_null() {
         let mut child_pos = pos_ref;
-        child_pos.side_to_move = pos_ref.side_to_move.opposite();
-        child_pos.ep_square = None;
+        child_pos.make_null_move();
 
         // Store and restore for non-destructive probe
         if ply + 1 < MAX_SEARCH_PLY {
@@ -451,41 +597,67 @@ pub fn search_node_with_arena<M: MoveGenerator, E: Evaluator>(
             // More aggressive null move reduction
             let null_reduction = (remaining / 3).max(2).min(remaining - 1);
             let child_key = arena.get(ply + 1).position.zobrist_hash();
-            // Optimization: Use unchecked access to avoid redundant bounds checks (safe
-            // within ply < MAX_SEARCH_PLY)
-            let next_rep_len = if rep_state.len < MAX_REPETITION_HISTORY {
-                unsafe {
-                    *rep_state.history.get_unchecked_mut(rep_state.len) = child_key;
-                }
-                rep_state.len + 1
-            } else {
-                rep_state.len
-            };
+            // Positions before the null move cannot be repeated by a legal
+            // sequence of moves, so the child starts a new barrier.
+            ctx.stats.null_move_tries += 1;
+            let parent_barrier = rep_state.null_barrier;
+            rep_state.null_barrier = rep_state.history.len();
+            rep_state.history.push(child_key);
+            ctx.heuristics.record_null_move(ply);
             let mut null_window = SearchWindow {
                 alpha: -window.beta,
                 beta: -window.beta + 1,
             };
-            let mut null_rep = RepetitionState {
-                history: rep_state.history,
-                len: next_rep_len,
-            };
             let null_score = -search_node_with_arena(
                 ctx,
                 arena,
                 ply + 1,
                 remaining - null_reduction - 1,
                 &mut null_window,
-                &mut null_rep,
+                rep_state,
             );
+            rep_state.history.pop();
+            rep_state.null_barrier = parent_barrier;
             if null_score >= window.beta {
+                ctx.stats.null_move_cutoffs += 1;
                 return null_score;
             }
         }
     }
 
+    if ply > 0
+        && !in_check
+        && !is_pv_node
+        && excluded.is_null()
+        && remaining >= PROBCUT_MIN_DEPTH
+        && window.beta.abs() < MATE_SCORE - MAX_SEARCH_PLY as i32
+    {
+        let probcut_beta = window.beta + PROBCUT_MARGIN;
+        // A TT bound at comparable depth already says the captures fall short.
+        let refuted_by_tt = tt_entry.is_some_and(|e| {
+            e.depth >= 0
+                && e.depth as usize + PROBCUT_REDUCTION > remaining
+                && e.flag != TTFlag::Lower as u8
+                && e.value < probcut_beta
+        });
+        if !refuted_by_tt
+            && let Some(score) = probcut(
+                ctx,
+                arena,
+                ply,
+                remaining,
+                probcut_beta,
+                static_eval,
+                rep_state,
+            )
+        {
+            return score;
+        }
+    }
+
     let mut moves = {
         let (parent, _) = arena.get_pair_mut(ply, ply + 1);
-        generate_pseudo_moves_fast(&parent.position)
+        ctx.movegen.pseudo_moves(&parent.position)
     };
     let move_len = moves.len();
 
@@ -499,56 +671,30 @@ pub fn search_node_with_arena<M: MoveGenerator, E: Evaluator>(
 
     let mut best_score = i32::MIN;
     let mut best_move = bitboard::mov::ChessMove::null();
-    // Work with a local mutable vector so we can reorder based on TT best move.
-    // Prioritize TT best move first (already at 0 if found).
-    if let Some(e) = tt_exact_needs_verify
-        && !e.best_move.is_null()
-    {
-        for i in 0..move_len {
-            if moves[i] == e.best_move {
-                moves.swap(0, i);
-                break;
-            }
-        }
-    }
-
     let pos = arena.get(ply).position;
-    let tt_best_move = tt_exact_needs_verify
-        .map(|e| e.best_move)
-        .filter(|m| !m.is_null());
+    let tt_best_move = tt_entry.map(|e| e.best_move).filter(|m| !m.is_null());
 
-    // Move TT best move to front if found
+    // Move TT best move to front if found; it is searched before the
+    // heuristically ordered rest.
+    let mut tt_move_first = false;
     if let Some(tt_move) = tt_best_move {
         for i in 0..move_len {
             if moves[i] == tt_move {
                 moves.swap(0, i);
+                tt_move_first = true;
                 break;
             }
         }
     }
-
-    if let Some(e) = tt_exact_needs_verify
-        && !e.best_move.is_null()
-    {
-        let mut has_move = false;
-        for i in 0..move_len {
-            if moves[i] == e.best_move {
-                has_move = true;
-                break;
-            }
-        }
-        if has_move {
-            // Fast legality check using bitboard operations
-            if is_move_legal_without_making(&pos, &e.best_move) {
-                return e.value;
-            }
-        }
-    }
+    let may_extend = ply < EXTENSION_PLY_FACTOR * ctx.root_depth;
 
     let mut legal_move_count = 0usize;
+    let mut tried = TriedMoves::new();
     for move_idx in 0..moves.len() {
         // Pick best move for this iteration
-        pick_best_move(&mut moves, ctx.heuristics, &pos, ply, move_idx);
+        if move_idx > 0 || !tt_move_first {
+            pick_best_move(&mut moves, ctx.heuristics, &pos, ply, move_idx);
+        }
 
         // Prefetch future move entries while iterating the ordered move list.
         // Applying this in search (instead of movegen) targets the true hot loop.
@@ -557,10 +703,13 @@ pub fn search_node_with_arena<M: MoveGenerator, E: Evaluator>(
         }
 
         let m = moves[move_idx];
+        if m == excluded {
+            continue;
+        }
 
         // Fast legality check using bitboard operations without making the move.
         // This avoids the expensive make/unmake cycle for illegal moves.
-        if !is_move_legal_without_making(&pos, &m) {
+        if !ctx.movegen.is_legal(&pos, &m) {
             continue;
         }
 
@@ -583,10 +732,12 @@ pub fn search_node_with_arena<M: MoveGenerator, E: Evaluator>(
             m.move_type,
             MoveType::Capture | MoveType::EnPassant | MoveType::Promotion(_)
         );
+        // Checking moves are never pruned or reduced as quiet moves.
+        let gives_check = ctx.movegen.in_check(&child_pos);
 
         // Late Move Pruning (LMP): at shallow depths, skip quiet moves entirely
         // beyond a certain count.
-        if !in_check && !is_pv_node && !is_tactical && remaining <= 4 {
+        if !in_check && !is_pv_node && !is_tactical && !gives_check && remaining <= 4 {
             let lmp_threshold = match remaining {
                 1 => 4,
                 2 => 8,
@@ -595,12 +746,13 @@ pub fn search_node_with_arena<M: MoveGenerator, E: Evaluator>(
                 _ => 999,
             };
             if move_index >= lmp_threshold {
+                ctx.stats.lmp_prunes += 1;
                 continue;
             }
         }
 
         // Extended Futility Pruning: skip quiet moves if static eval + margin < alpha
-        if !in_check && !is_pv_node && !is_tactical && move_index > 0 {
+        if !in_check && !is_pv_node && !is_tactical && !gives_check && move_index > 0 {
             let can_prune = match remaining {
                 1 => static_eval + FUTILITY_MARGIN_D1 < window.alpha,
                 2 => static_eval + FUTILITY_MARGIN_D2 < window.alpha,
@@ -608,6 +760,7 @@ pub fn search_node_with_arena<M: MoveGenerator, E: Evaluator>(
                 _ => false,
             };
             if can_prune {
+                ctx.stats.futility_prunes += 1;
                 continue;
             }
         }
@@ -618,32 +771,82 @@ pub fn search_node_with_arena<M: MoveGenerator, E: Evaluator>(
             // Skip losing captures at higher depths, be more lenient at depth 1
             let see_threshold = if remaining >= 4 { -50 } else { -100 };
             if see_value < see_threshold {
+                ctx.stats.see_prunes += 1;
                 continue;
             }
         }
 
-        let child_key = arena.get(ply + 1).position.zobrist_hash();
-        // Optimization: Use unchecked access to avoid redundant bounds checks (safe
-        // within ply < MAX_SEARCH_PLY)
-        let next_rep_len = if rep_state.len < MAX_REPETITION_HISTORY {
-            unsafe {
-                *rep_state.history.get_unchecked_mut(rep_state.len) = child_key;
+        // Singular extension: if every other move fails low against a
+        // margin below the TT value, the TT move is the only good one and
+        // deserves more depth. If the others fail high too, several moves
+        // beat beta (multi-cut); if only the TT value does, the TT move is
+        // searched shallower.
+        let mut extension = 0;
+        if let Some(e) = tt_entry
+            && may_extend
+            && ply > 0
+            && move_index == 0
+            && tt_move_first
+            && excluded.is_null()
+            && remaining >= SINGULAR_MIN_DEPTH
+            && e.depth >= 0
+            && e.depth as usize + SINGULAR_TT_DEPTH_MARGIN >= remaining
+            && e.flag != TTFlag::Upper as u8
+            && e.value.abs() < MATE_SCORE - MAX_SEARCH_PLY as i32
+        {
+            let singular_beta = e.value - SINGULAR_MARGIN_PER_DEPTH * remaining as i32;
+            let mut singular_window = SearchWindow {
+                alpha: singular_beta - 1,
+                beta: singular_beta,
+            };
+            let value = search_node_excluding(
+                ctx,
+                arena,
+                ply,
+                (remaining - 1) / 2,
+                &mut singular_window,
+                rep_state,
+                m,
+            );
+            // The verification search reused this ply's PV row and child slot.
+            ctx.pv.clear(ply);
+            arena.get_mut(ply + 1).position = child_pos;
+
+            if value < singular_beta {
+                extension = SINGULAR_EXTENSION;
+            } else if singular_beta >= window.beta {
+                return singular_beta;
+            } else if e.value >= window.beta {
+                extension = SINGULAR_NEGATIVE_EXTENSION;
             }
-            rep_state.len + 1
-        } else {
-            rep_state.len
-        };
+        }
+
+        // Check extension: a checking move is searched one ply deeper.
+        if extension == 0 && may_extend && gives_check {
+            extension = CHECK_EXTENSION;
+        }
+        let new_depth = (remaining as i32 - 1 + extension).max(0) as usize;
+
+        let child_key = arena.get(ply + 1).position.zobrist_hash();
+        rep_state.history.push(child_key);
+        ctx.heuristics.record_move(ply, &pos, &m);
 
         // Late Move Reduction (LMR): reduce depth for moves that don't look promising.
-        let mut depth_for_search = remaining - 1;
+        let mut depth_for_search = new_depth;
         let mut do_full_depth_search = true;
 
         // Very aggressive LMR: start at move 1 for quiet moves
-        if move_index > 0 && remaining >= 2 && !is_tactical && !in_check && !is_pv_node {
+        if move_index > 0
+            && remaining >= 2
+            && !is_tactical
+            && !gives_check
+            && !in_check
+            && !is_pv_node
+        {
             // Aggressive LMR formula
             let reduction = ((move_index as f64).ln() * (remaining as f64).ln() * 0.7) as usize;
             if reduction > 0 {
-                depth_for_search = (remaining - 1).saturating_sub(reduction);
+                depth_for_search = new_depth.saturating_sub(reduction);
                 do_full_depth_search = false;
             }
         }
@@ -655,34 +858,26 @@ pub fn search_node_with_arena<M: MoveGenerator, E: Evaluator>(
                 alpha: -window.beta,
                 beta: -window.alpha,
             };
-            let mut child_rep = RepetitionState {
-                history: rep_state.history,
-                len: next_rep_len,
-            };
             -search_node_with_arena(
                 ctx,
                 arena,
                 ply + 1,
                 depth_for_search,
                 &mut child_window,
-                &mut child_rep,
+                rep_state,
             )
         } else {
             let mut null_window = SearchWindow {
                 alpha: -window.alpha - 1,
                 beta: -window.alpha,
             };
-            let mut null_rep = RepetitionState {
-                history: rep_state.history,
-                len: next_rep_len,
-            };
             let mut pvs_score = -search_node_with_arena(
                 ctx,
                 arena,
                 ply + 1,
                 depth_for_search,
                 &mut null_window,
-                &mut null_rep,
+                rep_state,
             );
 
             if pvs_score > window.alpha && pvs_score < window.beta {
@@ -690,17 +885,13 @@ pub fn search_node_with_arena<M: MoveGenerator, E: Evaluator>(
                     alpha: -window.beta,
                     beta: -window.alpha,
                 };
-                let mut full_rep = RepetitionState {
-                    history: rep_state.history,
-                    len: next_rep_len,
-                };
                 pvs_score = -search_node_with_arena(
                     ctx,
                     arena,
                     ply + 1,
                     depth_for_search,
                     &mut full_window,
-                    &mut full_rep,
+                    rep_state,
                 );
             }
 
@@ -709,29 +900,26 @@ pub fn search_node_with_arena<M: MoveGenerator, E: Evaluator>(
 
         // If LMR returned a value > alpha, re-search at full depth to verify
         if !do_full_depth_search && score > window.alpha {
+            ctx.stats.lmr_researches += 1;
             let mut lmr_window = SearchWindow {
                 alpha: -window.beta,
                 beta: -window.alpha,
             };
-            let mut lmr_rep = RepetitionState {
-                history: rep_state.history,
-                len: next_rep_len,
-            };
-            score = -search_node_with_arena(
-                ctx,
-                arena,
-                ply + 1,
-                remaining - 1,
-                &mut lmr_window,
-                &mut lmr_rep,
-            );
+            score =
+                -search_node_with_arena(ctx, arena, ply + 1, new_depth, &mut lmr_window, rep_state);
         }
 
+        rep_state.history.pop();
+
         if score > best_score {
             best_score = score;
             best_move = m;
         }
 
+        if score > window.alpha {
+            ctx.pv.update(ply, m);
+        }
+
         // Optimization: Use max() for alpha updates instead of if-else.
         // Compiler generates cmov instead of conditional branch, better pipeline
         // utilization.
@@ -739,9 +927,18 @@ pub fn search_node_with_arena<M: MoveGenerator, E: Evaluator>(
 
         // Beta cutoff
         if window.alpha >= window.beta {
-            ctx.heuristics.update_on_beta_cutoff(ply, m, remaining);
+            ctx.stats.beta_cutoffs += 1;
+            ctx.stats.first_move_cutoffs += u64::from(move_index == 0);
+            ctx.heuristics
+                .update_on_beta_cutoff(&pos, ply, m, remaining, &tried);
             break;
         }
+        tried.push(m);
+    }
+
+    // Only the excluded move was playable: it is not singular.
+    if legal_move_count == 0 && !excluded.is_null() {
+        return window.alpha;
     }
 
     if legal_move_count == 0 {
@@ -752,6 +949,10 @@ pub fn search_node_with_arena<M: MoveGenerator, E: Evaluator>(
         return 0;
     }
 
+    if !excluded.is_null() {
+        return best_score;
+    }
+
     // Store TT result with correct bound semantics from the original window.
     let tt_flag = if best_score <= original_alpha {
         TTFlag::Upper
@@ -760,8 +961,17 @@ pub fn search_node_with_arena<M: MoveGenerator, E: Evaluator>(
     } else {
         TTFlag::Exact
     };
-    ctx.tt
-        .store(key, best_score, remaining as i8, tt_flag, best_move);
+    ctx.tt.store(
+        key,
+        ply,
+        &TTEntry {
+            value: best_score,
+            static_eval: Some(static_eval),
+            depth: remaining as i8,
+            flag: tt_flag as u8,
+            best_move,
+        },
+    );
 
     best_score
 }
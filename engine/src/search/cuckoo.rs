@@ -0,0 +1,184 @@
+// src/search/cuckoo.rs
+//
+// Cuckoo tables for upcoming-repetition detection (Marcel van Kervinck's
+// method). Every reversible non-pawn move `piece s1 <-> s2` changes the
+// Zobrist key by `piece[s1] ^ piece[s2] ^ side`; the table stores those
+// deltas so a single lookup tells whether some earlier position on the path
+// is one such move away from the current one.
+
+use bitboard::Square;
+use bitboard::bitboard::bishop_attacks;
+use bitboard::bitboard::rook_attacks;
+use bitboard::bitboardmask::BitBoardMask;
+use bitboard::piece::Color;
+use bitboard::piece::Piece;
+use bitboard::piece::PieceKind;
+use bitboard::position::Position;
+use bitboard::tables::king_attack::KING_ATTACKS;
+use bitboard::tables::knight_attack::KNIGHT_ATTACKS;
+use bitboard::zobrist::ZOBRIST_PIECE_KEYS;
+use bitboard::zobrist::ZOBRIST_SIDE;
+use bitboard::zobrist::piece_index;
+use std::sync::LazyLock;
+
+const CUCKOO_SIZE: usize = 8192;
+
+/// One reversible move: its key delta, squares and the squares strictly
+/// between them that must be empty for it to be playable.
+#[derive(Clone, Copy, Default)]
+struct CuckooEntry {
+    key: u64,
+    from: u8,
+    to: u8,
+    between: u64,
+}
+
+struct CuckooTable {
+    entries: Box<[CuckooEntry]>,
+}
+
+static CUCKOO: LazyLock<CuckooTable> = LazyLock::new(CuckooTable::build);
+
+#[allow(clippy::cast_possible_truncation)]
+const fn h1(key: u64) -> usize {
+    (key & (CUCKOO_SIZE as u64 - 1)) as usize
+}
+
+#[allow(clippy::cast_possible_truncation)]
+const fn h2(key: u64) -> usize {
+    ((key >> 16) & (CUCKOO_SIZE as u64 - 1)) as usize
+}
+
+/// Attacks of `piece` from `sq` on an empty board.
+fn empty_board_attacks(piece: Piece, sq: Square) -> u64 {
+    let empty = BitBoardMask(0);
+    match piece.kind() {
+        PieceKind::Knight => KNIGHT_ATTACKS[sq.index()].0,
+        PieceKind::Bishop => bishop_attacks(sq, empty).0,
+        PieceKind::Rook => rook_attacks(sq, empty).0,
+        PieceKind::Queen => bishop_attacks(sq, empty).0 | rook_attacks(sq, empty).0,
+        PieceKind::King => KING_ATTACKS[sq.index()].0,
+        _ => 0,
+    }
+}
+
+/// Squares strictly between two aligned squares; empty otherwise.
+fn between(s1: Square, s2: Square) -> u64 {
+    let (b1, b2) = (BitBoardMask(1 << s1.index()), BitBoardMask(1 << s2.index()));
+    if rook_attacks(s1, BitBoardMask(0)).0 & b2.0 != 0 {
+        rook_attacks(s1, b2).0 & rook_attacks(s2, b1).0
+    } else if bishop_attacks(s1, BitBoardMask(0)).0 & b2.0 != 0 {
+        bishop_attacks(s1, b2).0 & bishop_attacks(s2, b1).0
+    } else {
+        0
+    }
+}
+
+impl CuckooTable {
+    fn build() -> Self {
+        let mut entries = vec![CuckooEntry::default(); CUCKOO_SIZE].into_boxed_slice();
+        let squares = Square::all_array();
+
+        let kinds = [
+            PieceKind::Knight,
+            PieceKind::Bishop,
+            PieceKind::Rook,
+            PieceKind::Queen,
+            PieceKind::King,
+        ];
+        for color in [Color::White, Color::Black] {
+            for kind in kinds {
+                let piece = Piece::from_parts(color, Some(kind));
+                let keys = &ZOBRIST_PIECE_KEYS[piece_index(piece)];
+                for (i, &s1) in squares.iter().enumerate() {
+                    for &s2 in &squares[i + 1..] {
+                        if empty_board_attacks(piece, s1) & (1 << s2.index()) == 0 {
+                            continue;
+                        }
+                        #[allow(clippy::cast_possible_truncation)]
+                        let mut entry = CuckooEntry {
+                            key: keys[s1.index()] ^ keys[s2.index()] ^ ZOBRIST_SIDE,
+                            from: s1.index() as u8,
+                            to: s2.index() as u8,
+                            between: between(s1, s2),
+                        };
+                        // Standard cuckoo insertion: displace whatever occupies
+                        // the slot into its alternative slot until one is free.
+                        let mut slot = h1(entry.key);
+                        loop {
+                            std::mem::swap(&mut entries[slot], &mut entry);
+                            if entry.key == 0 {
+                                break;
+                            }
+                            slot = if slot == h1(entry.key) {
+                                h2(entry.key)
+                            } else {
+                                h1(entry.key)
+                            };
+                        }
+                    }
+                }
+            }
+        }
+
+        Self { entries }
+    }
+
+    fn lookup(&self, key: u64) -> Option<&CuckooEntry> {
+        [h1(key), h2(key)]
+            .into_iter()
+            .map(|slot| &self.entries[slot])
+            .find(|e| e.key == key)
+    }
+}
+
+/// Reversible move turning the position with key `key` (`pos`) into the
+/// one with key `earlier`, if a single non-pawn move does that and no piece
+/// stands between its squares. The squares are returned unordered: the
+/// piece may be on either one.
+pub fn reversible_move_between(pos: &Position, key: u64, earlier: u64) -> Option<(Square, Square)> {
+    let entry = CUCKOO.lookup(key ^ earlier)?;
+    if entry.between & pos.all_pieces().0 != 0 {
+        return None;
+    }
+    let from = Square::try_from_index(entry.from)?;
+    let to = Square::try_from_index(entry.to)?;
+    Some((from, to))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table_holds_every_reversible_move() {
+        // 2 colours x (336 knight + 560 bishop + 896 rook + 1456 queen + 420
+        // king) / 2 directions.
+        let stored = CUCKOO.entries.iter().filter(|e| e.key != 0).count();
+        assert_eq!(stored, 3668);
+    }
+
+    #[test]
+    fn test_detects_knight_shuffle_and_blocked_slider() {
+        let start = Position::default();
+        let mut after = start;
+        after.make_move(&start.parse_uci_move("g1f3").unwrap());
+        let (a, b) = reversible_move_between(&after, after.zobrist_hash(), start.zobrist_hash())
+            .expect("Nf3-g1 undoes Ng1-f3");
+        let mut squares = [a.index(), b.index()];
+        squares.sort_unstable();
+        assert_eq!(squares, [Square::G1.index(), Square::F3.index()]);
+
+        // Ra1-a3 and back would be reversible, but the pawn on a2 blocks it.
+        let rook_moved = Position::from_fen("4k3/8/8/8/8/R7/P7/4K3 b - - 1 1");
+        let rook_home = Position::from_fen("4k3/8/8/8/8/8/P7/R3K3 w - - 0 1");
+        assert!(
+            reversible_move_between(
+                &rook_moved,
+                rook_moved.zobrist_hash(),
+                rook_home.zobrist_hash()
+            )
+            .is_none()
+        );
+    }
+}
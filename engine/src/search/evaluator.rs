@@ -37,8 +37,11 @@ use crate::search::piecesquaretable::QUEEN_ENDGAME_TABLE;
 use crate::search::piecesquaretable::QUEEN_SQUARE_TABLE;
 use crate::search::piecesquaretable::ROOK_ENDGAME_TABLE;
 use crate::search::piecesquaretable::ROOK_SQUARE_TABLE;
+use bitboard::BitBoardMask;
 use bitboard::attack::is_square_attacked;
 use bitboard::bitboard::king_attacks;
+use bitboard::pawns;
+use bitboard::pawns::PASSED_PAWN_MASKS;
 use bitboard::piece::Color;
 use bitboard::piece::Piece;
 use bitboard::piece::PieceKind;
@@ -176,22 +179,8 @@ impl Evaluator for MaterialEvaluator {
 }
 
 fn evaluate_bishop_pair(pos: &Position) -> i32 {
-    // SIMD-optimized: count bishops for both sides in parallel
-    let white_bishops_bb = pos
-        .pieces
-        .get(Piece::from_parts(Color::White, Some(PieceKind::Bishop)))
-        .0;
-    let black_bishops_bb = pos
-        .pieces
-        .get(Piece::from_parts(Color::Black, Some(PieceKind::Bishop)))
-        .0;
-
-    // Use SIMD for parallel popcount (pads with zeros for unused lanes)
-    let vec = bitboard::intrinsics::SimdU64x4::new(white_bishops_bb, black_bishops_bb, 0, 0);
-    let counts = vec.popcnt_parallel();
-
-    let white_bishops = counts[0] as i32;
-    let black_bishops = counts[1] as i32;
+    let white_bishops = pos.count(Piece::WhiteBishop) as i32;
+    let black_bishops = pos.count(Piece::BlackBishop) as i32;
 
     ((white_bishops >= 2) as i32 - (black_bishops >= 2) as i32) * BISHOP_PAIR_BONUS
 }
@@ -213,28 +202,13 @@ fn evaluate_pawn_structure_for_color(pos: &Position, color: Color) -> i32 {
         return 0;
     }
 
-    let mut score = 0;
-    let mut file_counts = [0u8; 8];
+    let doubled = pawns::doubled_pawns(our_pawns).count_ones() as i32;
+    let isolated = pawns::isolated_pawns(our_pawns).count_ones() as i32;
+    let mut score = -doubled * DOUBLED_PAWN_PENALTY - isolated * ISOLATED_PAWN_PENALTY;
 
     for sq in our_pawns.squares() {
-        file_counts[sq.file() as usize] = file_counts[sq.file() as usize].saturating_add(1);
-    }
-
-    for sq in our_pawns.squares() {
-        let file = sq.file() as usize;
-        let rank = sq.rank() as usize;
-
-        if file_counts[file] > 1 {
-            score -= DOUBLED_PAWN_PENALTY;
-        }
-
-        let left_file_has_pawn = file > 0 && file_counts[file - 1] > 0;
-        let right_file_has_pawn = file < 7 && file_counts[file + 1] > 0;
-        if !left_file_has_pawn && !right_file_has_pawn {
-            score -= ISOLATED_PAWN_PENALTY;
-        }
-
-        if is_passed_pawn(sq, color, their_pawns.0) {
+        if is_passed_pawn(sq, color, their_pawns) {
+            let rank = sq.rank() as usize;
             let advance = if color == Color::White {
                 rank
             } else {
@@ -247,31 +221,8 @@ fn evaluate_pawn_structure_for_color(pos: &Position, color: Color) -> i32 {
     score
 }
 
-fn is_passed_pawn(sq: bitboard::Square, color: Color, enemy_pawns_bits: u64) -> bool {
-    let file = sq.file() as i32;
-    let rank = sq.rank() as i32;
-
-    let file_start = (file - 1).max(0);
-    let file_end = (file + 1).min(7);
-
-    for ef in file_start..=file_end {
-        for er in 0..8 {
-            let ahead = match color {
-                Color::White => er > rank,
-                Color::Black => er < rank,
-            };
-            if !ahead {
-                continue;
-            }
-
-            let idx = (er * 8 + ef) as u64;
-            if (enemy_pawns_bits & (1u64 << idx)) != 0 {
-                return false;
-            }
-        }
-    }
-
-    true
+fn is_passed_pawn(sq: bitboard::Square, color: Color, enemy_pawns: BitBoardMask) -> bool {
+    (PASSED_PAWN_MASKS[color as usize][sq.index()] & enemy_pawns).is_empty()
 }
 
 fn compute_phase(pos: &Position) -> i32 {
@@ -279,41 +230,12 @@ fn compute_phase(pos: &Position) -> i32 {
 
     let mut phase = MAX_PHASE;
 
-    // SIMD-optimized phase computation: count all pieces for both sides in parallel
+    // Piece counts are cached in the position, so this is a handful of loads.
     for color in [Color::White, Color::Black] {
-        // Load 4 piece bitboards into SIMD vector for parallel popcount
-        let pawns = pos.pieces.get(Piece::from_parts(color, Some(Pawn))).0;
-        let knights = pos.pieces.get(Piece::from_parts(color, Some(Knight))).0;
-        let bishops = pos.pieces.get(Piece::from_parts(color, Some(Bishop))).0;
-        let rooks = pos.pieces.get(Piece::from_parts(color, Some(Rook))).0;
-
-        // Parallel popcount on 4 bitboards simultaneously
-        let vec = bitboard::intrinsics::SimdU64x4::new(pawns, knights, bishops, rooks);
-        let counts = vec.popcnt_parallel();
-
-        let queen_count = pos
-            .pieces
-            .get(Piece::from_parts(color, Some(Queen)))
-            .0
-            .count_ones() as i32;
-        let king_count = pos
-            .pieces
-            .get(Piece::from_parts(color, Some(King)))
-            .0
-            .count_ones() as i32;
-
-        // Use SIMD results
-        let pawn_count = counts[0] as i32;
-        let knight_count = counts[1] as i32;
-        let bishop_count = counts[2] as i32;
-        let rook_count = counts[3] as i32;
-
-        phase -= PHASE_WEIGHTS[0] * pawn_count
-            + PHASE_WEIGHTS[1] * knight_count
-            + PHASE_WEIGHTS[2] * bishop_count
-            + PHASE_WEIGHTS[3] * rook_count
-            + PHASE_WEIGHTS[4] * queen_count
-            + PHASE_WEIGHTS[5] * king_count;
+        for kind in [Pawn, Knight, Bishop, Rook, Queen, King] {
+            let count = pos.count(Piece::from_parts(color, Some(kind))) as i32;
+            phase -= PHASE_WEIGHTS[kind as usize] * count;
+        }
     }
 
     phase.clamp(0, MAX_PHASE)
@@ -480,98 +402,94 @@ fn evaluate_king_safety(pos: &Position) -> i32 {
 
     for color in [Color::White, Color::Black] {
         let king_piece = Piece::from_parts(color, Some(PieceKind::King));
-        let king_bb = pos.pieces.get(king_piece);
-
-        if king_bb.is_empty() {
+        if pos.count(king_piece) == 0 {
             continue; // No king (shouldn't happen in legal position)
         }
 
-        // Find king square (should be exactly one)
-        if let Some(king_sq) = king_bb.squares().next() {
-            let king_rank = king_sq.rank() as i32;
-            let king_file = king_sq.file() as i32;
-
-            // Penalty: king on open files/ranks (few pawns nearby)
-            let our_pawns = pos
-                .pieces
-                .get(Piece::from_parts(color, Some(PieceKind::Pawn)));
-            let enemy_pawns = pos
-                .pieces
-                .get(Piece::from_parts(color.opposite(), Some(PieceKind::Pawn)));
-
-            let mut nearby_pawn_count = 0;
-            for file_offset in -1..=1 {
-                let check_file = king_file + file_offset;
-                if !(0..=7).contains(&check_file) {
+        let king_sq = pos.king_square(color);
+        let king_rank = king_sq.rank() as i32;
+        let king_file = king_sq.file() as i32;
+
+        // Penalty: king on open files/ranks (few pawns nearby)
+        let our_pawns = pos
+            .pieces
+            .get(Piece::from_parts(color, Some(PieceKind::Pawn)));
+        let enemy_pawns = pos
+            .pieces
+            .get(Piece::from_parts(color.opposite(), Some(PieceKind::Pawn)));
+
+        let mut nearby_pawn_count = 0;
+        for file_offset in -1..=1 {
+            let check_file = king_file + file_offset;
+            if !(0..=7).contains(&check_file) {
+                continue;
+            }
+
+            for rank_offset in -1..=1 {
+                let check_rank = king_rank + rank_offset;
+                if !(0..=7).contains(&check_rank) {
                     continue;
                 }
 
-                for rank_offset in -1..=1 {
-                    let check_rank = king_rank + rank_offset;
-                    if !(0..=7).contains(&check_rank) {
-                        continue;
-                    }
-
-                    // Count our own pawns for shelter
-                    let idx = ((check_rank * 8 + check_file) as u32) as u64;
-                    if check_file != king_file && (our_pawns.0 & (1u64 << idx)) != 0 {
-                        nearby_pawn_count += 1;
-                    }
+                // Count our own pawns for shelter
+                let idx = ((check_rank * 8 + check_file) as u32) as u64;
+                if check_file != king_file && (our_pawns.0 & (1u64 << idx)) != 0 {
+                    nearby_pawn_count += 1;
                 }
             }
+        }
 
-            let safety_penalty = if nearby_pawn_count < 2 {
-                EXPOSED_KING_PENALTY
-            } else {
-                0
-            };
+        let safety_penalty = if nearby_pawn_count < 2 {
+            EXPOSED_KING_PENALTY
+        } else {
+            0
+        };
 
-            // Penalty: king on edge of board (fewer escape squares)
-            let escape_penalty =
-                if king_file == 0 || king_file == 7 || king_rank == 0 || king_rank == 7 {
-                    KING_LACKING_ESCAPE_SQUARES / 2
-                } else {
-                    0
-                };
+        // Penalty: king on edge of board (fewer escape squares)
+        let escape_penalty = if king_file == 0 || king_file == 7 || king_rank == 0 || king_rank == 7
+        {
+            KING_LACKING_ESCAPE_SQUARES / 2
+        } else {
+            0
+        };
 
-            // Penalty: king on semi-open file (no friendly pawns but enemy pawns present)
-            let file_mask = 0x0101_0101_0101_0101u64 << (king_file as u32);
-            let our_pawn_on_file = (our_pawns.0 & file_mask) != 0;
-            let enemy_pawn_on_file = (enemy_pawns.0 & file_mask) != 0;
-            let semi_open_file_penalty = if !our_pawn_on_file && enemy_pawn_on_file {
-                OPEN_FILE_NEAR_KING
-            } else {
-                0
-            };
+        // Penalty: king on semi-open file (no friendly pawns but enemy pawns present)
+        let file_mask = 0x0101_0101_0101_0101u64 << (king_file as u32);
+        let our_pawn_on_file = (our_pawns.0 & file_mask) != 0;
+        let enemy_pawn_on_file = (enemy_pawns.0 & file_mask) != 0;
+        let semi_open_file_penalty = if !our_pawn_on_file && enemy_pawn_on_file {
+            OPEN_FILE_NEAR_KING
+        } else {
+            0
+        };
 
-            let mut king_safety_penalty = safety_penalty + escape_penalty + semi_open_file_penalty;
+        let mut king_safety_penalty = safety_penalty + escape_penalty + semi_open_file_penalty;
 
-            // Bonus for castling (if castling rights still exist, king is safer)
-            let castling_bonus = if color == Color::White {
-                let has_castling = pos.castling_rights.kingside(Color::White)
-                    || pos.castling_rights.queenside(Color::White);
-                if has_castling {
-                    -EXPOSED_KING_PENALTY / 3
-                } else {
-                    0
-                }
+        // Bonus for castling (if castling rights still exist, king is safer)
+        let castling_bonus = if color == Color::White {
+            let has_castling = pos.castling_rights.kingside(Color::White)
+                || pos.castling_rights.queenside(Color::White);
+            if has_castling {
+                -EXPOSED_KING_PENALTY / 3
             } else {
-                let has_castling = pos.castling_rights.kingside(Color::Black)
-                    || pos.castling_rights.queenside(Color::Black);
-                if has_castling {
-                    -EXPOSED_KING_PENALTY / 3
-                } else {
-                    0
-                }
-            };
-
-            king_safety_penalty += castling_bonus;
-
-            if color == Color::White {
-                white_safety += king_safety_penalty;
+                0
+            }
+        } else {
+            let has_castling = pos.castling_rights.kingside(Color::Black)
+                || pos.castling_rights.queenside(Color::Black);
+            if has_castling {
+                -EXPOSED_KING_PENALTY / 3
             } else {
-                black_safety += king_safety_penalty;
+                0
             }
+        };
+
+        king_safety_penalty += castling_bonus;
+
+        if color == Color::White {
+            white_safety += king_safety_penalty;
+        } else {
+            black_safety += king_safety_penalty;
         }
     }
 
@@ -688,19 +606,13 @@ fn evaluate_bare_king_endgame(pos: &Position) -> i32 {
     };
 
     // Get king positions
-    let def_king_sq = pos
-        .pieces
-        .get(Piece::from_parts(defending_color, Some(PieceKind::King)))
-        .first_square();
-    let att_king_sq = pos
-        .pieces
-        .get(Piece::from_parts(attacking_color, Some(PieceKind::King)))
-        .first_square();
-
-    let (def_sq, att_sq) = match (def_king_sq, att_king_sq) {
-        (Some(d), Some(a)) => (d, a),
-        _ => return 0, // Missing king (illegal position)
-    };
+    if pos.count(Piece::from_parts(defending_color, Some(PieceKind::King))) == 0
+        || pos.count(Piece::from_parts(attacking_color, Some(PieceKind::King))) == 0
+    {
+        return 0; // Missing king (illegal position)
+    }
+    let def_sq = pos.king_square(defending_color);
+    let att_sq = pos.king_square(attacking_color);
 
     // Distance to edge: reward pushing defending king to the edge
     // Center squares (d4, d5, e4, e5) are far from edge; corners are at the edge
@@ -751,26 +663,11 @@ fn evaluate_krk_progress(
     def_sq: bitboard::square::Square,
     att_sq: bitboard::square::Square,
 ) -> i32 {
-    let attacker_rooks = pos
-        .pieces
-        .get(Piece::from_parts(attacking_color, Some(PieceKind::Rook)))
-        .count() as i32;
-    let attacker_queens = pos
-        .pieces
-        .get(Piece::from_parts(attacking_color, Some(PieceKind::Queen)))
-        .count() as i32;
-    let attacker_minors = pos
-        .pieces
-        .get(Piece::from_parts(attacking_color, Some(PieceKind::Knight)))
-        .count() as i32
-        + pos
-            .pieces
-            .get(Piece::from_parts(attacking_color, Some(PieceKind::Bishop)))
-            .count() as i32;
-    let attacker_pawns = pos
-        .pieces
-        .get(Piece::from_parts(attacking_color, Some(PieceKind::Pawn)))
-        .count() as i32;
+    let count = |kind| i32::from(pos.count(Piece::from_parts(attacking_color, Some(kind))));
+    let attacker_rooks = count(PieceKind::Rook);
+    let attacker_queens = count(PieceKind::Queen);
+    let attacker_minors = count(PieceKind::Knight) + count(PieceKind::Bishop);
+    let attacker_pawns = count(PieceKind::Pawn);
 
     // Restrict to pure KRK: king + single rook vs lone king.
     if attacker_rooks != 1 || attacker_queens != 0 || attacker_minors != 0 || attacker_pawns != 0 {
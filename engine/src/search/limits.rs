@@ -0,0 +1,57 @@
+// src/search/limits.rs
+
+use crate::search::timeman::TimeControl;
+use bitboard::mov::ChessMove;
+
+/// Default iterative-deepening ceiling when no depth is requested.
+pub const DEFAULT_MAX_DEPTH: usize = 64;
+
+/// Limits for one call of `Engine::search_with_limits`. Every limit is
+/// optional except the depth ceiling; the search stops at whichever is hit
+/// first.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SearchLimits {
+    /// Maximum iterative-deepening depth.
+    pub depth: usize,
+    /// Wall-clock budget in milliseconds. Takes precedence over `clock`.
+    pub movetime_ms: Option<u64>,
+    /// Side to move's clock; the time manager derives soft/hard deadlines.
+    pub clock: Option<TimeControl>,
+    /// Stop once the engine's node counter reaches this many nodes.
+    pub nodes: Option<u64>,
+    /// Stop once a mate in at most this many moves is proven.
+    pub mate: Option<u32>,
+    /// Restrict the root to these moves. Empty means all legal moves.
+    pub search_moves: Vec<ChessMove>,
+    /// Zobrist keys of the game positions before the root, oldest first,
+    /// since the last capture or pawn move. Used for repetition detection.
+    pub game_history: Vec<u64>,
+    /// `go ponder`: the clock only starts once the engine's ponder flag is
+    /// cleared by `ponderhit`, and elapsed time counts from the `go`.
+    pub ponder: bool,
+}
+
+impl Default for SearchLimits {
+    fn default() -> Self {
+        Self {
+            depth: DEFAULT_MAX_DEPTH,
+            movetime_ms: None,
+            clock: None,
+            nodes: None,
+            mate: None,
+            search_moves: Vec::new(),
+            game_history: Vec::new(),
+            ponder: false,
+        }
+    }
+}
+
+impl SearchLimits {
+    /// Fixed-depth search with no other limit.
+    pub fn depth(depth: usize) -> Self {
+        Self {
+            depth,
+            ..Self::default()
+        }
+    }
+}
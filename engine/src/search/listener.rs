@@ -0,0 +1,69 @@
+// src/search/listener.rs
+//
+// Structured search output. `Engine::analyse` reports its progress and its
+// final result to a `SearchListener` instead of printing; the UCI front end
+// is one listener, turning updates into `info` lines.
+
+use crate::search::core::ScoreBound;
+use bitboard::mov::ChessMove;
+
+/// One progress report: a single (multi)PV line at a given depth.
+#[derive(Clone, Copy, Debug)]
+pub struct SearchUpdate<'a> {
+    pub depth: usize,
+    pub seldepth: usize,
+    /// 1-based index of the line when MultiPV is enabled.
+    pub multipv: usize,
+    pub score: i32,
+    pub bound: ScoreBound,
+    pub pv: &'a [ChessMove],
+    /// Main-search nodes so far, over all threads.
+    pub nodes: u64,
+    pub elapsed_ms: u64,
+    pub hashfull: u16,
+    pub tbhits: u64,
+}
+
+/// Score of one root move in the last iteration. Moves that lost to an
+/// earlier one under PVS only carry an upper bound.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RootMoveScore {
+    pub mv: ChessMove,
+    pub score: i32,
+    pub bound: ScoreBound,
+}
+
+/// Outcome of `Engine::analyse`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SearchResult {
+    /// Null only when the root has no legal move.
+    pub best_move: ChessMove,
+    pub ponder_move: Option<ChessMove>,
+    pub score: i32,
+    pub pv: Vec<ChessMove>,
+    /// Last depth whose iteration was not cut short by a limit.
+    pub depth: usize,
+    pub nodes: u64,
+    pub elapsed_ms: u64,
+    /// Root moves of the last iteration, best first. Moves an interrupted
+    /// iteration did not reach keep their score from the one before.
+    pub root_moves: Vec<RootMoveScore>,
+}
+
+/// Receives the output of `Engine::analyse`. Both methods default to doing
+/// nothing. Updates come from the main search thread, so a listener does not
+/// need to be `Sync`.
+pub trait SearchListener {
+    /// A PV line: after every iteration (once per MultiPV line), on every
+    /// aspiration failure and about once a second within long iterations.
+    fn on_update(&mut self, _update: &SearchUpdate<'_>) {}
+
+    /// The final result, once, just before `analyse` returns it.
+    fn on_result(&mut self, _result: &SearchResult) {}
+}
+
+/// Listener that ignores everything.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SilentListener;
+
+impl SearchListener for SilentListener {}
@@ -1,12 +1,19 @@
 #[allow(clippy::module_inception)]
 pub mod search;
 
+pub mod cuckoo;
 pub mod engine;
 pub mod evaluator;
+pub mod limits;
+pub mod listener;
+pub mod ordering;
 pub mod piecesquaretable;
+pub mod pv;
 pub mod quiescence;
 pub mod see;
+pub mod stats;
 pub mod tablebase;
+pub mod timeman;
 
 // The main search implementation lives in `search.rs` but we keep the
 // `engine` shim for compatibility. Expose it as a sibling module name
@@ -0,0 +1,478 @@
+// src/search/ordering.rs
+//
+// Move-ordering statistics for the main search: two killers per ply, a
+// counter-move table, butterfly history, continuation history one and two
+// plies back, and capture history. History tables use gravity updates: an
+// entry moves towards the bonus (or malus) in proportion to its distance
+// from saturation, so it stays within +-HISTORY_MAX and stale statistics
+// fade instead of piling up.
+
+use crate::search::MAX_SEARCH_PLY;
+use bitboard::MoveList;
+use bitboard::mov::ChessMove;
+use bitboard::mov::MoveType;
+use bitboard::piece::Color;
+use bitboard::piece::Piece;
+use bitboard::piece::PieceKind;
+use bitboard::position::Position;
+
+/// Bound on every history entry.
+const HISTORY_MAX: i32 = 16_384;
+/// A cutoff at depth `d` is worth `d * d * HISTORY_BONUS_SCALE`, capped.
+const HISTORY_BONUS_SCALE: i32 = 16;
+const HISTORY_BONUS_MAX: i32 = 1_600;
+/// Capture history only breaks ties between captures of similar MVV-LVA.
+const CAPTURE_HISTORY_DIVISOR: i32 = 16;
+
+// Ordering tiers. Quiet history sums three tables and stays well below the
+// counter-move tier.
+const CAPTURE_SCORE: i32 = 100_000;
+const PROMOTION_SCORE: i32 = 90_000;
+const KILLER_1_SCORE: i32 = 80_000;
+const KILLER_2_SCORE: i32 = 70_000;
+const COUNTER_MOVE_SCORE: i32 = 60_000;
+
+/// Moves remembered per node for the malus; later ones go unpenalized.
+pub const MAX_TRIED_MOVES: usize = 32;
+
+const PIECE_TO_SIZE: usize = 12 * 64;
+const VICTIM_KINDS: usize = 6;
+
+/// Moving piece and destination square of a move: the key of the
+/// counter-move and continuation tables.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct PieceTo(usize);
+
+impl PieceTo {
+    fn of(pos: &Position, mv: &ChessMove) -> Option<Self> {
+        let piece = pos.piece_at_square(mv.from);
+        (piece != Piece::None).then(|| Self(piece.index() * 64 + mv.to.index()))
+    }
+}
+
+const fn is_quiet(mv: &ChessMove) -> bool {
+    !matches!(
+        mv.move_type,
+        MoveType::Capture | MoveType::EnPassant | MoveType::Promotion(_)
+    )
+}
+
+fn history_bonus(depth: usize) -> i32 {
+    let depth = i32::try_from(depth)
+        .unwrap_or(i32::MAX)
+        .min(HISTORY_BONUS_MAX);
+    depth
+        .saturating_mul(depth)
+        .saturating_mul(HISTORY_BONUS_SCALE)
+        .min(HISTORY_BONUS_MAX)
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn apply_gravity(entry: &mut i16, bonus: i32) {
+    let value = i32::from(*entry);
+    *entry = (value + bonus - value * bonus.abs() / HISTORY_MAX) as i16;
+}
+
+/// Moves searched at a node without causing a cutoff, split into quiets and
+/// tactical moves, so the move that does cut off can penalize them.
+pub struct TriedMoves {
+    quiets: [ChessMove; MAX_TRIED_MOVES],
+    quiet_len: usize,
+    tactical: [ChessMove; MAX_TRIED_MOVES],
+    tactical_len: usize,
+}
+
+impl Default for TriedMoves {
+    fn default() -> Self {
+        Self {
+            quiets: [ChessMove::null(); MAX_TRIED_MOVES],
+            quiet_len: 0,
+            tactical: [ChessMove::null(); MAX_TRIED_MOVES],
+            tactical_len: 0,
+        }
+    }
+}
+
+impl TriedMoves {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, mv: ChessMove) {
+        let (list, len) = if is_quiet(&mv) {
+            (&mut self.quiets, &mut self.quiet_len)
+        } else {
+            (&mut self.tactical, &mut self.tactical_len)
+        };
+        if *len < MAX_TRIED_MOVES {
+            list[*len] = mv;
+            *len += 1;
+        }
+    }
+
+    fn quiets(&self) -> &[ChessMove] {
+        &self.quiets[..self.quiet_len]
+    }
+
+    fn tactical(&self) -> &[ChessMove] {
+        &self.tactical[..self.tactical_len]
+    }
+}
+
+pub struct SearchHeuristics {
+    killer_moves: [[ChessMove; 2]; MAX_SEARCH_PLY],
+    /// Butterfly history by side to move, from and to square.
+    history: [[[i16; 64]; 64]; 2],
+    /// Refutation of the previous move, by its piece-to.
+    counter_moves: Box<[ChessMove]>,
+    /// Quiet history by (earlier move piece-to, move piece-to), shared by the
+    /// one- and two-ply lookups.
+    continuation: Box<[i16]>,
+    /// Capture history by (attacker piece-to, victim kind).
+    capture_history: Box<[i16]>,
+    /// Piece-to of the move played at each ply of the current line; `None`
+    /// for a null move.
+    line: [Option<PieceTo>; MAX_SEARCH_PLY],
+}
+
+impl Default for SearchHeuristics {
+    fn default() -> Self {
+        Self {
+            killer_moves: [[ChessMove::null(); 2]; MAX_SEARCH_PLY],
+            history: [[[0; 64]; 64]; 2],
+            counter_moves: vec![ChessMove::null(); PIECE_TO_SIZE].into_boxed_slice(),
+            continuation: vec![0; PIECE_TO_SIZE * PIECE_TO_SIZE].into_boxed_slice(),
+            capture_history: vec![0; PIECE_TO_SIZE * VICTIM_KINDS].into_boxed_slice(),
+            line: [None; MAX_SEARCH_PLY],
+        }
+    }
+}
+
+impl SearchHeuristics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Carry the statistics into a new search. Histories are halved so the
+    /// previous search still guides ordering without outweighing fresh
+    /// cutoffs; killers and the current line belong to the old tree and are
+    /// dropped. Counter moves are kept as they are.
+    pub fn age(&mut self) {
+        self.killer_moves = [[ChessMove::null(); 2]; MAX_SEARCH_PLY];
+        self.line = [None; MAX_SEARCH_PLY];
+        self.history
+            .iter_mut()
+            .flatten()
+            .flatten()
+            .chain(self.continuation.iter_mut())
+            .chain(self.capture_history.iter_mut())
+            .for_each(|v| *v /= 2);
+    }
+
+    /// Note that `mv` is played at `ply` from `pos`, for the counter-move
+    /// and continuation lookups below it.
+    pub fn record_move(&mut self, ply: usize, pos: &Position, mv: &ChessMove) {
+        if ply < MAX_SEARCH_PLY {
+            self.line[ply] = PieceTo::of(pos, mv);
+        }
+    }
+
+    pub const fn record_null_move(&mut self, ply: usize) {
+        if ply < MAX_SEARCH_PLY {
+            self.line[ply] = None;
+        }
+    }
+
+    /// The move played `back` plies above `ply`.
+    fn earlier(&self, ply: usize, back: usize) -> Option<PieceTo> {
+        ply.checked_sub(back)
+            .and_then(|p| self.line.get(p).copied().flatten())
+    }
+
+    fn score_move(&self, pos: &Position, mv: &ChessMove, ply: usize) -> i32 {
+        match mv.move_type {
+            MoveType::Capture | MoveType::EnPassant => {
+                CAPTURE_SCORE
+                    + mvv_lva_score(pos, mv)
+                    + self.capture_entry(pos, mv).map_or(0, |i| {
+                        i32::from(self.capture_history[i]) / CAPTURE_HISTORY_DIVISOR
+                    })
+            }
+            MoveType::Promotion(kind) => {
+                PROMOTION_SCORE
+                    + match kind {
+                        PieceKind::Queen => 900,
+                        PieceKind::Rook => 500,
+                        PieceKind::Bishop => 330,
+                        PieceKind::Knight => 320,
+                        _ => 0,
+                    }
+            }
+            _ => self.quiet_score(pos, mv, ply),
+        }
+    }
+
+    /// Killers and counter moves only bump moves already in the generated
+    /// list, so unlike the TT move they never need `is_pseudo_legal`.
+    fn quiet_score(&self, pos: &Position, mv: &ChessMove, ply: usize) -> i32 {
+        if ply < MAX_SEARCH_PLY {
+            if self.killer_moves[ply][0] == *mv {
+                return KILLER_1_SCORE;
+            }
+            if self.killer_moves[ply][1] == *mv {
+                return KILLER_2_SCORE;
+            }
+        }
+        if self
+            .earlier(ply, 1)
+            .is_some_and(|prev| self.counter_moves[prev.0] == *mv)
+        {
+            return COUNTER_MOVE_SCORE;
+        }
+
+        let side = pos.side_to_move.index();
+        let mut score = i32::from(self.history[side][mv.from.index()][mv.to.index()]);
+        if let Some(current) = PieceTo::of(pos, mv) {
+            for back in [1, 2] {
+                if let Some(prev) = self.earlier(ply, back) {
+                    score += i32::from(self.continuation[prev.0 * PIECE_TO_SIZE + current.0]);
+                }
+            }
+        }
+        score
+    }
+
+    /// Index of `mv` in the capture history, if it captures something.
+    fn capture_entry(&self, pos: &Position, mv: &ChessMove) -> Option<usize> {
+        let victim = match mv.move_type {
+            MoveType::EnPassant => PieceKind::Pawn,
+            _ => {
+                let piece = pos.piece_at_square(mv.to);
+                if piece == Piece::None {
+                    return None;
+                }
+                piece.kind()
+            }
+        };
+        PieceTo::of(pos, mv).map(|attacker| attacker.0 * VICTIM_KINDS + victim as usize)
+    }
+
+    fn update_quiet(&mut self, pos: &Position, ply: usize, mv: &ChessMove, bonus: i32) {
+        let side = pos.side_to_move.index();
+        apply_gravity(
+            &mut self.history[side][mv.from.index()][mv.to.index()],
+            bonus,
+        );
+        let Some(current) = PieceTo::of(pos, mv) else {
+            return;
+        };
+        for back in [1, 2] {
+            if let Some(prev) = self.earlier(ply, back) {
+                apply_gravity(
+                    &mut self.continuation[prev.0 * PIECE_TO_SIZE + current.0],
+                    bonus,
+                );
+            }
+        }
+    }
+
+    fn update_capture(&mut self, pos: &Position, mv: &ChessMove, bonus: i32) {
+        if let Some(i) = self.capture_entry(pos, mv) {
+            apply_gravity(&mut self.capture_history[i], bonus);
+        }
+    }
+
+    /// Reward `mv`, which failed high at `ply` of `pos`, and penalize the
+    /// moves in `tried` that were searched before it. A quiet cutoff also
+    /// becomes a killer and the counter move to the previous move; tactical
+    /// moves tried first are penalized whatever kind of move cut off.
+    pub fn update_on_beta_cutoff(
+        &mut self,
+        pos: &Position,
+        ply: usize,
+        mv: ChessMove,
+        depth: usize,
+        tried: &TriedMoves,
+    ) {
+        let bonus = history_bonus(depth);
+        if is_quiet(&mv) {
+            if ply < MAX_SEARCH_PLY && self.killer_moves[ply][0] != mv {
+                self.killer_moves[ply][1] = self.killer_moves[ply][0];
+                self.killer_moves[ply][0] = mv;
+            }
+            if let Some(prev) = self.earlier(ply, 1) {
+                self.counter_moves[prev.0] = mv;
+            }
+            self.update_quiet(pos, ply, &mv, bonus);
+            for quiet in tried.quiets() {
+                self.update_quiet(pos, ply, quiet, -bonus);
+            }
+        } else {
+            self.update_capture(pos, &mv, bonus);
+        }
+        for tactical in tried.tactical() {
+            self.update_capture(pos, tactical, -bonus);
+        }
+    }
+}
+
+/// Instead of one big sort, we call this in a loop during search.
+/// It finds the best move from 'target_index' to the end and swaps it to
+/// 'target_index'.
+pub fn pick_best_move(
+    moves: &mut MoveList,
+    heuristics: &SearchHeuristics,
+    pos: &Position,
+    ply: usize,
+    target_index: usize,
+) {
+    if target_index >= moves.len() {
+        return;
+    }
+
+    let slice = moves.as_slice();
+    let mut best_score = i32::MIN;
+    let mut best_idx = target_index;
+
+    let tmp = target_index..moves.len();
+    for i in tmp {
+        let score = heuristics.score_move(pos, &slice[i], ply);
+        if score > best_score {
+            best_score = score;
+            best_idx = i;
+        }
+    }
+
+    moves.swap(target_index, best_idx);
+}
+
+const fn piece_value(kind: PieceKind) -> i32 {
+    match kind {
+        PieceKind::Pawn => 100,
+        PieceKind::Knight => 320,
+        PieceKind::Bishop => 330,
+        PieceKind::Rook => 500,
+        PieceKind::Queen => 900,
+        PieceKind::King => 10_000,
+    }
+}
+
+fn get_piece_on_square(pos: &Position, sq: bitboard::Square) -> Piece {
+    pos.piece_at_square(sq)
+}
+
+fn mvv_lva_score(pos: &Position, mv: &ChessMove) -> i32 {
+    let victim_piece = match mv.move_type {
+        MoveType::EnPassant => {
+            let us = pos.side_to_move;
+            let cap_sq = match us {
+                Color::White => mv.to.backward(1).unwrap(),
+                Color::Black => mv.to.forward(1).unwrap(),
+            };
+            get_piece_on_square(pos, cap_sq)
+        }
+        _ => get_piece_on_square(pos, mv.to),
+    };
+
+    let victim_value = if victim_piece != Piece::None {
+        piece_value(victim_piece.kind())
+    } else {
+        0
+    };
+
+    let attacker_piece = get_piece_on_square(pos, mv.from);
+    let attacker_value = if attacker_piece != Piece::None {
+        piece_value(attacker_piece.kind())
+    } else {
+        0
+    };
+
+    victim_value * 100 - attacker_value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(h: &SearchHeuristics, pos: &Position, uci: &str, ply: usize) -> i32 {
+        h.score_move(pos, &pos.parse_uci_move(uci).unwrap(), ply)
+    }
+
+    #[test]
+    fn test_gravity_saturates_at_history_max() {
+        let mut entry = 0i16;
+        for _ in 0..1_000 {
+            apply_gravity(&mut entry, HISTORY_BONUS_MAX);
+        }
+        assert!(i32::from(entry) <= HISTORY_MAX);
+        assert!(i32::from(entry) > HISTORY_MAX - HISTORY_BONUS_MAX);
+        for _ in 0..1_000 {
+            apply_gravity(&mut entry, -HISTORY_BONUS_MAX);
+        }
+        assert!(i32::from(entry) >= -HISTORY_MAX);
+    }
+
+    #[test]
+    fn test_cutoff_rewards_move_and_penalizes_earlier_quiets() {
+        let pos = Position::default();
+        let mut h = SearchHeuristics::new();
+        let cut = pos.parse_uci_move("g1f3").unwrap();
+        let mut tried = TriedMoves::new();
+        tried.push(pos.parse_uci_move("a2a3").unwrap());
+        h.update_on_beta_cutoff(&pos, 3, cut, 4, &tried);
+
+        // Killer at the cutoff ply, plain history elsewhere.
+        assert_eq!(score(&h, &pos, "g1f3", 3), KILLER_1_SCORE);
+        assert!(score(&h, &pos, "g1f3", 5) > 0);
+        assert!(score(&h, &pos, "a2a3", 5) < 0);
+        assert_eq!(score(&h, &pos, "b2b3", 5), 0);
+    }
+
+    #[test]
+    fn test_counter_move_and_continuation_follow_previous_move() {
+        let start = Position::default();
+        let mut pos = start;
+        let e4 = start.parse_uci_move("e2e4").unwrap();
+        pos.make_move(&e4);
+        let mut h = SearchHeuristics::new();
+        h.record_move(0, &start, &e4);
+        h.update_on_beta_cutoff(
+            &pos,
+            1,
+            pos.parse_uci_move("c7c5").unwrap(),
+            4,
+            &TriedMoves::new(),
+        );
+
+        // A fresh killer table leaves c5 ordered as the counter to e4.
+        h.age();
+        h.record_move(0, &start, &e4);
+        assert_eq!(score(&h, &pos, "c7c5", 1), COUNTER_MOVE_SCORE);
+
+        // After a different first move only the halved histories remain,
+        // and the continuation entry keyed by e4 no longer applies.
+        let d4 = start.parse_uci_move("d2d4").unwrap();
+        let mut other = start;
+        other.make_move(&d4);
+        h.record_move(0, &start, &d4);
+        let without_continuation = score(&h, &other, "c7c5", 1);
+        assert!(without_continuation > 0);
+        assert!(without_continuation < history_bonus(4));
+    }
+
+    #[test]
+    fn test_capture_history_reorders_close_captures() {
+        // MVV-LVA prefers the knight taking the rook, by the small gap
+        // between knight and bishop values.
+        let pos = Position::from_fen("4k3/8/8/3r4/1N6/5B2/8/4K3 w - - 0 1");
+        let mut h = SearchHeuristics::new();
+        assert!(score(&h, &pos, "b4d5", 2) > score(&h, &pos, "f3d5", 2));
+
+        let mut tried = TriedMoves::new();
+        tried.push(pos.parse_uci_move("b4d5").unwrap());
+        h.update_on_beta_cutoff(&pos, 2, pos.parse_uci_move("f3d5").unwrap(), 6, &tried);
+        assert!(score(&h, &pos, "f3d5", 2) > score(&h, &pos, "b4d5", 2));
+        // Captures never drop below quiet moves, however bad their history.
+        assert!(score(&h, &pos, "b4d5", 2) > KILLER_1_SCORE);
+    }
+}
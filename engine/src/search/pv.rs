@@ -0,0 +1,112 @@
+// src/search/pv.rs
+
+use crate::search::core::MAX_SEARCH_PLY;
+use bitboard::mov::ChessMove;
+
+/// Triangular principal-variation table.
+///
+/// Row `ply` holds the best line found from the node at `ply`. A node clears
+/// its row on entry and, whenever a move raises alpha, replaces it with that
+/// move followed by the child's row.
+pub struct PvTable {
+    moves: Vec<ChessMove>,
+    len: [usize; MAX_SEARCH_PLY],
+}
+
+impl Default for PvTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PvTable {
+    pub fn new() -> Self {
+        Self {
+            moves: vec![ChessMove::null(); MAX_SEARCH_PLY * MAX_SEARCH_PLY],
+            len: [0; MAX_SEARCH_PLY],
+        }
+    }
+
+    /// Forget the line stored for `ply`.
+    #[inline]
+    pub const fn clear(&mut self, ply: usize) {
+        if ply < MAX_SEARCH_PLY {
+            self.len[ply] = 0;
+        }
+    }
+
+    /// Make `mv` followed by the child line (row `ply + 1`) the line for `ply`.
+    pub fn update(&mut self, ply: usize, mv: ChessMove) {
+        if ply >= MAX_SEARCH_PLY {
+            return;
+        }
+        let row = ply * MAX_SEARCH_PLY;
+        self.moves[row] = mv;
+
+        let child_len = if ply + 1 < MAX_SEARCH_PLY {
+            self.len[ply + 1].min(MAX_SEARCH_PLY - 1)
+        } else {
+            0
+        };
+        if child_len > 0 {
+            let child_row = row + MAX_SEARCH_PLY;
+            self.moves
+                .copy_within(child_row..child_row + child_len, row + 1);
+        }
+        self.len[ply] = child_len + 1;
+    }
+
+    /// Line currently stored for `ply`.
+    pub fn line(&self, ply: usize) -> &[ChessMove] {
+        if ply >= MAX_SEARCH_PLY {
+            return &[];
+        }
+        let row = ply * MAX_SEARCH_PLY;
+        &self.moves[row..row + self.len[ply]]
+    }
+}
+
+/// One root line reported to the GUI: its score (side to move) and moves,
+/// starting with the root move.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PvLine {
+    pub score: i32,
+    pub pv: Vec<ChessMove>,
+}
+
+/// Format a line as space-separated UCI moves.
+pub fn pv_to_string(pv: &[ChessMove]) -> String {
+    pv.iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitboard::Square;
+    use bitboard::mov::MoveType;
+
+    #[test]
+    fn test_update_prepends_child_line() {
+        let e2e4 = ChessMove::new(Square::E2, Square::E4, MoveType::Quiet);
+        let e7e5 = ChessMove::new(Square::E7, Square::E5, MoveType::Quiet);
+        let g1f3 = ChessMove::new(Square::G1, Square::F3, MoveType::Quiet);
+
+        let mut pv = PvTable::new();
+        pv.clear(2);
+        pv.update(2, g1f3);
+        pv.clear(1);
+        pv.update(1, e7e5);
+        pv.update(0, e2e4);
+
+        assert_eq!(pv.line(0), &[e2e4, e7e5, g1f3]);
+        assert_eq!(pv_to_string(pv.line(0)), "e2e4 e7e5 g1f3");
+
+        // A cleared child row truncates the parent line.
+        pv.clear(1);
+        pv.update(0, e2e4);
+        assert_eq!(pv.line(0), &[e2e4]);
+    }
+}
@@ -2,12 +2,10 @@ use crate::core::arena::Arena;
 use crate::search::evaluator::Evaluator;
 use crate::search::evaluator::evaluate_for_side_to_move;
 use crate::search::see::compute_see;
+use crate::search::stats::SearchStats;
 use bitboard::MoveList;
 use bitboard::mov::ChessMove;
 use bitboard::movegen::MoveGenerator;
-use bitboard::movegen::generate_legal_moves_fast;
-use bitboard::movegen::generate_pseudo_captures_fast;
-use bitboard::movegen::is_move_legal_without_making;
 use bitboard::piece::Color;
 use bitboard::piece::Piece;
 use bitboard::piece::PieceKind;
@@ -36,23 +34,25 @@ pub fn quiescence_with_arena<M: MoveGenerator, E: Evaluator>(
     movegen: &M,
     evaluator: &E,
     arena: &mut Arena,
+    stats: &mut SearchStats,
     ply: usize,
     alpha: i32,
     beta: i32,
 ) -> i32 {
-    quiescence_internal(movegen, evaluator, arena, ply, alpha, beta, 0)
+    quiescence_internal(movegen, evaluator, arena, stats, ply, (alpha, beta), 0)
 }
 
 fn quiescence_internal<M: MoveGenerator, E: Evaluator>(
     movegen: &M,
     evaluator: &E,
     arena: &mut Arena,
+    stats: &mut SearchStats,
     ply: usize,
-    mut alpha: i32,
-    beta: i32,
+    (mut alpha, beta): (i32, i32),
     qsearch_depth: usize,
 ) -> i32 {
-    crate::search::core::update_seldepth(ply);
+    stats.seldepth = stats.seldepth.max(ply);
+    stats.qsearch_nodes += 1;
 
     let pos = arena.get(ply).position;
     let is_dense_position = pos.all_pieces().count() >= HIGH_DENSITY_PIECE_COUNT;
@@ -88,10 +88,10 @@ fn quiescence_internal<M: MoveGenerator, E: Evaluator>(
     // Otherwise, search captures and (at shallow depth) checking moves
     // Use MoveList for stack allocation (avoids heap in hot path)
     let mut moves = if in_check {
-        generate_legal_moves_fast(&pos)
+        movegen.legal_moves(&pos)
     } else {
         // Generate captures first
-        let move_list = generate_pseudo_captures_fast(&pos);
+        let move_list = movegen.pseudo_captures(&pos);
 
         // Filter with delta and SEE pruning
         let mut filtered = MoveList::new();
@@ -146,7 +146,7 @@ fn quiescence_internal<M: MoveGenerator, E: Evaluator>(
         if let Some(limit) = CHECK_GEN_DEPTH_LIMIT
             && qsearch_depth < limit
         {
-            let all_quiet = generate_legal_moves_fast(&pos);
+            let all_quiet = movegen.legal_moves(&pos);
             for i in 0..all_quiet.len() {
                 let m = all_quiet[i];
                 // Skip if already in move list (captures/promotions)
@@ -161,12 +161,7 @@ fn quiescence_internal<M: MoveGenerator, E: Evaluator>(
                     continue;
                 }
 
-                // Check if this move gives check
-                let mut temp = pos;
-                let undo = temp.make_move(&m);
-                let gives_check = movegen.in_check(&temp);
-                temp.unmake_move(&m, &undo);
-                if gives_check {
+                if movegen.gives_check(&pos, &m) {
                     filtered.push(m);
                 }
             }
@@ -214,7 +209,7 @@ fn quiescence_internal<M: MoveGenerator, E: Evaluator>(
 
         // Fast legality check using bitboard operations without making the move
         let pos_ref = &arena.get(ply).position;
-        if !in_check && !is_move_legal_without_making(pos_ref, &m) {
+        if !in_check && !movegen.is_legal(pos_ref, &m) {
             continue;
         }
 
@@ -234,9 +229,9 @@ fn quiescence_internal<M: MoveGenerator, E: Evaluator>(
             movegen,
             evaluator,
             arena,
+            stats,
             ply + 1,
-            -beta,
-            -alpha,
+            (-beta, -alpha),
             qsearch_depth + 1,
         );
 
@@ -1,30 +1,45 @@
 // src/search/search.rs
 
 use crate::core::arena::Arena;
+use crate::core::tt::TranspositionTable;
 use crate::search::core::INF;
 use crate::search::core::MATE_SCORE;
-use crate::search::core::MAX_REPETITION_HISTORY;
+use crate::search::core::MAX_SEARCH_PLY;
 use crate::search::core::RepetitionState;
+use crate::search::core::ScoreBound;
 use crate::search::core::SearchContext;
-use crate::search::core::SearchHeuristics;
+use crate::search::core::SearchCounters;
 use crate::search::core::SearchWindow;
-use crate::search::core::current_seldepth;
-use crate::search::core::flush_local_node_count;
-use crate::search::core::pick_best_move;
-use crate::search::core::reset_node_count;
-use crate::search::core::reset_seldepth;
+use crate::search::core::clock_running;
+use crate::search::core::search_limit_reached;
 use crate::search::core::search_node_with_arena;
 use crate::search::evaluator::Evaluator;
 use crate::search::evaluator::evaluate_for_side_to_move;
+use crate::search::limits::SearchLimits;
+use crate::search::listener::RootMoveScore;
+use crate::search::listener::SearchListener;
+use crate::search::listener::SearchResult;
+use crate::search::listener::SearchUpdate;
+use crate::search::listener::SilentListener;
+use crate::search::ordering::SearchHeuristics;
+use crate::search::ordering::pick_best_move;
+use crate::search::pv::PvLine;
+use crate::search::pv::PvTable;
+use crate::search::stats::SearchStats;
+use crate::search::tablebase::MAX_TB_PIECES;
+use crate::search::tablebase::RootTbRanking;
+use crate::search::tablebase::SyzygyTablebase;
+use crate::search::tablebase::TbConfig;
+use crate::search::tablebase::TbProbe;
+use crate::search::timeman::DEFAULT_MOVE_OVERHEAD_MS;
+use crate::search::timeman::MAX_MOVE_OVERHEAD_MS;
+use crate::search::timeman::TimeManager;
 use bitboard::MoveList;
 use bitboard::mov::ChessMove;
 use bitboard::movegen::MoveGenerator;
-use bitboard::movegen::generate_legal_moves_fast;
 use bitboard::position::Position;
-use std::cell::RefCell;
 use std::sync::Arc;
-use std::sync::RwLock;
-use std::sync::atomic::AtomicI32;
+use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering;
 use std::time::Instant;
 
@@ -32,13 +47,50 @@ const ASPIRATION_START_DELTA_CP: i32 = 20;
 const ASPIRATION_MAX_RESEARCHES: usize = 3;
 const ASPIRATION_MIN_DEPTH: usize = 3;
 const ASPIRATION_MATE_GUARD_CP: i32 = 500;
-const PARALLEL_MIN_DEPTH: usize = 6; // Enable parallel root search for practical bench depths
+
+/// Upper bound for the `MultiPV` option.
+pub const MAX_MULTI_PV: usize = 256;
+/// Upper bound for the `Threads` option.
+pub const MAX_THREADS: usize = 256;
+
+// Lazy SMP depth perturbation: helper `i` skips iteration `d` when
+// `((d + SKIP_PHASE[j]) / SKIP_SIZE[j]) % 2 == 1` with `j = (i - 1) % 20`, so
+// helpers spread over neighbouring depths instead of duplicating the main
+// thread.
+const SKIP_SIZE: [usize; 20] = [1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 3, 3, 4, 4, 4, 4, 4, 4, 4, 4];
+const SKIP_PHASE: [usize; 20] = [0, 1, 0, 1, 2, 3, 0, 1, 2, 3, 4, 5, 0, 1, 2, 3, 4, 5, 6, 7];
 
 struct RootSearchParams<'a> {
     time_budget_ms: Option<u64>,
     stop: Option<&'a std::sync::atomic::AtomicBool>,
+    pondering: Option<&'a AtomicBool>,
     start: &'a Instant,
     last_info_time: &'a mut Instant,
+    node_limit: Option<u64>,
+    counters: &'a SearchCounters,
+    /// 1-based MultiPV index reported by periodic progress lines.
+    multipv: usize,
+    /// Progress receiver; `None` for the helpers, which search silently.
+    listener: Option<&'a mut dyn SearchListener>,
+    /// Scores of the root moves searched in this window.
+    root_scores: Vec<RootMoveScore>,
+    /// The moves are already in search order; skip the move picker.
+    presorted: bool,
+    /// Game position keys before the root, for repetition detection.
+    game_history: &'a [u64],
+}
+
+impl RootSearchParams<'_> {
+    fn limit_reached(&self) -> bool {
+        search_limit_reached(
+            self.stop,
+            self.pondering,
+            self.time_budget_ms,
+            Some(self.start),
+            self.node_limit,
+            self.counters,
+        )
+    }
 }
 
 pub struct Engine<
@@ -50,8 +102,37 @@ pub struct Engine<
     evaluator: E,
     arena_capacity: usize,
     num_threads: usize,
-    tt: Arc<RwLock<crate::core::tt::TranspositionTable>>,
+    tt: Arc<TranspositionTable>,
+    /// Persistent pool running the Lazy SMP helpers, one thread per helper.
     thread_pool: Option<rayon::ThreadPool>,
+    helpers: Vec<HelperThread>,
+    pv: PvTable,
+    /// Main-thread move-ordering statistics, aged between searches.
+    heuristics: SearchHeuristics,
+    /// Main-thread counters of the last search; helpers keep their own.
+    stats: SearchStats,
+    last_pv: Vec<ChessMove>,
+    multi_pv: usize,
+    last_lines: Vec<PvLine>,
+    move_overhead_ms: u64,
+    /// Set by the caller for a `ponder` search, cleared on `ponderhit`.
+    pondering: Arc<AtomicBool>,
+    /// Stop flag of `analyse`.
+    stop: Arc<AtomicBool>,
+    /// Root move scores of the iteration in progress and of the last one.
+    iteration_scores: Vec<RootMoveScore>,
+    root_scores: Vec<RootMoveScore>,
+    completed_depth: usize,
+    /// Node and tablebase-hit totals of the current search, all threads.
+    counters: Arc<SearchCounters>,
+    tablebase: Option<Arc<SyzygyTablebase>>,
+    /// The `Syzygy*` options.
+    tb_config: TbConfig,
+    /// Probing inside the tree for the current search; `None` once the root
+    /// ranking has settled what the tables can tell.
+    tb_search: Option<TbConfig>,
+    /// Extra debug checks and diagnostics.
+    verbose: bool,
 }
 
 impl<M: MoveGenerator + Clone + Send + Sync + 'static, E: Evaluator + Clone + Send + Sync + 'static>
@@ -64,22 +145,123 @@ impl<M: MoveGenerator + Clone + Send + Sync + 'static, E: Evaluator + Clone + Se
             evaluator,
             arena_capacity: arena_size,
             num_threads: 1,
-            tt: Arc::new(RwLock::new(crate::core::tt::TranspositionTable::new(20))),
+            tt: Arc::new(TranspositionTable::new(20)),
             thread_pool: None,
+            helpers: Vec::new(),
+            pv: PvTable::new(),
+            heuristics: SearchHeuristics::new(),
+            stats: SearchStats::default(),
+            last_pv: Vec::new(),
+            multi_pv: 1,
+            last_lines: Vec::new(),
+            move_overhead_ms: DEFAULT_MOVE_OVERHEAD_MS,
+            pondering: Arc::new(AtomicBool::new(false)),
+            stop: Arc::new(AtomicBool::new(false)),
+            iteration_scores: Vec::new(),
+            root_scores: Vec::new(),
+            completed_depth: 0,
+            counters: Arc::new(SearchCounters::default()),
+            tablebase: None,
+            tb_config: TbConfig::default(),
+            tb_search: None,
+            verbose: false,
+        }
+    }
+
+    /// Principal variation of the last completed iteration of `search`.
+    pub fn principal_variation(&self) -> &[ChessMove] {
+        &self.last_pv
+    }
+
+    /// Flag a `SearchLimits::ponder` search keeps its clock stopped on.
+    /// Set it before such a search and clear it on `ponderhit`; the search
+    /// then keeps running under the time limits of its clock.
+    pub fn ponder_flag(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.pondering)
+    }
+
+    /// Flag that ends an `analyse` in progress, from any thread. It stays
+    /// set until the caller clears it, so a stop sent just before a search
+    /// starts is not lost.
+    pub fn stop_flag(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.stop)
+    }
+
+    /// Move to ponder on after `best`: the second move of the last PV or,
+    /// failing that, the hash move of the position after `best`.
+    pub fn ponder_move(&self, root: &Position, best: ChessMove) -> Option<ChessMove> {
+        if best.is_null() {
+            return None;
+        }
+        let mut child = *root;
+        child.make_move(&best);
+        let candidate = match self.last_pv.as_slice() {
+            [first, second, ..] if *first == best => Some(*second),
+            _ => self.tt.entry(child.zobrist_hash(), 0).map(|e| e.best_move),
+        }?;
+        self.movegen
+            .legal_moves(&child)
+            .as_slice()
+            .contains(&candidate)
+            .then_some(candidate)
+    }
+
+    /// Main-search nodes of the last search, over all threads.
+    pub fn node_count(&self) -> u64 {
+        self.counters.flushed_nodes()
+    }
+
+    pub const fn set_verbose(&mut self, verbose: bool) {
+        self.verbose = verbose;
+    }
+
+    /// Counters of the last search, merged over all search threads.
+    pub fn search_stats(&self) -> SearchStats {
+        let mut total = self.stats;
+        for helper in &self.helpers {
+            total.merge(&helper.stats);
         }
+        total
+    }
+
+    /// Root lines of the last completed iteration, best first. Holds up to
+    /// `MultiPV` entries; with the default of 1 it mirrors
+    /// `principal_variation`.
+    pub fn pv_lines(&self) -> &[PvLine] {
+        &self.last_lines
     }
 
-    /// Set number of threads to use for root parallelism. 1 = serial.
+    /// Number of root moves to search with exact scores and report as
+    /// separate lines. Clamped to `1..=MAX_MULTI_PV`.
+    pub fn set_multi_pv(&mut self, n: usize) {
+        self.multi_pv = n.clamp(1, MAX_MULTI_PV);
+    }
+
+    /// Time kept in reserve per move for GUI and network latency when
+    /// searching on a clock. Clamped to `MAX_MOVE_OVERHEAD_MS`.
+    pub fn set_move_overhead_ms(&mut self, ms: u64) {
+        self.move_overhead_ms = ms.min(MAX_MOVE_OVERHEAD_MS);
+    }
+
+    /// Set the number of search threads (Lazy SMP). 1 = serial; every
+    /// extra thread is a helper sharing the transposition table with the
+    /// main search. Clamped to `1..=MAX_THREADS`.
     pub fn set_num_threads(&mut self, n: usize) {
-        let n = n.max(1);
+        let n = n.clamp(1, MAX_THREADS);
         if self.num_threads != n {
             self.num_threads = n;
-            // Rebuild thread pool if threads > 1
+            // Rebuild the helper pool; the main search runs on the caller.
             if n > 1 {
-                self.thread_pool = rayon::ThreadPoolBuilder::new().num_threads(n).build().ok();
+                self.thread_pool = rayon::ThreadPoolBuilder::new()
+                    .num_threads(n - 1)
+                    .build()
+                    .ok();
             } else {
                 self.thread_pool = None;
             }
+            let arena_capacity = self.arena_capacity;
+            self.helpers
+                .resize_with(n - 1, || HelperThread::new(arena_capacity));
         }
     }
 
@@ -88,9 +270,7 @@ impl<M: MoveGenerator + Clone + Send + Sync + 'static, E: Evaluator + Clone + Se
     /// Size is clamped between 1 MB and 1024 MB.
     pub fn set_hash_size_mb(&mut self, size_mb: usize) {
         let size_mb = size_mb.clamp(1, 1024);
-        // Each TTEntry is approximately 24 bytes
-        let entry_size = std::mem::size_of::<crate::core::tt::TTEntry>();
-        let target_entries = (size_mb * 1024 * 1024) / entry_size;
+        let target_entries = (size_mb * 1024 * 1024) / TranspositionTable::ENTRY_BYTES;
 
         // Find nearest power of 2
         let mut size_pow2 = 1;
@@ -98,9 +278,7 @@ impl<M: MoveGenerator + Clone + Send + Sync + 'static, E: Evaluator + Clone + Se
             size_pow2 += 1;
         }
 
-        self.tt = Arc::new(RwLock::new(crate::core::tt::TranspositionTable::new(
-            size_pow2,
-        )));
+        self.tt = Arc::new(TranspositionTable::new(size_pow2));
     }
 
     /// Iterative deepening search. `max_depth` is the maximum search depth to
@@ -122,8 +300,183 @@ impl<M: MoveGenerator + Clone + Send + Sync + 'static, E: Evaluator + Clone + Se
         time_budget_ms: Option<u64>,
         stop: Option<&std::sync::atomic::AtomicBool>,
     ) -> (ChessMove, i32) {
+        let limits = SearchLimits {
+            depth: max_depth,
+            movetime_ms: time_budget_ms,
+            ..SearchLimits::default()
+        };
+        self.search_with_limits(root, &limits, stop)
+    }
+
+    /// Iterative deepening search bounded by `limits`: depth, time, node
+    /// count, a proven mate distance and an optional root move subset. An
+    /// iteration interrupted by a limit still supplies the best move if it
+    /// finished at least one root move, as every finished move carries a
+    /// full score; otherwise the last completed iteration's move stands.
+    ///
+    /// With a clock the `TimeManager` sets a hard deadline, checked in the
+    /// tree and before every root move, and a soft one, checked after every
+    /// iteration and stretched while the best move is unstable.
+    ///
+    /// With more than one thread, helpers run their own iterative deepening
+    /// on the same root until the main search returns (Lazy SMP). They only
+    /// contribute through the shared transposition table; the result is the
+    /// main thread's.
+    pub fn search_with_limits(
+        &mut self,
+        root: &Position,
+        limits: &SearchLimits,
+        stop: Option<&std::sync::atomic::AtomicBool>,
+    ) -> (ChessMove, i32) {
+        self.run_search(root, limits, stop, &mut SilentListener)
+    }
+
+    /// Search `root` like `search_with_limits`, reporting progress to
+    /// `listener` and returning the full result, which the listener also
+    /// receives. The search ends early once `stop_flag` is set.
+    pub fn analyse(
+        &mut self,
+        root: &Position,
+        limits: SearchLimits,
+        listener: &mut impl SearchListener,
+    ) -> SearchResult {
+        let start = Instant::now();
+        let stop = Arc::clone(&self.stop);
+        let (best_move, score) = self.run_search(root, &limits, Some(&stop), listener);
+        #[allow(clippy::cast_possible_truncation)]
+        let elapsed_ms = start.elapsed().as_millis() as u64;
+        let result = SearchResult {
+            best_move,
+            ponder_move: self.ponder_move(root, best_move),
+            score,
+            pv: self.last_pv.clone(),
+            depth: self.completed_depth,
+            nodes: self.search_stats().main_nodes,
+            elapsed_ms,
+            root_moves: self.root_scores.clone(),
+        };
+        listener.on_result(&result);
+        result
+    }
+
+    /// Search with the tablebases in charge of the root: when they cover it,
+    /// only the moves keeping the best outcome are searched and the
+    /// outcome's score is reported instead of the search's, mates aside.
+    fn run_search(
+        &mut self,
+        root: &Position,
+        limits: &SearchLimits,
+        stop: Option<&std::sync::atomic::AtomicBool>,
+        listener: &mut dyn SearchListener,
+    ) -> (ChessMove, i32) {
+        self.tt.new_search();
+        self.counters.reset();
+
+        let Some(ranking) = self.rank_tablebase_root(root, limits) else {
+            self.tb_search = Some(self.tb_config);
+            return self.run_threads(root, limits, stop, listener);
+        };
+        // A DTZ ranking leaves the tree nothing to learn from the tables,
+        // and neither does a root that is not won; the search then plays on
+        // its own evaluation among the moves that keep the outcome.
+        self.tb_search = (!ranking.dtz && ranking.score() > 0).then_some(self.tb_config);
+        let tb_limits = SearchLimits {
+            search_moves: ranking.best_moves(),
+            ..limits.clone()
+        };
+        let tb_score = ranking.score();
+        let mut listener = TbScoreListener {
+            inner: listener,
+            score: tb_score,
+        };
+        let (best_move, score) = self.run_threads(root, &tb_limits, stop, &mut listener);
+        (best_move, tb_reported_score(score, tb_score))
+    }
+
+    /// Tablebase ranking of the root moves `limits` allows, if the tables
+    /// cover the root.
+    fn rank_tablebase_root(&self, root: &Position, limits: &SearchLimits) -> Option<RootTbRanking> {
+        let mut ranking = self
+            .tablebase
+            .as_deref()?
+            .rank_root_moves(root, self.tb_config)?;
+        self.counters.add_tb_hits(ranking.moves.len() as u64);
+        if !limits.search_moves.is_empty() {
+            ranking
+                .moves
+                .retain(|m| limits.search_moves.contains(&m.mv));
+        }
+        (!ranking.moves.is_empty()).then_some(ranking)
+    }
+
+    fn run_threads(
+        &mut self,
+        root: &Position,
+        limits: &SearchLimits,
+        stop: Option<&std::sync::atomic::AtomicBool>,
+        listener: &mut dyn SearchListener,
+    ) -> (ChessMove, i32) {
+        let Some(pool) = self.thread_pool.take() else {
+            return self.iterate_main(root, limits, stop, listener);
+        };
+
+        let helpers_stop = AtomicBool::new(false);
+        let mut helpers = std::mem::take(&mut self.helpers);
+        let result = pool.in_place_scope(|scope| {
+            for (i, helper) in helpers.iter_mut().enumerate() {
+                let job = HelperJob {
+                    id: i + 1,
+                    movegen: self.movegen.clone(),
+                    evaluator: self.evaluator.clone(),
+                    tt: Arc::clone(&self.tt),
+                    counters: Arc::clone(&self.counters),
+                    tablebase: self.tablebase.clone(),
+                    tb_search: self.tb_search,
+                    root: *root,
+                    limits: limits.clone(),
+                    stop: &helpers_stop,
+                };
+                scope.spawn(move |_| helper.run(&job));
+            }
+
+            let result = self.iterate_main(root, limits, stop, listener);
+            helpers_stop.store(true, Ordering::Relaxed);
+            result
+        });
+        self.helpers = helpers;
+        self.thread_pool = Some(pool);
+
+        result
+    }
+
+    /// Main-thread iterative deepening: aspiration windows, MultiPV, time
+    /// management and all progress reports.
+    fn iterate_main(
+        &mut self,
+        root: &Position,
+        limits: &SearchLimits,
+        stop: Option<&std::sync::atomic::AtomicBool>,
+        listener: &mut dyn SearchListener,
+    ) -> (ChessMove, i32) {
+        let max_depth = limits.depth;
+        let mut time_manager = match (limits.movetime_ms, &limits.clock) {
+            (Some(movetime), _) => TimeManager::fixed(movetime),
+            (None, Some(clock)) => TimeManager::from_clock(clock, self.move_overhead_ms),
+            (None, None) => TimeManager::unlimited(),
+        };
+        let time_budget_ms = time_manager.hard_limit_ms();
+        let node_limit = limits.nodes;
+        let pondering_flag = limits.ponder.then(|| Arc::clone(&self.pondering));
+        let pondering = pondering_flag.as_deref();
+        let counters = Arc::clone(&self.counters);
+
+        self.last_pv.clear();
+        self.last_lines.clear();
+        self.root_scores.clear();
+        self.completed_depth = 0;
+        self.stats = SearchStats::default();
         if max_depth == 0 {
-            let moves = generate_legal_moves_fast(root);
+            let moves = root_moves(&self.movegen, root, limits);
             if moves.is_empty() {
                 let score = if self.movegen.in_check(root) {
                     -MATE_SCORE
@@ -143,25 +496,26 @@ impl<M: MoveGenerator + Clone + Send + Sync + 'static, E: Evaluator + Clone + Se
 
         let mut last_completed_move = ChessMove::null();
         let mut last_completed_score = i32::MIN;
-        let mut heuristics = SearchHeuristics::new();
+        let mut last_completed_pv: Vec<ChessMove> = Vec::new();
+        // Taken out of `self` for the search so the root helpers can borrow
+        // the engine mutably alongside it.
+        let mut heuristics = std::mem::take(&mut self.heuristics);
+        heuristics.age();
 
         // Iterative deepening loop
         for d in 1..=max_depth {
-            reset_seldepth(0);
+            self.stats.seldepth = 0;
 
             // Prepare arena and root position for this depth
             self.arena.reset();
             self.arena.get_mut(0).position.copy_from(root);
 
-            let mut moves = {
-                let (parent, _) = self.arena.get_pair_mut(0, 1);
-                generate_legal_moves_fast(&parent.position)
-            };
+            let mut moves = root_moves(&self.movegen, root, limits);
 
             // Diagnostic movegen validation is expensive; keep it for debug
             // sessions only when verbose logging is enabled.
             #[cfg(debug_assertions)]
-            if crate::VERBOSE.load(Ordering::Relaxed) {
+            if self.verbose {
                 bitboard::movegen::validate_legal_move_generation(&self.arena.get(0).position);
             }
 
@@ -177,12 +531,37 @@ impl<M: MoveGenerator + Clone + Send + Sync + 'static, E: Evaluator + Clone + Se
             let fallback_move = moves[0];
             let mut best_score = i32::MIN;
             let mut best_move = fallback_move;
+            let mut best_pv: Vec<ChessMove> = Vec::new();
             let mut searched_any = false;
 
             // Probe TT and reorder instantly if match found
             self.probe_for_best_move(d, &mut moves);
 
-            if self.num_threads <= 1 || d < PARALLEL_MIN_DEPTH {
+            let mut depth_lines: Vec<PvLine> = Vec::new();
+            if self.multi_pv > 1 {
+                let mut params = RootSearchParams {
+                    time_budget_ms,
+                    stop,
+                    pondering,
+                    start: &start,
+                    last_info_time: &mut last_info_time,
+                    node_limit,
+                    counters: &counters,
+                    multipv: 1,
+                    listener: Some(&mut *listener),
+                    root_scores: Vec::new(),
+                    presorted: false,
+                    game_history: &limits.game_history,
+                };
+                depth_lines =
+                    self.search_root_multipv(root, d, &moves, &mut heuristics, &mut params);
+                if let Some(first) = depth_lines.first() {
+                    best_score = first.score;
+                    best_move = first.pv[0];
+                    best_pv = first.pv.clone();
+                    searched_any = true;
+                }
+            } else {
                 // Use serial search for single-threaded mode or shallow depths
                 // (parallel overhead not worth it for shallow searches)
                 let can_use_aspiration = d >= ASPIRATION_MIN_DEPTH
@@ -199,13 +578,18 @@ impl<M: MoveGenerator + Clone + Send + Sync + 'static, E: Evaluator + Clone + Se
                         let mut params = RootSearchParams {
                             time_budget_ms,
                             stop,
+                            pondering,
                             start: &start,
                             last_info_time: &mut last_info_time,
+                            node_limit,
+                            counters: &counters,
+                            multipv: 1,
+                            listener: Some(&mut *listener),
+                            root_scores: Vec::new(),
+                            presorted: false,
+                            game_history: &limits.game_history,
                         };
-                        let window = SearchWindow {
-                            alpha: -beta,
-                            beta: -alpha,
-                        };
+                        let window = SearchWindow { alpha, beta };
                         let (window_best_move, window_best_score, window_searched_any) = self
                             .search_root_serial_window(
                                 root,
@@ -219,6 +603,7 @@ impl<M: MoveGenerator + Clone + Send + Sync + 'static, E: Evaluator + Clone + Se
                         best_move = window_best_move;
                         best_score = window_best_score;
                         searched_any = window_searched_any;
+                        best_pv = self.pv.line(0).to_vec();
 
                         // On timeout/stop before any move, keep previous completed result.
                         if !searched_any {
@@ -231,13 +616,43 @@ impl<M: MoveGenerator + Clone + Send + Sync + 'static, E: Evaluator + Clone + Se
                             break;
                         }
 
+                        // Report the failed window so GUIs can show the trend.
+                        #[allow(clippy::cast_possible_truncation)]
+                        let elapsed = start.elapsed().as_millis() as u64;
+                        let bound = if fail_high {
+                            ScoreBound::Lower
+                        } else {
+                            ScoreBound::Upper
+                        };
+                        listener.on_update(&SearchUpdate {
+                            depth: d,
+                            seldepth: self.stats.seldepth.max(d),
+                            multipv: 1,
+                            score: best_score,
+                            bound,
+                            pv: &best_pv,
+                            nodes: counters.nodes(),
+                            elapsed_ms: elapsed,
+                            hashfull: self.tt.hashfull_per_mille(),
+                            tbhits: counters.tb_hits(),
+                        });
+
                         researches += 1;
+                        self.stats.aspiration_researches += 1;
                         if researches >= ASPIRATION_MAX_RESEARCHES {
                             let mut params = RootSearchParams {
                                 time_budget_ms,
                                 stop,
+                                pondering,
                                 start: &start,
                                 last_info_time: &mut last_info_time,
+                                node_limit,
+                                counters: &counters,
+                                multipv: 1,
+                                listener: Some(&mut *listener),
+                                root_scores: Vec::new(),
+                                presorted: false,
+                                game_history: &limits.game_history,
                             };
                             let window = SearchWindow {
                                 alpha: -INF,
@@ -255,6 +670,7 @@ impl<M: MoveGenerator + Clone + Send + Sync + 'static, E: Evaluator + Clone + Se
                             best_move = full_best_move;
                             best_score = full_best_score;
                             searched_any = full_searched_any;
+                            best_pv = self.pv.line(0).to_vec();
                             break;
                         }
 
@@ -270,8 +686,16 @@ impl<M: MoveGenerator + Clone + Send + Sync + 'static, E: Evaluator + Clone + Se
                     let mut params = RootSearchParams {
                         time_budget_ms,
                         stop,
+                        pondering,
                         start: &start,
                         last_info_time: &mut last_info_time,
+                        node_limit,
+                        counters: &counters,
+                        multipv: 1,
+                        listener: Some(&mut *listener),
+                        root_scores: Vec::new(),
+                        presorted: false,
+                        game_history: &limits.game_history,
                     };
                     let window = SearchWindow {
                         alpha: -INF,
@@ -285,135 +709,7 @@ impl<M: MoveGenerator + Clone + Send + Sync + 'static, E: Evaluator + Clone + Se
                         &mut params,
                         &window,
                     );
-                }
-            } else {
-                // Parallel root move evaluation using rayon with persistent thread pool
-                use rayon::prelude::*;
-
-                // Use the persistent thread pool (already created in set_num_threads)
-                let pool = self
-                    .thread_pool
-                    .as_ref()
-                    .expect("Thread pool not initialized");
-
-                // Clone components into the closure so each thread owns its data.
-                let mg = self.movegen.clone();
-                let ev = self.evaluator.clone();
-                let arena_cap = self.arena_capacity;
-
-                // Shared atomic alpha for basic cutoffs across threads
-                // Use -INF instead of i32::MIN to avoid overflow when negating values
-                let shared_alpha = Arc::new(AtomicI32::new(-INF));
-
-                // Use thread-local storage to reuse arenas and TTs across moves
-                thread_local! {
-                    static THREAD_ARENA: RefCell<Option<Arena>> = const { RefCell::new(None) };
-                    static THREAD_TT: RefCell<Option<crate::core::tt::TranspositionTable>> = const { RefCell::new(None) };
-                }
-
-                let parallel_best = pool.install(|| {
-                    moves
-                        .as_slice()
-                        .par_iter()
-                        .copied()
-                        .map(move |m| {
-                            // Get or create thread-local arena (reused across all moves in this
-                            // thread)
-                            let score = THREAD_ARENA.with(|arena_cell| {
-                                let mut arena_opt = arena_cell.borrow_mut();
-                                if arena_opt.is_none() {
-                                    *arena_opt = Some(Arena::new(arena_cap));
-                                }
-                                let local_arena = arena_opt.as_mut().unwrap();
-
-                                local_arena.get_mut(0).position.copy_from(root);
-                                let child_pos = {
-                                    let parent = local_arena.get_mut(0);
-                                    let undo = parent.position.make_move(&m);
-                                    let child = parent.position;
-                                    parent.position.unmake_move(&m, &undo);
-                                    child
-                                };
-                                local_arena.get_mut(1).position = child_pos;
-
-                                let mut repetition_history = [0u64; MAX_REPETITION_HISTORY];
-                                repetition_history[0] = root.zobrist_hash();
-                                repetition_history[1] = local_arena.get(1).position.zobrist_hash();
-
-                                // Get or create thread-local TT (reused across all moves in this
-                                // thread)
-                                THREAD_TT.with(|tt_cell| {
-                                    let mut tt_opt = tt_cell.borrow_mut();
-                                    if tt_opt.is_none() {
-                                        // 16MB per thread (2^20 entries)
-                                        *tt_opt =
-                                            Some(crate::core::tt::TranspositionTable::new(20));
-                                    }
-                                    let local_tt = tt_opt.as_mut().unwrap();
-                                    let mut local_heuristics = SearchHeuristics::new();
-
-                                    // Use shared alpha for better cutoffs in parallel search
-                                    let current_alpha = shared_alpha.load(Ordering::Relaxed);
-
-                                    let mut ctx = SearchContext {
-                                        movegen: &mg,
-                                        evaluator: &ev,
-                                        tt: local_tt,
-                                        heuristics: &mut local_heuristics,
-                                        stop,
-                                        time_budget_ms,
-                                        start_time: Some(&start),
-                                    };
-
-                                    let score = -search_node_with_arena(
-                                        &mut ctx,
-                                        local_arena,
-                                        1,
-                                        d - 1,
-                                        &mut SearchWindow {
-                                            alpha: -INF,
-                                            beta: -current_alpha,
-                                        },
-                                        &mut RepetitionState {
-                                            history: repetition_history,
-                                            len: 2,
-                                        },
-                                    );
-
-                                    // Publish this worker's batched node count.
-                                    flush_local_node_count();
-
-                                    // Update shared alpha if we found a better move
-                                    loop {
-                                        let current = shared_alpha.load(Ordering::Relaxed);
-                                        if score <= current {
-                                            break;
-                                        }
-                                        if shared_alpha
-                                            .compare_exchange(
-                                                current,
-                                                score,
-                                                Ordering::Relaxed,
-                                                Ordering::Relaxed,
-                                            )
-                                            .is_ok()
-                                        {
-                                            break;
-                                        }
-                                    }
-
-                                    score
-                                })
-                            });
-                            (m, score)
-                        })
-                        .reduce_with(|a, b| if a.1 >= b.1 { a } else { b })
-                });
-
-                if let Some((m, score)) = parallel_best {
-                    best_score = score;
-                    best_move = m;
-                    searched_any = true;
+                    best_pv = self.pv.line(0).to_vec();
                 }
             }
 
@@ -428,49 +724,192 @@ impl<M: MoveGenerator + Clone + Send + Sync + 'static, E: Evaluator + Clone + Se
             }
 
             // Completed this depth successfully; compute elapsed and print UCI info.
+            if depth_lines.is_empty() {
+                let pv = if best_pv.first() == Some(&best_move) {
+                    best_pv
+                } else {
+                    vec![best_move]
+                };
+                depth_lines.push(PvLine {
+                    score: best_score,
+                    pv,
+                });
+            }
+            for line in &mut depth_lines {
+                self.extend_pv_from_tt(root, &mut line.pv, d);
+            }
+            // A limit may have cut the iteration short. Its best move still
+            // stands, since every move it did search has a full score, but
+            // the depth does not count as completed and the moves it did
+            // not reach keep their scores from the previous iteration.
+            let interrupted = search_limit_reached(
+                stop,
+                pondering,
+                time_budget_ms,
+                Some(&start),
+                node_limit,
+                &counters,
+            );
             last_completed_move = best_move;
             last_completed_score = best_score;
+            last_completed_pv = depth_lines[0].pv.clone();
+            let mut scores = std::mem::take(&mut self.iteration_scores);
+            if interrupted {
+                let reached: Vec<ChessMove> = scores.iter().map(|r| r.mv).collect();
+                scores.extend(self.root_scores.iter().filter(|r| !reached.contains(&r.mv)));
+            } else {
+                self.completed_depth = d;
+            }
+            scores.sort_by_key(|r| std::cmp::Reverse(r.score));
+            self.root_scores = scores;
             #[allow(clippy::cast_possible_truncation)]
             let elapsed = start.elapsed().as_millis() as u64;
-            let pv_str = if crate::VERBOSE.load(Ordering::Relaxed) && !last_completed_move.is_null()
-            {
-                last_completed_move.to_string()
-            } else {
-                String::new()
-            };
-            let seldepth = current_seldepth().max(d);
-            let hashfull = self.tt.read().unwrap().hashfull_per_mille();
-            // Always print info at the end of each depth
-            crate::search::core::print_uci_info(
-                d,
-                seldepth,
-                last_completed_score,
-                &pv_str,
-                elapsed,
-                hashfull,
-            );
+            let seldepth = self.stats.seldepth.max(d);
+            let hashfull = self.tt.hashfull_per_mille();
+            let nodes = counters.nodes();
+            let tbhits = counters.tb_hits();
+            // Always report the end of each depth, one update per PV
+            for (k, line) in depth_lines.iter().enumerate() {
+                listener.on_update(&SearchUpdate {
+                    depth: d,
+                    seldepth,
+                    multipv: k + 1,
+                    score: line.score,
+                    bound: ScoreBound::Exact,
+                    pv: &line.pv,
+                    nodes,
+                    elapsed_ms: elapsed,
+                    hashfull,
+                    tbhits,
+                });
+            }
+            self.last_lines = depth_lines;
 
-            // Stop if time budget exceeded or external stop requested
-            if let Some(mt) = time_budget_ms
-                && elapsed >= mt
+            // Stop if a time/node budget is exhausted or external stop requested
+            if interrupted {
+                break;
+            }
+            time_manager.on_iteration(best_move, best_score);
+            if clock_running(pondering)
+                && time_manager.should_stop_after_iteration(elapsed, &self.root_scores)
             {
                 break;
             }
-            if let Some(stopflag) = stop
-                && stopflag.load(Ordering::Relaxed)
+            if let Some(mate) = limits.mate
+                && last_completed_score > MATE_SCORE - 100
+                && (MATE_SCORE - last_completed_score + 1) / 2
+                    <= i32::try_from(mate).unwrap_or(i32::MAX)
             {
                 break;
             }
         }
 
+        if last_completed_pv.first() != Some(&last_completed_move) {
+            last_completed_pv = vec![last_completed_move];
+        }
+        if self.last_lines.is_empty() {
+            self.last_lines.push(PvLine {
+                score: last_completed_score,
+                pv: last_completed_pv.clone(),
+            });
+        }
+        self.last_pv = last_completed_pv;
+        self.heuristics = heuristics;
+        counters.flush_local_nodes();
+
         (last_completed_move, last_completed_score)
     }
 
+    /// MultiPV root search: one full-window pass per requested line, each
+    /// pass excluding the root moves already reported. Lines come back
+    /// sorted best first; a stop or timeout ends the passes early.
+    fn search_root_multipv(
+        &mut self,
+        root: &Position,
+        d: usize,
+        moves: &MoveList,
+        heuristics: &mut SearchHeuristics,
+        params: &mut RootSearchParams<'_>,
+    ) -> Vec<PvLine> {
+        let wanted = self.multi_pv.min(moves.len());
+        let mut lines: Vec<PvLine> = Vec::with_capacity(wanted);
+        let window = SearchWindow {
+            alpha: -INF,
+            beta: INF,
+        };
+
+        while lines.len() < wanted {
+            let mut remaining = MoveList::new();
+            for &m in moves.iter() {
+                if !lines.iter().any(|line| line.pv[0] == m) {
+                    remaining.push(m);
+                }
+            }
+
+            params.multipv = lines.len() + 1;
+            let (m, score, searched_any) = self.search_root_serial_window(
+                root,
+                d,
+                &mut remaining,
+                heuristics,
+                params,
+                &window,
+            );
+            let line = searched_any.then(|| {
+                let pv = self.pv.line(0);
+                let pv = if pv.first() == Some(&m) {
+                    pv.to_vec()
+                } else {
+                    vec![m]
+                };
+                PvLine { score, pv }
+            });
+
+            let Some(line) = line else {
+                break;
+            };
+            lines.push(line);
+        }
+
+        // Later passes can outscore earlier ones through search instability.
+        lines.sort_by_key(|line| std::cmp::Reverse(line.score));
+        lines
+    }
+
+    /// Lengthen `pv` with exact TT moves where the triangular table was cut
+    /// short (TT cutoffs, draws). Every move is checked for legality and the
+    /// walk stops on a repeated position.
+    fn extend_pv_from_tt(&self, root: &Position, pv: &mut Vec<ChessMove>, max_len: usize) {
+        let mut pos = *root;
+        let mut seen = Vec::with_capacity(max_len + 1);
+        seen.push(pos.zobrist_hash());
+        for mv in pv.iter() {
+            pos.make_move(mv);
+            seen.push(pos.zobrist_hash());
+        }
+
+        let tt = &self.tt;
+        while pv.len() < max_len {
+            let Some(mv) = tt.exact_move(*seen.last().unwrap()) else {
+                break;
+            };
+            if !self.movegen.is_pseudo_legal(&pos, &mv) || !self.movegen.is_legal(&pos, &mv) {
+                break;
+            }
+            pos.make_move(&mv);
+            let key = pos.zobrist_hash();
+            if seen.contains(&key) {
+                break;
+            }
+            seen.push(key);
+            pv.push(mv);
+        }
+    }
+
     fn probe_for_best_move(&mut self, d: usize, moves: &mut MoveList) {
         let key = self.arena.get(0).position.zobrist_hash();
-        let tt_guard = self.tt.read().unwrap();
         #[allow(clippy::cast_possible_truncation)]
-        if let Some(e) = tt_guard.probe(key, d as i8, -INF, INF) {
+        if let Some(e) = self.tt.probe(key, d as i8, -INF, INF, 0) {
             let bmove = e.best_move;
             if bmove.is_null() {
                 return;
@@ -486,7 +925,7 @@ impl<M: MoveGenerator + Clone + Send + Sync + 'static, E: Evaluator + Clone + Se
             }
 
             #[cfg(debug_assertions)]
-            if crate::VERBOSE.load(Ordering::Relaxed) {
+            if self.verbose {
                 eprintln!(
                     "[debug] TT best move not found in root move list: move={bmove} key={key}"
                 );
@@ -494,16 +933,15 @@ impl<M: MoveGenerator + Clone + Send + Sync + 'static, E: Evaluator + Clone + Se
         }
     }
 
-    /// Clear all search state including node counts, tablebase hits, and
-    /// transposition table.
-    ///
-    /// # Panics
-    ///
-    /// Panics if the transposition table lock is poisoned.
+    /// Clear all search state including node counts, tablebase hits,
+    /// move-ordering statistics and transposition table.
     pub fn clear_state(&mut self) {
-        reset_node_count();
-        crate::search::core::TB_HITS.store(0, Ordering::Relaxed);
-        self.tt.write().unwrap().clear();
+        self.counters.reset();
+        self.tt.clear();
+        self.heuristics = SearchHeuristics::new();
+        for helper in &mut self.helpers {
+            helper.heuristics = SearchHeuristics::new();
+        }
     }
 
     /// Set the path to Syzygy tablebase files.
@@ -513,7 +951,36 @@ impl<M: MoveGenerator + Clone + Send + Sync + 'static, E: Evaluator + Clone + Se
     /// Returns an error if the tablebase path is invalid or cannot be
     /// initialized.
     pub fn set_tablebase_path(&mut self, path: &str) -> Result<(), String> {
-        crate::search::tablebase::set_syzygy_path(path)
+        self.tablebase = SyzygyTablebase::open(path)?.map(Arc::new);
+        Ok(())
+    }
+
+    /// The `Syzygy*` options in effect.
+    pub const fn tablebase_config(&self) -> TbConfig {
+        self.tb_config
+    }
+
+    /// `SyzygyProbeDepth`: least remaining depth for probes inside the
+    /// tree. Clamped to at least 1.
+    pub fn set_syzygy_probe_depth(&mut self, depth: usize) {
+        self.tb_config.probe_depth = depth.max(1);
+    }
+
+    /// `SyzygyProbeLimit`: most pieces of a probed position; 0 disables
+    /// probing. Clamped to `MAX_TB_PIECES`.
+    pub fn set_syzygy_probe_limit(&mut self, pieces: usize) {
+        self.tb_config.probe_limit = pieces.min(MAX_TB_PIECES);
+    }
+
+    /// `Syzygy50MoveRule`: whether wins and losses the 50-move rule spoils
+    /// count as draws.
+    pub const fn set_syzygy_50_move_rule(&mut self, rule50: bool) {
+        self.tb_config.rule50 = rule50;
+    }
+
+    /// Syzygy tables set by `set_tablebase_path`, if any.
+    pub fn tablebase(&self) -> Option<&SyzygyTablebase> {
+        self.tablebase.as_deref()
     }
 
     fn search_root_serial_window(
@@ -525,146 +992,441 @@ impl<M: MoveGenerator + Clone + Send + Sync + 'static, E: Evaluator + Clone + Se
         params: &mut RootSearchParams<'_>,
         window: &SearchWindow,
     ) -> (ChessMove, i32, bool) {
-        // Serial path: get exclusive access to the TT
-        let mut tt_guard = self.tt.write().unwrap();
-        let tt_ref: &mut crate::core::tt::TranspositionTable = &mut tt_guard;
-
-        let mut best_score = i32::MIN;
-        let mut best_move = *moves.get(0).unwrap();
-        let mut searched_any = false;
-        let mut local_alpha = window.alpha;
-
-        for i in 0..moves.len() {
-            // Pick best move for this iteration
-            pick_best_move(moves, heuristics, root, 0, i);
-            let m = *moves.get(i).unwrap();
-            // Check stop flag and time budget before each root move.
-            let now = Instant::now();
-            #[allow(clippy::cast_possible_truncation)]
-            let elapsed = now.duration_since(*params.start).as_millis() as u64;
-            if let Some(mt) = params.time_budget_ms
-                && elapsed >= mt
-            {
-                break;
-            }
-            if let Some(stopflag) = params.stop
-                && stopflag.load(Ordering::Relaxed)
-            {
-                break;
-            }
+        let mut ctx = SearchContext {
+            movegen: &self.movegen,
+            evaluator: &self.evaluator,
+            tt: &self.tt,
+            heuristics,
+            pv: &mut self.pv,
+            stats: &mut self.stats,
+            stop: params.stop,
+            pondering: params.pondering,
+            time_budget_ms: params.time_budget_ms,
+            start_time: Some(params.start),
+            node_limit: params.node_limit,
+            counters: params.counters,
+            tablebase: TbProbe::new(self.tablebase.as_deref(), self.tb_search),
+            root_depth: d,
+        };
+        let result = search_root_window(&mut ctx, &mut self.arena, root, d, moves, params, window);
+        // Aspiration re-searches start the iteration's scores over; MultiPV
+        // passes add to them.
+        if params.multipv == 1 {
+            self.iteration_scores.clear();
+        }
+        self.iteration_scores.append(&mut params.root_scores);
+        result
+    }
+}
 
-            let child_pos = {
-                let parent = self.arena.get_mut(0);
-                let undo = parent.position.make_move(&m);
-                let child = parent.position;
-                parent.position.unmake_move(&m, &undo);
-                child
-            };
-            self.arena.get_mut(1).position = child_pos;
+/// Legal root moves, narrowed to `limits.search_moves` when any of them is
+/// legal here.
+fn root_moves<M: MoveGenerator>(movegen: &M, root: &Position, limits: &SearchLimits) -> MoveList {
+    let mut moves = movegen.legal_moves(root);
+    if limits
+        .search_moves
+        .iter()
+        .any(|m| moves.as_slice().contains(m))
+    {
+        moves = MoveList::from_vec(
+            moves
+                .iter()
+                .copied()
+                .filter(|m| limits.search_moves.contains(m))
+                .collect(),
+        );
+    }
+    moves
+}
 
-            let mut repetition_history = [0u64; MAX_REPETITION_HISTORY];
-            repetition_history[0] = root.zobrist_hash();
-            repetition_history[1] = self.arena.get(1).position.zobrist_hash();
+/// Search every root move inside `window` with PVS, keeping the root line in
+/// `ctx.pv`. Shared by the main thread and the Lazy SMP helpers. Returns the
+/// best move and score, and whether any move finished before a limit hit.
+fn search_root_window<M: MoveGenerator, E: Evaluator>(
+    ctx: &mut SearchContext<'_, M, E>,
+    arena: &mut Arena,
+    root: &Position,
+    d: usize,
+    moves: &mut MoveList,
+    params: &mut RootSearchParams<'_>,
+    window: &SearchWindow,
+) -> (ChessMove, i32, bool) {
+    let mut best_score = i32::MIN;
+    let mut best_move = *moves.get(0).unwrap();
+    let mut searched_any = false;
+    let mut local_alpha = window.alpha;
+    let mut rep = RepetitionState::new(params.game_history, root.zobrist_hash());
+    ctx.pv.clear(0);
+
+    for i in 0..moves.len() {
+        // Pick best move for this iteration
+        if !params.presorted {
+            pick_best_move(moves, ctx.heuristics, root, 0, i);
+        }
+        let m = *moves.get(i).unwrap();
+        // Check stop flag, time and node budgets before each root move.
+        let now = Instant::now();
+        #[allow(clippy::cast_possible_truncation)]
+        let elapsed = now.duration_since(*params.start).as_millis() as u64;
+        if params.limit_reached() {
+            break;
+        }
 
-            let mut ctx = SearchContext {
-                movegen: &self.movegen,
-                evaluator: &self.evaluator,
-                tt: tt_ref,
-                heuristics,
-                stop: params.stop,
-                time_budget_ms: params.time_budget_ms,
-                start_time: Some(params.start),
+        let child_pos = {
+            let parent = arena.get_mut(0);
+            let undo = parent.position.make_move(&m);
+            let child = parent.position;
+            parent.position.unmake_move(&m, &undo);
+            child
+        };
+        arena.get_mut(1).position = child_pos;
+
+        rep.history.push(arena.get(1).position.zobrist_hash());
+        ctx.heuristics.record_move(0, root, &m);
+
+        let score = if i == 0 {
+            let mut search_window = SearchWindow {
+                alpha: -window.beta,
+                beta: -local_alpha,
+            };
+            -search_node_with_arena(ctx, arena, 1, d - 1, &mut search_window, &mut rep)
+        } else {
+            let mut search_window = SearchWindow {
+                alpha: -local_alpha - 1,
+                beta: -local_alpha,
             };
+            let mut pvs_score =
+                -search_node_with_arena(ctx, arena, 1, d - 1, &mut search_window, &mut rep);
 
-            let score = if i == 0 {
-                let mut search_window = SearchWindow {
+            if pvs_score > local_alpha && pvs_score < window.beta {
+                let mut full_window = SearchWindow {
                     alpha: -window.beta,
                     beta: -local_alpha,
                 };
-                let mut rep = RepetitionState {
-                    history: repetition_history,
-                    len: 2,
-                };
-                -search_node_with_arena(
-                    &mut ctx,
-                    &mut self.arena,
-                    1,
-                    d - 1,
-                    &mut search_window,
-                    &mut rep,
-                )
-            } else {
-                let mut search_window = SearchWindow {
-                    alpha: -local_alpha - 1,
-                    beta: -local_alpha,
-                };
-                let mut rep = RepetitionState {
-                    history: repetition_history,
-                    len: 2,
-                };
-                let mut pvs_score = -search_node_with_arena(
-                    &mut ctx,
-                    &mut self.arena,
-                    1,
-                    d - 1,
-                    &mut search_window,
-                    &mut rep,
-                );
+                pvs_score =
+                    -search_node_with_arena(ctx, arena, 1, d - 1, &mut full_window, &mut rep);
+            }
 
-                if pvs_score > local_alpha && pvs_score < window.beta {
-                    let mut full_window = SearchWindow {
-                        alpha: -window.beta,
-                        beta: -local_alpha,
-                    };
-                    let mut full_rep = RepetitionState {
-                        history: repetition_history,
-                        len: 2,
-                    };
-                    pvs_score = -search_node_with_arena(
-                        &mut ctx,
-                        &mut self.arena,
-                        1,
-                        d - 1,
-                        &mut full_window,
-                        &mut full_rep,
-                    );
-                }
+            pvs_score
+        };
 
-                pvs_score
-            };
+        rep.history.pop();
 
-            if !searched_any || score > best_score {
-                best_score = score;
-                best_move = m;
-            }
-            searched_any = true;
+        // A search cut short by a limit returns a meaningless score.
+        if params.limit_reached() {
+            break;
+        }
 
-            if score > local_alpha {
-                local_alpha = score;
-            }
-            if local_alpha >= window.beta {
+        let bound = if score >= window.beta {
+            ScoreBound::Lower
+        } else if score <= local_alpha {
+            ScoreBound::Upper
+        } else {
+            ScoreBound::Exact
+        };
+        params.root_scores.push(RootMoveScore {
+            mv: m,
+            score,
+            bound,
+        });
+
+        if !searched_any || score > best_score {
+            best_score = score;
+            best_move = m;
+            ctx.pv.update(0, m);
+        }
+        searched_any = true;
+
+        if score > local_alpha {
+            local_alpha = score;
+        }
+        if local_alpha >= window.beta {
+            break;
+        }
+
+        // Periodic progress info is useful for timed UCI searches,
+        // but it is expensive noise for fixed-depth bench runs.
+        if params.time_budget_ms.is_some()
+            && now.duration_since(*params.last_info_time).as_millis() >= 1000
+            && let Some(listener) = params.listener.as_deref_mut()
+        {
+            listener.on_update(&SearchUpdate {
+                depth: d,
+                seldepth: ctx.stats.seldepth.max(d),
+                multipv: params.multipv,
+                score: best_score,
+                bound: ScoreBound::Exact,
+                pv: ctx.pv.line(0),
+                nodes: ctx.counters.nodes(),
+                elapsed_ms: elapsed,
+                hashfull: ctx.tt.hashfull_per_mille(),
+                tbhits: ctx.counters.tb_hits(),
+            });
+            *params.last_info_time = now;
+        }
+    }
+
+    (best_move, best_score, searched_any)
+}
+
+/// Root order of helper `id`: the TT move first, then the other moves
+/// rotated by `id`; without a TT move the whole list rotates. Helpers thus
+/// start on different moves and fill different parts of the shared TT.
+fn order_helper_root(moves: &mut MoveList, tt_move: Option<ChessMove>, id: usize) {
+    let slice = moves.as_mut_slice();
+    let rest = match slice.iter().position(|m| Some(*m) == tt_move) {
+        Some(pos) => {
+            slice.swap(0, pos);
+            &mut slice[1..]
+        }
+        None => slice,
+    };
+    if rest.len() > 1 {
+        let shift = id % rest.len();
+        rest.rotate_left(shift);
+    }
+}
+
+/// Per-helper search state, kept across searches so arenas and tables are
+/// allocated once per `Threads` setting.
+struct HelperThread {
+    arena: Arena,
+    pv: PvTable,
+    heuristics: SearchHeuristics,
+    stats: SearchStats,
+}
+
+/// Score to report for a search score at a tablebase root: the tables'
+/// verdict, unless the search has proven a mate.
+fn tb_reported_score(search_score: i32, tb_score: i32) -> i32 {
+    if search_score.abs() >= MATE_SCORE - MAX_SEARCH_PLY as i32 {
+        search_score
+    } else {
+        tb_score
+    }
+}
+
+/// Passes progress on with the root's tablebase score in place of the
+/// search's.
+struct TbScoreListener<'a> {
+    inner: &'a mut dyn SearchListener,
+    score: i32,
+}
+
+impl SearchListener for TbScoreListener<'_> {
+    fn on_update(&mut self, update: &SearchUpdate<'_>) {
+        let score = tb_reported_score(update.score, self.score);
+        let bound = if score == update.score {
+            update.bound
+        } else {
+            ScoreBound::Exact
+        };
+        self.inner.on_update(&SearchUpdate {
+            score,
+            bound,
+            ..*update
+        });
+    }
+}
+
+/// Everything a helper needs for one search. Owned copies keep the spawned
+/// task independent of the engine, which the main thread borrows mutably.
+struct HelperJob<'a, M, E> {
+    /// 1-based helper index; 0 is the main thread.
+    id: usize,
+    movegen: M,
+    evaluator: E,
+    tt: Arc<TranspositionTable>,
+    counters: Arc<SearchCounters>,
+    tablebase: Option<Arc<SyzygyTablebase>>,
+    tb_search: Option<TbConfig>,
+    root: Position,
+    limits: SearchLimits,
+    /// Raised by the main thread once it has its result.
+    stop: &'a AtomicBool,
+}
+
+impl HelperThread {
+    fn new(arena_capacity: usize) -> Self {
+        Self {
+            arena: Arena::new(arena_capacity),
+            pv: PvTable::new(),
+            heuristics: SearchHeuristics::new(),
+            stats: SearchStats::default(),
+        }
+    }
+
+    /// Helper iterations whose depth this helper leaves to its siblings.
+    fn skips_depth(id: usize, d: usize) -> bool {
+        let j = (id - 1) % SKIP_SIZE.len();
+        ((d + SKIP_PHASE[j]) / SKIP_SIZE[j]) % 2 == 1
+    }
+
+    /// Iterative deepening on the shared TT until the main thread stops us.
+    /// Results are only published through the TT. Root moves are searched
+    /// in `order_helper_root` order, so helpers explore the root
+    /// differently.
+    fn run<M: MoveGenerator, E: Evaluator>(&mut self, job: &HelperJob<'_, M, E>) {
+        let start = Instant::now();
+        let mut last_info_time = start;
+        self.heuristics.age();
+        self.stats = SearchStats::default();
+        let window = SearchWindow {
+            alpha: -INF,
+            beta: INF,
+        };
+
+        for d in 1..=job.limits.depth {
+            if job.stop.load(Ordering::Relaxed) {
                 break;
             }
+            if Self::skips_depth(job.id, d) {
+                continue;
+            }
 
-            // Periodic progress info is useful for timed UCI searches,
-            // but it is expensive noise for fixed-depth bench runs.
-            if params.time_budget_ms.is_some()
-                && now.duration_since(*params.last_info_time).as_millis() >= 1000
-            {
-                let pv_str = if crate::VERBOSE.load(Ordering::Relaxed) && !best_move.is_null() {
-                    best_move.to_string()
-                } else {
-                    "".to_string()
-                };
-                let seldepth = current_seldepth().max(d);
-                let hashfull = tt_ref.hashfull_per_mille();
-                crate::search::core::print_uci_info(
-                    d, seldepth, best_score, &pv_str, elapsed, hashfull,
-                );
-                *params.last_info_time = now;
+            self.arena.reset();
+            self.arena.get_mut(0).position.copy_from(&job.root);
+            let mut moves = root_moves(&job.movegen, &job.root, &job.limits);
+            if moves.is_empty() {
+                break;
             }
+            let tt_move = job
+                .tt
+                .entry(job.root.zobrist_hash(), 0)
+                .map(|e| e.best_move);
+            order_helper_root(&mut moves, tt_move, job.id);
+
+            let mut params = RootSearchParams {
+                time_budget_ms: None,
+                stop: Some(job.stop),
+                pondering: None,
+                start: &start,
+                last_info_time: &mut last_info_time,
+                node_limit: job.limits.nodes,
+                counters: &job.counters,
+                multipv: 1,
+                listener: None,
+                root_scores: Vec::new(),
+                presorted: true,
+                game_history: &job.limits.game_history,
+            };
+            let mut ctx = SearchContext {
+                movegen: &job.movegen,
+                evaluator: &job.evaluator,
+                tt: &job.tt,
+                heuristics: &mut self.heuristics,
+                pv: &mut self.pv,
+                stats: &mut self.stats,
+                stop: Some(job.stop),
+                pondering: None,
+                time_budget_ms: None,
+                start_time: Some(&start),
+                node_limit: job.limits.nodes,
+                counters: &job.counters,
+                tablebase: TbProbe::new(job.tablebase.as_deref(), job.tb_search),
+                root_depth: d,
+            };
+            search_root_window(
+                &mut ctx,
+                &mut self.arena,
+                &job.root,
+                d,
+                &mut moves,
+                &mut params,
+                &window,
+            );
+            job.counters.flush_local_nodes();
         }
+        job.counters.flush_local_nodes();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::evaluator::MaterialEvaluator;
+    use bitboard::movegen::SimpleMoveGen;
+
+    /// Root moves in the order a depth-1 helper search visits them.
+    fn helper_search_order(id: usize, tt_move: Option<ChessMove>) -> Vec<ChessMove> {
+        // Captures here would be picked first without the helper order.
+        let root = Position::from_fen(
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        );
+        let mut moves = SimpleMoveGen.legal_moves(&root);
+        order_helper_root(&mut moves, tt_move, id);
+        let ordered = moves.as_slice().to_vec();
+
+        let mut arena = Arena::new(64);
+        arena.get_mut(0).position = root;
+        let tt = TranspositionTable::new(1);
+        let mut heuristics = SearchHeuristics::new();
+        let mut pv = PvTable::new();
+        let mut stats = SearchStats::default();
+        let counters = SearchCounters::default();
+        let start = Instant::now();
+        let mut last_info_time = start;
+        let mut ctx = SearchContext {
+            movegen: &SimpleMoveGen,
+            evaluator: &MaterialEvaluator,
+            tt: &tt,
+            heuristics: &mut heuristics,
+            pv: &mut pv,
+            stats: &mut stats,
+            stop: None,
+            pondering: None,
+            time_budget_ms: None,
+            start_time: Some(&start),
+            node_limit: None,
+            counters: &counters,
+            tablebase: None,
+            root_depth: 1,
+        };
+        let mut params = RootSearchParams {
+            time_budget_ms: None,
+            stop: None,
+            pondering: None,
+            start: &start,
+            last_info_time: &mut last_info_time,
+            node_limit: None,
+            counters: &counters,
+            multipv: 1,
+            listener: None,
+            root_scores: Vec::new(),
+            presorted: true,
+            game_history: &[],
+        };
+        let window = SearchWindow {
+            alpha: -INF,
+            beta: INF,
+        };
+        search_root_window(
+            &mut ctx,
+            &mut arena,
+            &root,
+            1,
+            &mut moves,
+            &mut params,
+            &window,
+        );
+
+        let searched: Vec<ChessMove> = params.root_scores.iter().map(|s| s.mv).collect();
+        assert_eq!(searched, ordered);
+        searched
+    }
 
-        (best_move, best_score, searched_any)
+    #[test]
+    fn test_helpers_search_root_in_their_own_order() {
+        // Without a TT move, helpers start on different moves.
+        let first = helper_search_order(1, None);
+        let second = helper_search_order(2, None);
+        assert_ne!(first[0], second[0]);
+
+        // A TT move stays first for every helper; the rest still differ.
+        let tt_move = second[5];
+        let first = helper_search_order(1, Some(tt_move));
+        let second = helper_search_order(2, Some(tt_move));
+        assert_eq!((first[0], second[0]), (tt_move, tt_move));
+        assert_ne!(first[1], second[1]);
     }
 }
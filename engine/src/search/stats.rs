@@ -0,0 +1,141 @@
+// src/search/stats.rs
+//
+// Search statistics. Every search thread counts into its own `SearchStats`
+// through `SearchContext`, so the hot path never touches shared memory; the
+// engine merges the threads' counters once the search is over.
+
+use std::fmt;
+
+/// Counters for one search on one thread, or merged over several.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SearchStats {
+    /// Nodes entered by the main search (including verification searches).
+    pub main_nodes: u64,
+    pub qsearch_nodes: u64,
+    pub tt_probes: u64,
+    pub tt_hits: u64,
+    pub tt_cutoffs: u64,
+    pub beta_cutoffs: u64,
+    /// Beta cutoffs produced by the first legal move searched.
+    pub first_move_cutoffs: u64,
+    pub null_move_tries: u64,
+    pub null_move_cutoffs: u64,
+    pub probcut_cutoffs: u64,
+    pub lmr_researches: u64,
+    pub rfp_prunes: u64,
+    pub lmp_prunes: u64,
+    pub futility_prunes: u64,
+    pub see_prunes: u64,
+    pub aspiration_researches: u64,
+    /// Deepest ply reached in the current iteration, quiescence included.
+    pub seldepth: usize,
+}
+
+/// `part` per mille of `whole`; 0 for an empty `whole`.
+fn per_mille(part: u64, whole: u64) -> u64 {
+    (part * 1000).checked_div(whole).unwrap_or(0)
+}
+
+impl SearchStats {
+    /// Add `other`'s counters to these.
+    pub const fn merge(&mut self, other: &Self) {
+        self.main_nodes += other.main_nodes;
+        self.qsearch_nodes += other.qsearch_nodes;
+        self.tt_probes += other.tt_probes;
+        self.tt_hits += other.tt_hits;
+        self.tt_cutoffs += other.tt_cutoffs;
+        self.beta_cutoffs += other.beta_cutoffs;
+        self.first_move_cutoffs += other.first_move_cutoffs;
+        self.null_move_tries += other.null_move_tries;
+        self.null_move_cutoffs += other.null_move_cutoffs;
+        self.probcut_cutoffs += other.probcut_cutoffs;
+        self.lmr_researches += other.lmr_researches;
+        self.rfp_prunes += other.rfp_prunes;
+        self.lmp_prunes += other.lmp_prunes;
+        self.futility_prunes += other.futility_prunes;
+        self.see_prunes += other.see_prunes;
+        self.aspiration_researches += other.aspiration_researches;
+        if other.seldepth > self.seldepth {
+            self.seldepth = other.seldepth;
+        }
+    }
+}
+
+/// One line of `key value` pairs, suitable for `info string`.
+impl fmt::Display for SearchStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pct = |part, whole| {
+            let tenths = per_mille(part, whole);
+            format!("{}.{}%", tenths / 10, tenths % 10)
+        };
+        write!(
+            f,
+            "nodes {} qnodes {} tt probes {} hits {} cutoffs {} \
+             betacuts {} firstmove {} null {}/{} probcut {} lmr-research {} \
+             pruned rfp {} lmp {} futility {} see {} aspiration-research {}",
+            self.main_nodes,
+            self.qsearch_nodes,
+            self.tt_probes,
+            pct(self.tt_hits, self.tt_probes),
+            self.tt_cutoffs,
+            self.beta_cutoffs,
+            pct(self.first_move_cutoffs, self.beta_cutoffs),
+            self.null_move_cutoffs,
+            self.null_move_tries,
+            self.probcut_cutoffs,
+            self.lmr_researches,
+            self.rfp_prunes,
+            self.lmp_prunes,
+            self.futility_prunes,
+            self.see_prunes,
+            self.aspiration_researches,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_adds_counters() {
+        let mut a = SearchStats {
+            main_nodes: 10,
+            beta_cutoffs: 4,
+            first_move_cutoffs: 3,
+            ..SearchStats::default()
+        };
+        let b = SearchStats {
+            main_nodes: 5,
+            qsearch_nodes: 7,
+            beta_cutoffs: 4,
+            first_move_cutoffs: 4,
+            ..SearchStats::default()
+        };
+        a.merge(&b);
+        assert_eq!(a.main_nodes, 15);
+        assert_eq!(a.qsearch_nodes, 7);
+        assert_eq!(a.beta_cutoffs, 8);
+        assert_eq!(a.first_move_cutoffs, 7);
+    }
+
+    #[test]
+    fn test_display_reports_rates() {
+        let stats = SearchStats {
+            tt_probes: 8,
+            tt_hits: 2,
+            beta_cutoffs: 3,
+            first_move_cutoffs: 2,
+            ..SearchStats::default()
+        };
+        let line = stats.to_string();
+        assert!(line.contains("tt probes 8 hits 25.0%"), "{line}");
+        assert!(line.contains("firstmove 66.6%"), "{line}");
+        // No division by zero on an empty search.
+        assert!(
+            SearchStats::default()
+                .to_string()
+                .contains("firstmove 0.0%")
+        );
+    }
+}
@@ -1,98 +1,354 @@
 use bitboard::mov::ChessMove;
 use bitboard::movegen::generate_legal_moves_fast;
 use bitboard::position::Position;
-use once_cell::sync::Lazy;
 use shakmaty::CastlingMode;
 use shakmaty::Chess;
+use shakmaty::Position as _;
 use shakmaty::fen::Fen;
-use shakmaty_syzygy::AmbiguousWdl;
+use shakmaty_syzygy::Dtz;
 use shakmaty_syzygy::Tablebase;
+use shakmaty_syzygy::Wdl;
 use std::path::Path;
-use std::sync::RwLock;
 
-/// Very large score used for tablebase-proven wins/losses.
-const TB_WIN_CP: i32 = 20_000;
-/// Small score used for cursed/blessed outcomes in 50-move constrained endings.
-const TB_CURSED_CP: i32 = 120;
+/// Score of a tablebase win. Below every mate score, so a tablebase line is
+/// never reported as a forced mate.
+pub const TB_WIN_SCORE: i32 = 20_000;
+/// Largest `SyzygyProbeLimit`: Syzygy tables go up to seven pieces.
+pub const MAX_TB_PIECES: usize = 7;
 
-static TABLEBASE: Lazy<RwLock<Option<Tablebase<Chess>>>> = Lazy::new(|| RwLock::new(None));
+/// Root rank of a win (or loss) that is safe from the 50-move rule.
+const TB_RANK_WIN: i32 = 1000;
+/// With the 50-move rule, ranks below this are wins it turns into draws.
+const TB_RANK_RULE50_BOUND: i32 = 900;
 
-/// Configure Syzygy paths. Accepts one path or a ';'-separated list.
-pub fn set_syzygy_path(path_list: &str) -> Result<(), String> {
-    let trimmed = path_list.trim();
-    if trimmed.is_empty() {
-        *TABLEBASE.write().map_err(|_| "tablebase lock poisoned")? = None;
-        return Ok(());
+/// Options deciding when the tables are probed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TbConfig {
+    /// `SyzygyProbeDepth`: least remaining depth for probes inside the tree.
+    pub probe_depth: usize,
+    /// `SyzygyProbeLimit`: most pieces a probed position may have; 0 turns
+    /// probing off.
+    pub probe_limit: usize,
+    /// `Syzygy50MoveRule`: score wins and losses the 50-move rule spoils as
+    /// draws.
+    pub rule50: bool,
+}
+
+impl Default for TbConfig {
+    fn default() -> Self {
+        Self {
+            probe_depth: 1,
+            probe_limit: MAX_TB_PIECES,
+            rule50: true,
+        }
     }
+}
+
+/// Tablebase verdict on one root move. Higher ranks are better; all wins
+/// that are safe from the 50-move rule share the top rank.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RootTbMove {
+    pub mv: ChessMove,
+    pub rank: i32,
+    /// Score to report for the move, from the root side's view.
+    pub score: i32,
+}
 
-    let mut tb = Tablebase::<Chess>::new();
-    for dir in trimmed.split(';').map(str::trim).filter(|s| !s.is_empty()) {
-        tb.add_directory(Path::new(dir))
-            .map_err(|e| format!("failed to add syzygy directory '{dir}': {e}"))?;
+/// Root moves ranked by the tables, best first.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RootTbRanking {
+    pub moves: Vec<RootTbMove>,
+    /// Ranked by DTZ. Without DTZ tables the ranking only knows WDL and
+    /// cannot tell progress from shuffling.
+    pub dtz: bool,
+}
+
+impl RootTbRanking {
+    /// Moves that keep the best outcome the tables allow.
+    pub fn best_moves(&self) -> Vec<ChessMove> {
+        let top = self.moves[0].rank;
+        self.moves
+            .iter()
+            .take_while(|m| m.rank == top)
+            .map(|m| m.mv)
+            .collect()
     }
 
-    *TABLEBASE.write().map_err(|_| "tablebase lock poisoned")? = Some(tb);
-    Ok(())
+    /// Score of the position: that of the best moves.
+    pub fn score(&self) -> i32 {
+        self.moves[0].score
+    }
 }
 
-pub fn has_tablebases() -> bool {
-    TABLEBASE.read().map(|g| g.is_some()).unwrap_or(false)
+/// Syzygy tables loaded from one or more directories. Each engine owns its
+/// own set, shared by its search threads.
+pub struct SyzygyTablebase {
+    tables: Tablebase<Chess>,
 }
 
-/// Probe WDL and return a score from side-to-move perspective.
-pub fn probe_wdl_cp(pos: &Position) -> Option<i32> {
-    if pos.all_pieces().count() > 7 {
-        return None;
+impl SyzygyTablebase {
+    /// Load Syzygy tables from one path or a ';'-separated list; `None` for
+    /// an empty list.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a directory cannot be read.
+    pub fn open(path_list: &str) -> Result<Option<Self>, String> {
+        let trimmed = path_list.trim();
+        if trimmed.is_empty() {
+            return Ok(None);
+        }
+
+        let mut tables = Tablebase::<Chess>::new();
+        for dir in trimmed.split(';').map(str::trim).filter(|s| !s.is_empty()) {
+            tables
+                .add_directory(Path::new(dir))
+                .map_err(|e| format!("failed to add syzygy directory '{dir}': {e}"))?;
+        }
+        Ok(Some(Self { tables }))
     }
 
-    let fen_str = pos.to_fen();
-    let fen: Fen = fen_str.parse().ok()?;
-    let chess: Chess = fen.into_position(CastlingMode::Standard).ok()?;
+    /// Whether `pos` has few enough pieces for the loaded tables and `limit`.
+    fn covers(&self, pos: &Position, limit: usize) -> bool {
+        pos.all_pieces().count() as usize <= limit.min(self.tables.max_pieces())
+    }
+
+    /// WDL of `pos` as if its halfmove clock were zero, from the side to
+    /// move's view. Needs only the WDL tables.
+    fn probe_wdl(&self, pos: &Position) -> Option<Wdl> {
+        self.tables.probe_wdl_after_zeroing(&to_chess(pos)?).ok()
+    }
 
-    let guard = TABLEBASE.read().ok()?;
-    let tb = guard.as_ref()?;
-    let wdl = tb.probe_wdl(&chess).ok()?;
+    /// Rank every legal root move by the tables: by DTZ when the DTZ tables
+    /// are there, by WDL otherwise. `None` when `pos` is outside the tables,
+    /// beyond `config.probe_limit` or still has castling rights.
+    pub fn rank_root_moves(&self, pos: &Position, config: TbConfig) -> Option<RootTbRanking> {
+        if !self.covers(pos, config.probe_limit) {
+            return None;
+        }
+        let moves = generate_legal_moves_fast(pos);
+        if moves.is_empty() {
+            return None;
+        }
 
-    let score = match wdl {
-        AmbiguousWdl::Win | AmbiguousWdl::MaybeWin => TB_WIN_CP,
-        AmbiguousWdl::CursedWin => TB_CURSED_CP,
-        AmbiguousWdl::Draw => 0,
-        AmbiguousWdl::BlessedLoss => -TB_CURSED_CP,
-        AmbiguousWdl::Loss | AmbiguousWdl::MaybeLoss => -TB_WIN_CP,
-    };
+        let children: Vec<(ChessMove, Position, Chess)> = (0..moves.len())
+            .map(|i| {
+                let mv = moves[i];
+                let mut child = Position::default();
+                pos.apply_move_into(&mv, &mut child);
+                to_chess(&child).map(|chess| (mv, child, chess))
+            })
+            .collect::<Option<_>>()?;
 
-    Some(score)
+        let (mut ranked, dtz) = match self.rank_by_dtz(pos, &children, config) {
+            Some(ranked) => (ranked, true),
+            None => (self.rank_by_wdl(&children, config)?, false),
+        };
+        ranked.sort_by_key(|m| std::cmp::Reverse(m.rank));
+        Some(RootTbRanking { moves: ranked, dtz })
+    }
+
+    /// Rank moves by the DTZ after them, counted from the root. Wins that
+    /// zero the clock in time share the top rank; beyond that, and for
+    /// losses with a 50-move draw in sight, the clock decides.
+    fn rank_by_dtz(
+        &self,
+        pos: &Position,
+        children: &[(ChessMove, Position, Chess)],
+        config: TbConfig,
+    ) -> Option<Vec<RootTbMove>> {
+        children
+            .iter()
+            .map(|(mv, child, chess)| {
+                let dtz = if chess.is_checkmate() {
+                    1
+                } else if child.halfmove_clock == 0 {
+                    // A zeroing move: the outcome alone fixes the distance.
+                    Dtz::before_zeroing(-self.tables.probe_wdl_after_zeroing(chess).ok()?).0
+                } else {
+                    match -self.tables.probe_dtz(chess).ok()?.ignore_rounding().0 {
+                        d if d > 0 => d + 1,
+                        d if d < 0 => d - 1,
+                        _ => 0,
+                    }
+                };
+                let rank = dtz_rank(dtz, pos.halfmove_clock);
+                Some(RootTbMove {
+                    mv: *mv,
+                    rank,
+                    score: rank_score(rank, config.rule50),
+                })
+            })
+            .collect()
+    }
+
+    /// Rank moves by WDL alone, for tables without DTZ.
+    fn rank_by_wdl(
+        &self,
+        children: &[(ChessMove, Position, Chess)],
+        config: TbConfig,
+    ) -> Option<Vec<RootTbMove>> {
+        children
+            .iter()
+            .map(|(mv, _, chess)| {
+                let wdl = if chess.is_checkmate() {
+                    Wdl::Win
+                } else {
+                    -self.tables.probe_wdl_after_zeroing(chess).ok()?
+                };
+                let rank = wdl_rank(wdl);
+                Some(RootTbMove {
+                    mv: *mv,
+                    rank,
+                    score: rank_score(rank, config.rule50),
+                })
+            })
+            .collect()
+    }
 }
 
-/// Probe tablebases for all legal root moves and pick the best one.
-///
-/// Returns `None` when tablebases are not configured, the position is not
-/// tablebase-eligible (>7 pieces), or probing is incomplete for any legal move.
-pub fn probe_root_best_move(pos: &Position) -> Option<ChessMove> {
-    if !has_tablebases() || pos.all_pieces().count() > 7 {
-        return None;
+/// The tables plus the options under which the search probes them.
+#[derive(Clone, Copy)]
+pub struct TbProbe<'a> {
+    pub tables: &'a SyzygyTablebase,
+    pub config: TbConfig,
+}
+
+impl<'a> TbProbe<'a> {
+    /// Probing setup of a search; `None` without tables or configuration.
+    pub fn new(tables: Option<&'a SyzygyTablebase>, config: Option<TbConfig>) -> Option<Self> {
+        Some(Self {
+            tables: tables?,
+            config: config?,
+        })
     }
 
-    let moves = generate_legal_moves_fast(pos);
-    if moves.is_empty() {
-        return None;
+    /// Score of a node `remaining` plies above the horizon, from the side to
+    /// move's view. Only positions right after a capture or pawn move are
+    /// probed: the WDL tables assume a fresh 50-move clock.
+    pub fn probe(&self, pos: &Position, remaining: usize) -> Option<i32> {
+        if remaining < self.config.probe_depth
+            || pos.halfmove_clock != 0
+            || !self.tables.covers(pos, self.config.probe_limit)
+        {
+            return None;
+        }
+        let wdl = self.tables.probe_wdl(pos)?;
+        Some(wdl_score(wdl, self.config.rule50))
     }
+}
 
-    let mut best_move = ChessMove::null();
-    let mut best_score = i32::MIN;
+fn to_chess(pos: &Position) -> Option<Chess> {
+    let fen: Fen = pos.to_fen().parse().ok()?;
+    fen.into_position(CastlingMode::Standard).ok()
+}
 
-    for i in 0..moves.len() {
-        let mv = moves[i];
-        let mut child = Position::default();
-        pos.apply_move_into(&mv, &mut child);
+/// Search score of a WDL. With the 50-move rule, cursed wins and blessed
+/// losses are draws, nudged by the smallest margin towards their side.
+const fn wdl_score(wdl: Wdl, rule50: bool) -> i32 {
+    match wdl {
+        Wdl::Win => TB_WIN_SCORE,
+        Wdl::CursedWin if rule50 => 2,
+        Wdl::CursedWin => TB_WIN_SCORE,
+        Wdl::Draw => 0,
+        Wdl::BlessedLoss if rule50 => -2,
+        Wdl::BlessedLoss | Wdl::Loss => -TB_WIN_SCORE,
+    }
+}
 
-        // `probe_wdl_cp` is from side-to-move perspective in child, so negate.
-        let root_score = -probe_wdl_cp(&child)?;
-        if best_move.is_null() || root_score > best_score {
-            best_score = root_score;
-            best_move = mv;
+/// Root rank of a move by WDL alone. Cursed wins and blessed losses rank as
+/// if just past the 50-move budget, as `dtz_rank` would place them, so a
+/// real win always beats a cursed one; `rank_score` decides whether the
+/// rule makes them draws.
+const fn wdl_rank(wdl: Wdl) -> i32 {
+    match wdl {
+        Wdl::Loss => -TB_RANK_WIN,
+        Wdl::BlessedLoss => -TB_RANK_WIN + 101,
+        Wdl::Draw => 0,
+        Wdl::CursedWin => TB_RANK_WIN - 101,
+        Wdl::Win => TB_RANK_WIN,
+    }
+}
+
+/// Root rank of a move with the given DTZ (counted from the root) when
+/// the root's halfmove clock is `halfmoves`.
+fn dtz_rank(dtz: i32, halfmoves: u8) -> i32 {
+    let clock = i32::from(halfmoves);
+    if dtz > 0 {
+        if dtz + clock <= 99 {
+            TB_RANK_WIN
+        } else {
+            TB_RANK_WIN - (dtz + clock)
         }
+    } else if dtz < 0 {
+        if -dtz * 2 + clock < 100 {
+            -TB_RANK_WIN
+        } else {
+            -TB_RANK_WIN + (-dtz + clock)
+        }
+    } else {
+        0
+    }
+}
+
+/// Score reported for a root rank. Wins the 50-move rule spoils get a few
+/// centipawns, growing as the win gets closer.
+fn rank_score(rank: i32, rule50: bool) -> i32 {
+    let bound = if rule50 { TB_RANK_RULE50_BOUND } else { 1 };
+    if rank >= bound {
+        TB_WIN_SCORE
+    } else if rank > 0 {
+        (rank - 800).max(3) / 2
+    } else if rank == 0 {
+        0
+    } else if rank > -bound {
+        (rank + 800).min(-3) / 2
+    } else {
+        -TB_WIN_SCORE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dtz_rank_respects_fifty_move_rule() {
+        // Zeroing within the clock's budget: every such win ranks the same.
+        assert_eq!(dtz_rank(5, 0), dtz_rank(61, 30));
+        // Past the budget, the faster conversion ranks higher.
+        assert!(dtz_rank(21, 90) > dtz_rank(31, 90));
+        assert!(dtz_rank(21, 90) < dtz_rank(5, 0));
+        // A loss the 50-move rule may save ranks above a certain loss.
+        assert!(dtz_rank(-40, 30) > dtz_rank(-2, 0));
+        assert_eq!(dtz_rank(0, 50), 0);
     }
 
-    (!best_move.is_null()).then_some(best_move)
+    #[test]
+    fn test_wdl_rank_puts_real_win_above_cursed_win() {
+        assert!(wdl_rank(Wdl::Win) > wdl_rank(Wdl::CursedWin));
+        assert!(wdl_rank(Wdl::BlessedLoss) > wdl_rank(Wdl::Loss));
+        // The 50-move rule only changes what the ranks are worth.
+        assert_eq!(rank_score(wdl_rank(Wdl::CursedWin), false), TB_WIN_SCORE);
+        assert_eq!(rank_score(wdl_rank(Wdl::BlessedLoss), false), -TB_WIN_SCORE);
+        assert!((1..50).contains(&rank_score(wdl_rank(Wdl::CursedWin), true)));
+        assert!((-50..0).contains(&rank_score(wdl_rank(Wdl::BlessedLoss), true)));
+    }
+
+    #[test]
+    fn test_scores_stay_below_mates_and_follow_rule50() {
+        use crate::search::core::MATE_SCORE;
+        use crate::search::core::MAX_SEARCH_PLY;
+
+        let win = rank_score(dtz_rank(1, 0), true);
+        assert_eq!(win, TB_WIN_SCORE);
+        assert!(win < MATE_SCORE - MAX_SEARCH_PLY as i32);
+
+        // A win the clock spoils is a small edge with the rule, a win without.
+        let cursed = dtz_rank(60, 60);
+        assert!((1..50).contains(&rank_score(cursed, true)));
+        assert_eq!(rank_score(cursed, false), TB_WIN_SCORE);
+        assert_eq!(wdl_score(Wdl::CursedWin, true), 2);
+        assert_eq!(wdl_score(Wdl::BlessedLoss, false), -TB_WIN_SCORE);
+    }
 }
@@ -0,0 +1,314 @@
+// src/search/timeman.rs
+//
+// Clock-based time allocation. A search gets a soft deadline, checked between
+// iterations and scaled by how settled the root is, and a hard deadline that
+// aborts the tree. Both are measured from the start of the search and keep
+// `Move Overhead` in reserve for GUI/network latency.
+
+use crate::search::listener::RootMoveScore;
+use bitboard::mov::ChessMove;
+
+/// Default `Move Overhead` in milliseconds.
+pub const DEFAULT_MOVE_OVERHEAD_MS: u64 = 10;
+/// Upper bound for the `Move Overhead` option.
+pub const MAX_MOVE_OVERHEAD_MS: u64 = 5_000;
+
+/// Moves assumed to remain in sudden-death games.
+const DEFAULT_MOVES_TO_GO: u64 = 30;
+/// `movestogo` values above this are clamped to it.
+const MAX_MOVES_TO_GO: u64 = 50;
+/// Share of the increment spent on the current move, in percent.
+const INCREMENT_SHARE_PCT: u64 = 75;
+/// The hard deadline may exceed the soft one by this factor.
+const HARD_TO_SOFT_RATIO: u64 = 4;
+/// Never let a single move use more than this share of the clock, in percent.
+const MAX_CLOCK_SHARE_PCT: u64 = 75;
+
+/// Soft-limit scaling applied per recent best-move change, in percent.
+const INSTABILITY_PER_CHANGE_PCT: u64 = 30;
+/// Score drop (cp) between iterations that counts as a small/large drop.
+const SCORE_DROP_SMALL_CP: i32 = 30;
+const SCORE_DROP_LARGE_CP: i32 = 80;
+const SCORE_DROP_SMALL_PCT: u64 = 25;
+const SCORE_DROP_LARGE_PCT: u64 = 50;
+/// Iterations with an unchanged best move before the soft limit shrinks.
+const STABLE_ITERATIONS: u32 = 4;
+const STABLE_DISCOUNT_PCT: u64 = 35;
+/// Lead (cp) over every other root move that makes a stable best move
+/// clearly best.
+const CLEAR_BEST_MARGIN_CP: i32 = 200;
+/// A clearly best move ends the search once this share of the soft limit
+/// is used, in percent.
+const CLEAR_BEST_SOFT_PCT: u64 = 30;
+const MIN_SOFT_SCALE_PCT: u64 = 50;
+const MAX_SOFT_SCALE_PCT: u64 = 250;
+
+/// The side to move's clock as sent with `go`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TimeControl {
+    pub remaining_ms: u64,
+    pub increment_ms: u64,
+    pub moves_to_go: Option<u32>,
+}
+
+/// Soft/hard deadlines for one search plus the root stability bookkeeping
+/// that stretches or shrinks the soft one.
+#[derive(Clone, Debug)]
+pub struct TimeManager {
+    soft_ms: Option<u64>,
+    hard_ms: Option<u64>,
+    /// Whether the deadlines come from a clock and may be adjusted.
+    managed: bool,
+    last_best: ChessMove,
+    last_score: Option<i32>,
+    /// Decaying count of best-move changes, scaled by 2 per change.
+    best_move_changes: u64,
+    stable_iterations: u32,
+    score_drop_pct: u64,
+}
+
+impl TimeManager {
+    fn with_limits(soft_ms: Option<u64>, hard_ms: Option<u64>, managed: bool) -> Self {
+        Self {
+            soft_ms,
+            hard_ms,
+            managed,
+            last_best: ChessMove::null(),
+            last_score: None,
+            best_move_changes: 0,
+            stable_iterations: 0,
+            score_drop_pct: 0,
+        }
+    }
+
+    /// No deadlines: depth, nodes or an external stop end the search.
+    pub fn unlimited() -> Self {
+        Self::with_limits(None, None, false)
+    }
+
+    /// `go movetime`: both deadlines are the given budget.
+    pub fn fixed(movetime_ms: u64) -> Self {
+        Self::with_limits(Some(movetime_ms), Some(movetime_ms), false)
+    }
+
+    /// Allocate time for one move from the side to move's clock.
+    pub fn from_clock(clock: &TimeControl, move_overhead_ms: u64) -> Self {
+        let available = clock.remaining_ms.saturating_sub(move_overhead_ms).max(1);
+        let moves_to_go = clock.moves_to_go.map_or(DEFAULT_MOVES_TO_GO, |n| {
+            u64::from(n).clamp(1, MAX_MOVES_TO_GO)
+        });
+
+        let max_use = (available * MAX_CLOCK_SHARE_PCT / 100).max(1);
+        let base = available / moves_to_go + clock.increment_ms * INCREMENT_SHARE_PCT / 100;
+        let hard = (base * HARD_TO_SOFT_RATIO).clamp(1, max_use);
+        let soft = base.clamp(1, hard);
+
+        Self::with_limits(Some(soft), Some(hard), true)
+    }
+
+    /// Soft deadline before any stability scaling.
+    pub const fn soft_limit_ms(&self) -> Option<u64> {
+        self.soft_ms
+    }
+
+    /// Deadline after which the running iteration is abandoned.
+    pub const fn hard_limit_ms(&self) -> Option<u64> {
+        self.hard_ms
+    }
+
+    /// Record the result of a completed iteration.
+    pub fn on_iteration(&mut self, best_move: ChessMove, score: i32) {
+        self.best_move_changes /= 2;
+        if self.last_best.is_null() || best_move == self.last_best {
+            self.stable_iterations += 1;
+        } else {
+            self.best_move_changes += 2;
+            self.stable_iterations = 0;
+        }
+
+        self.score_drop_pct = match self.last_score {
+            Some(prev) if prev - score >= SCORE_DROP_LARGE_CP => SCORE_DROP_LARGE_PCT,
+            Some(prev) if prev - score >= SCORE_DROP_SMALL_CP => SCORE_DROP_SMALL_PCT,
+            _ => 0,
+        };
+
+        self.last_best = best_move;
+        self.last_score = Some(score);
+    }
+
+    /// Soft deadline scaled by root instability (best-move changes, falling
+    /// score) or shrunk when the best move has been stable for a while.
+    pub fn adjusted_soft_limit_ms(&self) -> Option<u64> {
+        let soft = self.soft_ms?;
+        if !self.managed {
+            return Some(soft);
+        }
+
+        let mut scale = 100 + self.best_move_changes * INSTABILITY_PER_CHANGE_PCT / 2;
+        scale += self.score_drop_pct;
+        if self.stable_iterations >= STABLE_ITERATIONS && self.score_drop_pct == 0 {
+            scale = scale.saturating_sub(STABLE_DISCOUNT_PCT);
+        }
+        let scale = scale.clamp(MIN_SOFT_SCALE_PCT, MAX_SOFT_SCALE_PCT);
+
+        let adjusted = soft * scale / 100;
+        Some(self.hard_ms.map_or(adjusted, |hard| adjusted.min(hard)))
+    }
+
+    /// Whether the best move has been stable for a while and leads every
+    /// other root move by a wide margin. `root_scores` come best first; the
+    /// others may be upper bounds, which only understates the lead.
+    fn clearly_best(&self, root_scores: &[RootMoveScore]) -> bool {
+        match root_scores {
+            [best, second, ..] => {
+                self.stable_iterations >= STABLE_ITERATIONS
+                    && best.score - second.score >= CLEAR_BEST_MARGIN_CP
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether to stop after an iteration that finished at `elapsed_ms`
+    /// with the given root move scores, best first. On the clock, a forced
+    /// reply needs no more than one iteration and a clearly best move only
+    /// a fraction of the soft limit.
+    pub fn should_stop_after_iteration(
+        &self,
+        elapsed_ms: u64,
+        root_scores: &[RootMoveScore],
+    ) -> bool {
+        if self.managed {
+            if root_scores.len() == 1 {
+                return true;
+            }
+            if self.clearly_best(root_scores)
+                && self
+                    .soft_ms
+                    .is_some_and(|soft| elapsed_ms >= soft * CLEAR_BEST_SOFT_PCT / 100)
+            {
+                return true;
+            }
+        }
+        self.adjusted_soft_limit_ms()
+            .is_some_and(|soft| elapsed_ms >= soft)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::ScoreBound;
+    use bitboard::Square;
+    use bitboard::mov::MoveType;
+
+    fn root_scores(scores: &[i32]) -> Vec<RootMoveScore> {
+        scores
+            .iter()
+            .zip(Square::all_array())
+            .map(|(&score, to)| RootMoveScore {
+                mv: ChessMove::new(Square::A1, to, MoveType::Quiet),
+                score,
+                bound: ScoreBound::Exact,
+            })
+            .collect()
+    }
+
+    fn clock(remaining_ms: u64, increment_ms: u64, moves_to_go: Option<u32>) -> TimeControl {
+        TimeControl {
+            remaining_ms,
+            increment_ms,
+            moves_to_go,
+        }
+    }
+
+    #[test]
+    fn test_sudden_death_keeps_overhead_and_orders_deadlines() {
+        let tm = TimeManager::from_clock(&clock(60_000, 1_000, None), 10);
+        let soft = tm.soft_limit_ms().unwrap();
+        let hard = tm.hard_limit_ms().unwrap();
+
+        assert_eq!(soft, (60_000 - 10) / 30 + 750);
+        assert_eq!(hard, soft * HARD_TO_SOFT_RATIO);
+    }
+
+    #[test]
+    fn test_low_clock_never_spends_whole_clock() {
+        let tm = TimeManager::from_clock(&clock(100, 0, Some(1)), 50);
+        let hard = tm.hard_limit_ms().unwrap();
+        assert!(hard < 50, "hard {hard}");
+        assert!(tm.soft_limit_ms().unwrap() <= hard);
+
+        // Overhead larger than the clock still yields a usable budget.
+        let tm = TimeManager::from_clock(&clock(5, 0, None), 10);
+        assert!(tm.hard_limit_ms().unwrap() >= 1);
+    }
+
+    #[test]
+    fn test_movestogo_divides_available_time() {
+        let tm = TimeManager::from_clock(&clock(60_010, 0, Some(10)), 10);
+        assert_eq!(tm.soft_limit_ms(), Some(6_000));
+    }
+
+    #[test]
+    fn test_instability_extends_and_stability_shrinks_soft_limit() {
+        let e2e4 = ChessMove::new(Square::E2, Square::E4, MoveType::Quiet);
+        let d2d4 = ChessMove::new(Square::D2, Square::D4, MoveType::Quiet);
+        let base = TimeManager::from_clock(&clock(60_000, 0, None), 0);
+        let soft = base.soft_limit_ms().unwrap();
+
+        let mut unstable = base.clone();
+        unstable.on_iteration(e2e4, 20);
+        unstable.on_iteration(d2d4, 20);
+        unstable.on_iteration(e2e4, -60);
+        assert!(unstable.adjusted_soft_limit_ms().unwrap() > soft);
+
+        let mut stable = base;
+        for _ in 0..6 {
+            stable.on_iteration(e2e4, 20);
+        }
+        assert!(stable.adjusted_soft_limit_ms().unwrap() < soft);
+        assert!(stable.should_stop_after_iteration(soft, &root_scores(&[20, 10])));
+    }
+
+    #[test]
+    fn test_clearly_best_move_stops_early() {
+        let e2e4 = ChessMove::new(Square::E2, Square::E4, MoveType::Quiet);
+        let mut tm = TimeManager::from_clock(&clock(60_000, 0, None), 0);
+        let soft = tm.soft_limit_ms().unwrap();
+        let early = soft * CLEAR_BEST_SOFT_PCT / 100;
+        let clear = root_scores(&[300, 50, 20]);
+        let close = root_scores(&[300, 250, 20]);
+
+        // A big lead alone is not enough before the best move has settled.
+        tm.on_iteration(e2e4, 300);
+        assert!(!tm.should_stop_after_iteration(early, &clear));
+
+        for _ in 0..STABLE_ITERATIONS {
+            tm.on_iteration(e2e4, 300);
+        }
+        assert!(tm.should_stop_after_iteration(early, &clear));
+        assert!(!tm.should_stop_after_iteration(early - 1, &clear));
+        assert!(!tm.should_stop_after_iteration(early, &close));
+
+        // Fixed budgets are never cut short.
+        let mut fixed = TimeManager::fixed(500);
+        for _ in 0..=STABLE_ITERATIONS {
+            fixed.on_iteration(e2e4, 300);
+        }
+        assert!(!fixed.should_stop_after_iteration(150, &clear));
+    }
+
+    #[test]
+    fn test_forced_move_and_fixed_budget() {
+        let tm = TimeManager::from_clock(&clock(60_000, 0, None), 10);
+        assert!(tm.should_stop_after_iteration(0, &root_scores(&[0])));
+        assert!(!tm.should_stop_after_iteration(0, &root_scores(&[0, 0])));
+
+        let fixed = TimeManager::fixed(500);
+        assert!(!fixed.should_stop_after_iteration(0, &root_scores(&[0])));
+        assert!(fixed.should_stop_after_iteration(500, &root_scores(&[0, 0])));
+        assert!(
+            !TimeManager::unlimited().should_stop_after_iteration(u64::MAX, &root_scores(&[0, 0]))
+        );
+    }
+}
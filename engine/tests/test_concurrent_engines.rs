@@ -0,0 +1,45 @@
+use bitboard::mov::ChessMove;
+use bitboard::movegen::SimpleMoveGen;
+use bitboard::position::Position;
+use engine::Engine;
+use engine::MaterialEvaluator;
+use engine::search::limits::SearchLimits;
+use std::thread;
+
+const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+const ENDGAME: &str = "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1";
+
+/// Node-limited single-threaded search on a fresh engine: move, score,
+/// counted nodes and the nodes in the engine's own statistics.
+fn run(fen: &str, nodes: u64) -> (ChessMove, i32, u64, u64) {
+    let pos = Position::from_fen(fen);
+    let limits = SearchLimits {
+        nodes: Some(nodes),
+        ..SearchLimits::default()
+    };
+    let mut engine = Engine::new(65_536, SimpleMoveGen, MaterialEvaluator);
+    let (best_move, score) = engine.search_with_limits(&pos, &limits, None);
+    (
+        best_move,
+        score,
+        engine.node_count(),
+        engine.search_stats().main_nodes,
+    )
+}
+
+#[test]
+fn test_engines_in_one_process_do_not_share_search_state() {
+    let alone = run(KIWIPETE, 30_000);
+
+    // The same search next to a busy engine must neither count the other
+    // engine's nodes nor stop early on its node limit.
+    let busy = thread::spawn(|| run(ENDGAME, 200_000));
+    let beside = thread::spawn(|| run(KIWIPETE, 30_000));
+    let beside = beside.join().unwrap();
+    let busy = busy.join().unwrap();
+
+    assert_eq!(alone, beside);
+    for (_, _, counted, stats_nodes) in [alone, busy] {
+        assert_eq!(counted, stats_nodes);
+    }
+}
@@ -5,12 +5,15 @@ use engine::MaterialEvaluator;
 use engine::core::arena::Arena;
 use engine::core::tt::TranspositionTable;
 use engine::search::INF;
-use engine::search::MAX_REPETITION_HISTORY;
 use engine::search::RepetitionState;
 use engine::search::SearchContext;
-use engine::search::SearchHeuristics;
+use engine::search::SearchCounters;
 use engine::search::SearchWindow;
+use engine::search::limits::SearchLimits;
+use engine::search::ordering::SearchHeuristics;
+use engine::search::pv::PvTable;
 use engine::search::search_node_with_arena;
+use engine::search::stats::SearchStats;
 
 #[test]
 fn test_fifty_move_rule_scored_as_draw() {
@@ -22,41 +25,149 @@ fn test_fifty_move_rule_scored_as_draw() {
     assert_eq!(score, 0, "Expected draw score for 50-move position");
 }
 
-#[test]
-fn test_threefold_repetition_scored_as_draw_in_search_node() {
-    let mut arena = Arena::new(256);
-    arena.get_mut(0).position = Position::default();
+/// Black is a queen up; white's knight and black's king shuffle.
+const SHUFFLE_FEN: &str = "4k3/8/8/8/8/8/q7/4K1N1 w - - 0 1";
 
-    let key = arena.get(0).position.zobrist_hash();
-    let mut repetition_history = [0u64; MAX_REPETITION_HISTORY];
-    repetition_history[0] = key;
-    repetition_history[1] = key;
-    repetition_history[2] = key;
+/// Play `moves` from `fen`, returning every position's key (start included)
+/// and the final position.
+fn play(fen: &str, moves: &[&str]) -> (Vec<u64>, Position) {
+    let mut pos = Position::from_fen(fen);
+    let mut keys = vec![pos.zobrist_hash()];
+    for mv in moves {
+        let m = pos.parse_uci_move(mv).expect("legal move");
+        pos.make_move(&m);
+        keys.push(pos.zobrist_hash());
+    }
+    (keys, pos)
+}
 
-    let mut tt = TranspositionTable::new(1);
-    let mut heuristics = SearchHeuristics::new();
+fn search_at_ply0(pos: Position, rep_state: &mut RepetitionState, depth: usize) -> i32 {
+    let mut arena = Arena::new(256);
+    arena.get_mut(0).position = pos;
 
+    let tt = TranspositionTable::new(10);
+    let mut heuristics = SearchHeuristics::new();
+    let mut pv = PvTable::new();
+    let mut stats = SearchStats::default();
+    let counters = SearchCounters::default();
     let mut ctx = SearchContext {
         movegen: &SimpleMoveGen,
         evaluator: &MaterialEvaluator,
-        tt: &mut tt,
+        tt: &tt,
         heuristics: &mut heuristics,
+        pv: &mut pv,
+        stats: &mut stats,
         stop: None,
+        pondering: None,
         time_budget_ms: None,
         start_time: None,
+        node_limit: None,
+        counters: &counters,
+        tablebase: None,
+        root_depth: depth,
     };
-
     let mut window = SearchWindow {
         alpha: -INF,
         beta: INF,
     };
+    search_node_with_arena(&mut ctx, &mut arena, 0, depth, &mut window, rep_state)
+}
 
-    let mut rep_state = RepetitionState {
-        history: repetition_history,
-        len: 3,
+#[test]
+fn test_twofold_repetition_in_tree_scored_as_draw() {
+    // Root is the position before Ke8; the shuffle then returns to a position
+    // first seen after the root.
+    let (keys, pos) = play(
+        "3k4/8/8/8/8/8/q7/4K1N1 b - - 0 1",
+        &["d8e8", "g1f3", "e8d8", "f3g1", "d8e8"],
+    );
+    let mut rep_state = RepetitionState::new(&[], keys[0]);
+    rep_state.history.extend_from_slice(&keys[1..]);
+
+    assert!(rep_state.is_repetition(pos.halfmove_clock));
+    assert_eq!(search_at_ply0(pos, &mut rep_state, 3), 0);
+}
+
+#[test]
+fn test_game_history_repetition_needs_three_occurrences() {
+    let (keys, pos) = play(SHUFFLE_FEN, &["g1f3", "e8d8", "f3g1", "d8e8"]);
+    // Second occurrence of the start position, both before the root.
+    let mut twice = RepetitionState::new(&keys[..3], keys[3]);
+    twice.history.push(keys[4]);
+    assert!(!twice.is_repetition(pos.halfmove_clock));
+
+    let (keys, pos) = play(
+        SHUFFLE_FEN,
+        &[
+            "g1f3", "e8d8", "f3g1", "d8e8", "g1f3", "e8d8", "f3g1", "d8e8",
+        ],
+    );
+    let mut thrice = RepetitionState::new(&keys[..7], keys[7]);
+    thrice.history.push(keys[8]);
+    assert!(thrice.is_repetition(pos.halfmove_clock));
+}
+
+#[test]
+fn test_engine_claims_threefold_from_game_history() {
+    // After two full shuffles and Nf3 Kd8, the position after Nf3-g1 has
+    // occurred twice; white can repeat it a third time.
+    let moves = [
+        "g1f3", "e8d8", "f3g1", "d8e8", "g1f3", "e8d8", "f3g1", "d8e8", "g1f3", "e8d8",
+    ];
+    let (keys, root) = play(SHUFFLE_FEN, &moves);
+    let mut engine = Engine::new(65_536, SimpleMoveGen, MaterialEvaluator);
+
+    let (_, losing) = engine.search(&root, 4, None, None);
+    assert!(losing < -500, "without history white is lost: {losing}");
+
+    let limits = SearchLimits {
+        game_history: keys[..keys.len() - 1].to_vec(),
+        ..SearchLimits::depth(4)
     };
+    engine.clear_state();
+    let (best_move, score) = engine.search_with_limits(&root, &limits, None);
+    assert_eq!(score, 0);
+    assert_eq!(best_move.to_string(), "f3g1");
+}
+
+#[test]
+fn test_upcoming_repetition_detected_with_cuckoo() {
+    // After Ke8 Nf3 Kd8 Ng1, black can return to the position after Ke8.
+    let (keys, pos) = play(
+        "3k4/8/8/8/8/8/q7/4K1N1 b - - 0 1",
+        &["d8e8", "g1f3", "e8d8", "f3g1"],
+    );
+
+    // That position is inside the tree: at least a draw.
+    let mut in_tree = RepetitionState::new(&[], keys[0]);
+    in_tree.history.extend_from_slice(&keys[1..]);
+    assert!(in_tree.has_upcoming_repetition(&pos, 4));
+
+    // If it is the root, repeating it once more is not yet a draw.
+    let mut at_root = RepetitionState::new(&keys[..1], keys[1]);
+    at_root.history.extend_from_slice(&keys[2..]);
+    assert!(!at_root.has_upcoming_repetition(&pos, 3));
+}
+
+#[test]
+fn test_repetition_does_not_span_null_move() {
+    let (keys, pos) = play(
+        "3k4/8/8/8/8/8/q7/4K1N1 b - - 0 1",
+        &["d8e8", "g1f3", "e8d8", "f3g1", "d8e8"],
+    );
+
+    // Same path, but a null move was made after the first occurrence.
+    let mut rep_state = RepetitionState::new(&[], keys[0]);
+    rep_state.history.extend_from_slice(&keys[1..]);
+    rep_state.null_barrier = 2;
+    let score = search_at_ply0(pos, &mut rep_state, 1);
 
-    let score = search_node_with_arena(&mut ctx, &mut arena, 0, 3, &mut window, &mut rep_state);
+    let mut fresh_rep = RepetitionState::new(&[], pos.zobrist_hash());
+    let expected = search_at_ply0(pos, &mut fresh_rep, 1);
 
-    assert_eq!(score, 0, "Expected draw score for threefold repetition");
+    assert_ne!(score, 0, "Position must not be adjudicated as a repetition");
+    assert_eq!(
+        score, expected,
+        "Repetition must not be counted across a null move"
+    );
 }
@@ -0,0 +1,76 @@
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+
+use bitboard::movegen::SimpleMoveGen;
+use bitboard::movegen::generate_legal_moves;
+use bitboard::position::Position;
+use engine::Engine;
+use engine::MaterialEvaluator;
+use engine::search::engine::MAX_THREADS;
+
+const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+#[test]
+fn test_lazy_smp_finds_same_tactic_as_single_thread() {
+    // Rxd5 wins the queen; every thread count must agree.
+    let pos = Position::from_fen("4k3/8/8/3q4/8/8/8/3RK3 w - - 0 1");
+    for threads in [1, 2, 4] {
+        let mut engine = Engine::new(65_536, SimpleMoveGen, MaterialEvaluator);
+        engine.set_num_threads(threads);
+        let (best_move, _) = engine.search(&pos, 6, None, None);
+        assert_eq!(best_move.to_string(), "d1d5", "threads={threads}");
+    }
+}
+
+#[test]
+fn test_lazy_smp_reuses_helpers_across_searches() {
+    let pos = Position::from_fen(KIWIPETE);
+    let mut engine = Engine::new(65_536, SimpleMoveGen, MaterialEvaluator);
+    engine.set_num_threads(3);
+
+    for depth in [4, 5, 6] {
+        let (best_move, _) = engine.search(&pos, depth, None, None);
+        assert!(generate_legal_moves(&pos).contains(&best_move));
+        assert_eq!(engine.principal_variation().first(), Some(&best_move));
+    }
+
+    // Shrinking and growing the pool keeps the engine usable.
+    engine.set_num_threads(1);
+    engine.search(&pos, 4, None, None);
+    engine.set_num_threads(4);
+    let (best_move, _) = engine.search(&pos, 5, None, None);
+    assert!(generate_legal_moves(&pos).contains(&best_move));
+}
+
+#[test]
+fn test_lazy_smp_stop_ends_all_threads() {
+    let pos = Position::from_fen(KIWIPETE);
+    let mut engine = Engine::new(65_536, SimpleMoveGen, MaterialEvaluator);
+    engine.set_num_threads(4);
+    let stop = AtomicBool::new(false);
+
+    let started = std::time::Instant::now();
+    std::thread::scope(|scope| {
+        scope.spawn(|| {
+            std::thread::sleep(std::time::Duration::from_millis(200));
+            stop.store(true, Ordering::Relaxed);
+        });
+        let (best_move, _) = engine.search(&pos, 64, None, Some(&stop));
+        assert!(generate_legal_moves(&pos).contains(&best_move));
+    });
+    assert!(
+        started.elapsed().as_secs() < 5,
+        "took {:?}",
+        started.elapsed()
+    );
+}
+
+#[test]
+fn test_thread_count_is_clamped() {
+    let pos = Position::from_fen(KIWIPETE);
+    let mut engine = Engine::new(65_536, SimpleMoveGen, MaterialEvaluator);
+    engine.set_num_threads(MAX_THREADS + 100);
+    engine.set_num_threads(0);
+    let (best_move, _) = engine.search(&pos, 3, None, None);
+    assert!(generate_legal_moves(&pos).contains(&best_move));
+}
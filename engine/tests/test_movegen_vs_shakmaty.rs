@@ -0,0 +1,201 @@
+// Differential move-generation test: Cody's move generator and board state
+// are checked against shakmaty ply by ply, over seeded random games and
+// random legal positions. Any disagreement fails immediately with the FEN and
+// the offending move.
+
+use std::collections::BTreeSet;
+
+use bitboard::movegen::generate_legal_moves;
+use bitboard::movegen::is_in_check;
+use bitboard::position::Position;
+use rand::RngExt;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use shakmaty::CastlingMode;
+use shakmaty::Chess;
+use shakmaty::EnPassantMode;
+use shakmaty::Position as _;
+use shakmaty::fen::Fen;
+
+const SEED: u64 = 0xC0D1_5EED;
+const RANDOM_GAMES: usize = 120;
+const MAX_GAME_PLIES: usize = 160;
+const RANDOM_POSITIONS: usize = 3_000;
+
+const START_FENS: [&str; 5] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+];
+
+fn shakmaty_fen(pos: &Chess) -> String {
+    // Cody records the en passant square after every double push.
+    Fen::from_position(pos, EnPassantMode::Always).to_string()
+}
+
+fn parse_shakmaty(fen: &str) -> Option<Chess> {
+    let fen: Fen = fen.parse().ok()?;
+    fen.into_position(CastlingMode::Standard).ok()
+}
+
+fn shakmaty_moves(pos: &Chess) -> BTreeSet<String> {
+    pos.legal_moves()
+        .iter()
+        .map(|m| m.to_uci(CastlingMode::Standard).to_string())
+        .collect()
+}
+
+/// Compare every observable piece of state for one position, then play each
+/// legal move on both sides and check the result and the unmake round-trip.
+fn assert_same_position(cody: &Position, reference: &Chess) {
+    let fen = shakmaty_fen(reference);
+    assert_eq!(cody.to_fen(), fen, "FEN mismatch");
+
+    assert_eq!(
+        is_in_check(cody, cody.side_to_move),
+        reference.is_check(),
+        "check status mismatch in {fen}"
+    );
+
+    let cody_moves: BTreeSet<String> = generate_legal_moves(cody)
+        .iter()
+        .map(|m| m.to_string())
+        .collect();
+    let expected = shakmaty_moves(reference);
+    if let Some(extra) = cody_moves.difference(&expected).next() {
+        panic!("illegal move {extra} generated in {fen}");
+    }
+    if let Some(missing) = expected.difference(&cody_moves).next() {
+        panic!("legal move {missing} not generated in {fen}");
+    }
+
+    for m in reference.legal_moves() {
+        let uci = m.to_uci(CastlingMode::Standard).to_string();
+        let cody_move = generate_legal_moves(cody)
+            .iter()
+            .copied()
+            .find(|cm| cm.to_string() == uci)
+            .unwrap_or_else(|| panic!("legal move {uci} not generated in {fen}"));
+
+        let mut after = *cody;
+        let undo = after.make_move(&cody_move);
+        let reference_after = reference.clone().play(m).expect("legal move");
+        assert_eq!(
+            after.to_fen(),
+            shakmaty_fen(&reference_after),
+            "make_move {uci} diverged in {fen}"
+        );
+
+        let mut into = Position::default();
+        cody.apply_move_into(&cody_move, &mut into);
+        assert_eq!(
+            into.to_fen(),
+            after.to_fen(),
+            "apply_move_into {uci} diverged in {fen}"
+        );
+
+        after.unmake_move(&cody_move, &undo);
+        assert_eq!(after.to_fen(), fen, "unmake_move {uci} diverged in {fen}");
+    }
+}
+
+#[test]
+fn test_random_games_match_shakmaty() {
+    let mut rng = StdRng::seed_from_u64(SEED);
+
+    for game in 0..RANDOM_GAMES {
+        let start = START_FENS[game % START_FENS.len()];
+        let mut cody = Position::from_fen(start);
+        let mut reference = parse_shakmaty(start).expect("valid start FEN");
+
+        for _ in 0..MAX_GAME_PLIES {
+            assert_same_position(&cody, &reference);
+
+            let legal = reference.legal_moves();
+            if legal.is_empty() {
+                break;
+            }
+            let m = legal[rng.random_range(0..legal.len())];
+            let uci = m.to_uci(CastlingMode::Standard).to_string();
+            let cody_move = generate_legal_moves(&cody)
+                .iter()
+                .copied()
+                .find(|cm| cm.to_string() == uci)
+                .expect("move set already compared");
+
+            cody.make_move(&cody_move);
+            reference.play_unchecked(m);
+        }
+    }
+}
+
+fn random_position_fen(rng: &mut StdRng) -> String {
+    const PIECES: [char; 10] = ['P', 'N', 'B', 'R', 'Q', 'p', 'n', 'b', 'r', 'q'];
+
+    let mut board = [None; 64];
+    board[rng.random_range(0..64usize)] = Some('K');
+    loop {
+        let sq = rng.random_range(0..64usize);
+        if board[sq].is_none() {
+            board[sq] = Some('k');
+            break;
+        }
+    }
+
+    let extra = rng.random_range(0..=12usize);
+    for _ in 0..extra {
+        let sq = rng.random_range(0..64usize);
+        let piece = PIECES[rng.random_range(0..PIECES.len())];
+        let rank = sq / 8;
+        let is_pawn = piece.eq_ignore_ascii_case(&'p');
+        if board[sq].is_none() && !(is_pawn && (rank == 0 || rank == 7)) {
+            board[sq] = Some(piece);
+        }
+    }
+
+    let mut placement = String::new();
+    for rank in (0..8).rev() {
+        let mut empty = 0;
+        for file in 0..8 {
+            match board[rank * 8 + file] {
+                Some(pc) => {
+                    if empty > 0 {
+                        placement.push_str(&empty.to_string());
+                        empty = 0;
+                    }
+                    placement.push(pc);
+                }
+                None => empty += 1,
+            }
+        }
+        if empty > 0 {
+            placement.push_str(&empty.to_string());
+        }
+        if rank > 0 {
+            placement.push('/');
+        }
+    }
+
+    let side = if rng.random_bool(0.5) { 'w' } else { 'b' };
+    format!("{placement} {side} - - 0 1")
+}
+
+#[test]
+fn test_random_positions_match_shakmaty() {
+    let mut rng = StdRng::seed_from_u64(SEED ^ 0xFFFF);
+    let mut checked = 0;
+
+    while checked < RANDOM_POSITIONS {
+        let fen = random_position_fen(&mut rng);
+        // shakmaty rejects illegal setups (adjacent kings, side not to move
+        // in check, impossible checks), which leaves only legal positions.
+        let Some(reference) = parse_shakmaty(&fen) else {
+            continue;
+        };
+        let cody = Position::from_fen(&fen);
+        assert_same_position(&cody, &reference);
+        checked += 1;
+    }
+}
@@ -0,0 +1,98 @@
+use std::collections::HashSet;
+
+use bitboard::mov::ChessMove;
+use bitboard::movegen::SimpleMoveGen;
+use bitboard::movegen::generate_legal_moves;
+use bitboard::position::Position;
+use engine::Engine;
+use engine::MaterialEvaluator;
+use engine::search::pv::PvLine;
+
+fn assert_legal_line(root: &Position, pv: &[ChessMove]) {
+    let mut pos = *root;
+    for mv in pv {
+        assert!(
+            generate_legal_moves(&pos).contains(mv),
+            "PV move {mv} is illegal in {}",
+            pos.to_fen()
+        );
+        pos.make_move(mv);
+    }
+}
+
+fn assert_distinct_sorted_lines(root: &Position, lines: &[PvLine], expected: usize) {
+    assert_eq!(lines.len(), expected);
+
+    let roots: HashSet<String> = lines.iter().map(|l| l.pv[0].to_string()).collect();
+    assert_eq!(roots.len(), expected, "root moves repeat: {lines:?}");
+
+    for pair in lines.windows(2) {
+        assert!(
+            pair[0].score >= pair[1].score,
+            "lines not sorted: {lines:?}"
+        );
+    }
+    for line in lines {
+        assert_legal_line(root, &line.pv);
+    }
+}
+
+#[test]
+fn test_multipv_reports_distinct_sorted_lines() {
+    let pos =
+        Position::from_fen("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3");
+    let mut engine = Engine::new(65_536, SimpleMoveGen, MaterialEvaluator);
+    engine.set_multi_pv(3);
+
+    let (best_move, score) = engine.search(&pos, 4, None, None);
+    let lines = engine.pv_lines();
+
+    assert_distinct_sorted_lines(&pos, lines, 3);
+    assert_eq!(lines[0].pv[0], best_move);
+    assert_eq!(lines[0].score, score);
+    assert_eq!(engine.principal_variation(), lines[0].pv.as_slice());
+}
+
+#[test]
+fn test_multipv_best_line_matches_single_pv() {
+    // Winning the queen is far ahead of every alternative, so the top line
+    // must not depend on the MultiPV setting.
+    let pos = Position::from_fen("4k3/8/8/3q4/8/8/8/3RK3 w - - 0 1");
+
+    let mut single = Engine::new(65_536, SimpleMoveGen, MaterialEvaluator);
+    let (single_move, _) = single.search(&pos, 4, None, None);
+
+    let mut multi = Engine::new(65_536, SimpleMoveGen, MaterialEvaluator);
+    multi.set_multi_pv(4);
+    let (multi_move, _) = multi.search(&pos, 4, None, None);
+
+    assert_eq!(single_move.to_string(), "d1d5");
+    assert_eq!(multi_move, single_move);
+    assert_distinct_sorted_lines(&pos, multi.pv_lines(), 4);
+}
+
+#[test]
+fn test_multipv_caps_at_legal_move_count() {
+    // Black king has exactly two legal moves.
+    let pos = Position::from_fen("7k/8/6K1/8/8/8/8/6R1 b - - 0 1");
+    let mut engine = Engine::new(65_536, SimpleMoveGen, MaterialEvaluator);
+    engine.set_multi_pv(5);
+
+    engine.search(&pos, 3, None, None);
+    assert_distinct_sorted_lines(&pos, engine.pv_lines(), generate_legal_moves(&pos).len());
+}
+
+#[test]
+fn test_multipv_with_threads() {
+    let pos =
+        Position::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+    let mut engine = Engine::new(65_536, SimpleMoveGen, MaterialEvaluator);
+    engine.set_num_threads(2);
+    engine.set_multi_pv(2);
+
+    let (best_move, _) = engine.search(&pos, 6, None, None);
+    let lines = engine.pv_lines();
+
+    assert_distinct_sorted_lines(&pos, lines, 2);
+    assert_eq!(lines[0].pv[0], best_move);
+}
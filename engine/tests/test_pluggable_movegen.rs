@@ -0,0 +1,83 @@
+use std::sync::Arc;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+
+use bitboard::MoveList;
+use bitboard::mov::ChessMove;
+use bitboard::movegen::MoveGenerator;
+use bitboard::movegen::SimpleMoveGen;
+use bitboard::position::Position;
+use engine::Engine;
+use engine::MaterialEvaluator;
+
+/// Delegates to `SimpleMoveGen` while counting how often the search asks for
+/// each kind of generation.
+#[derive(Clone, Default)]
+struct CountingMoveGen {
+    pseudo: Arc<AtomicU64>,
+    legal: Arc<AtomicU64>,
+    captures: Arc<AtomicU64>,
+    legality: Arc<AtomicU64>,
+}
+
+impl MoveGenerator for CountingMoveGen {
+    fn in_check(&self, pos: &Position) -> bool {
+        SimpleMoveGen.in_check(pos)
+    }
+
+    fn pseudo_moves(&self, pos: &Position) -> MoveList {
+        self.pseudo.fetch_add(1, Ordering::Relaxed);
+        SimpleMoveGen.pseudo_moves(pos)
+    }
+
+    fn legal_moves(&self, pos: &Position) -> MoveList {
+        self.legal.fetch_add(1, Ordering::Relaxed);
+        SimpleMoveGen.legal_moves(pos)
+    }
+
+    fn pseudo_captures(&self, pos: &Position) -> MoveList {
+        self.captures.fetch_add(1, Ordering::Relaxed);
+        SimpleMoveGen.pseudo_captures(pos)
+    }
+
+    fn is_pseudo_legal(&self, pos: &Position, mv: &ChessMove) -> bool {
+        SimpleMoveGen.is_pseudo_legal(pos, mv)
+    }
+
+    fn is_legal(&self, pos: &Position, mv: &ChessMove) -> bool {
+        self.legality.fetch_add(1, Ordering::Relaxed);
+        SimpleMoveGen.is_legal(pos, mv)
+    }
+}
+
+#[test]
+fn test_search_routes_through_move_generator() {
+    let movegen = CountingMoveGen::default();
+    let mut engine = Engine::new(65_536, movegen.clone(), MaterialEvaluator);
+
+    let pos =
+        Position::from_fen("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3");
+    let (best_move, _score) = engine.search(&pos, 4, None, None);
+
+    assert!(!best_move.is_null());
+    assert!(movegen.legal.load(Ordering::Relaxed) > 0, "root moves");
+    assert!(movegen.pseudo.load(Ordering::Relaxed) > 0, "interior nodes");
+    assert!(movegen.captures.load(Ordering::Relaxed) > 0, "quiescence");
+    assert!(
+        movegen.legality.load(Ordering::Relaxed) > 0,
+        "legality checks"
+    );
+}
+
+#[test]
+fn test_gives_check_default() {
+    let pos = Position::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1");
+    let checking = SimpleMoveGen
+        .legal_moves(&pos)
+        .iter()
+        .filter(|m| SimpleMoveGen.gives_check(&pos, m))
+        .map(|m| m.to_string())
+        .collect::<Vec<_>>();
+
+    assert_eq!(checking, vec!["a1a8".to_string()]);
+}
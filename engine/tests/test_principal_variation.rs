@@ -0,0 +1,62 @@
+use bitboard::movegen::SimpleMoveGen;
+use bitboard::movegen::generate_legal_moves;
+use bitboard::position::Position;
+use engine::Engine;
+use engine::MaterialEvaluator;
+
+fn assert_legal_line(root: &Position, pv: &[bitboard::mov::ChessMove]) {
+    let mut pos = *root;
+    for mv in pv {
+        assert!(
+            generate_legal_moves(&pos).contains(mv),
+            "PV move {mv} is illegal in {}",
+            pos.to_fen()
+        );
+        pos.make_move(mv);
+    }
+}
+
+#[test]
+fn test_pv_starts_with_best_move_and_is_legal() {
+    let pos =
+        Position::from_fen("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3");
+    let mut engine = Engine::new(65_536, SimpleMoveGen, MaterialEvaluator);
+
+    let (best_move, _score) = engine.search(&pos, 5, None, None);
+    let pv = engine.principal_variation();
+
+    assert_eq!(pv.first(), Some(&best_move));
+    assert!(pv.len() > 1, "expected more than the root move, got {pv:?}");
+    // Checks and singular moves extend a line, but only within twice the
+    // nominal depth.
+    assert!(pv.len() <= 2 * 5);
+    assert_legal_line(&pos, pv);
+}
+
+#[test]
+fn test_pv_for_forced_mate_reaches_mate() {
+    // Back-rank mate in one.
+    let pos = Position::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1");
+    let mut engine = Engine::new(65_536, SimpleMoveGen, MaterialEvaluator);
+
+    let (best_move, _score) = engine.search(&pos, 3, None, None);
+    let pv = engine.principal_variation();
+
+    assert_eq!(best_move.to_string(), "a1a8");
+    assert_eq!(pv.first(), Some(&best_move));
+    assert_legal_line(&pos, pv);
+}
+
+#[test]
+fn test_pv_with_threads_is_legal() {
+    let pos =
+        Position::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+    let mut engine = Engine::new(65_536, SimpleMoveGen, MaterialEvaluator);
+    engine.set_num_threads(2);
+
+    let (best_move, _score) = engine.search(&pos, 6, None, None);
+    let pv = engine.principal_variation();
+
+    assert_eq!(pv.first(), Some(&best_move));
+    assert_legal_line(&pos, pv);
+}
@@ -0,0 +1,28 @@
+use bitboard::movegen::SimpleMoveGen;
+use bitboard::position::Position;
+use engine::Engine;
+use engine::MaterialEvaluator;
+
+fn search(fen: &str, depth: usize) -> (String, i32) {
+    let pos = Position::from_fen(fen);
+    let mut engine = Engine::new(65_536, SimpleMoveGen, MaterialEvaluator);
+    engine.set_num_threads(1);
+    let (best, score) = engine.search(&pos, depth, None, None);
+    (best.to_string(), score)
+}
+
+#[test]
+fn test_knight_fork_found_at_probcut_depths() {
+    // Nc7+ forks king and rook; the win only shows after the king moves.
+    let (best, score) = search("r3k3/8/8/3N4/8/8/8/4K3 w - - 0 1", 7);
+    assert_eq!(best, "d5c7");
+    assert!(score > 0, "expected the rook to be won, got {score}");
+}
+
+#[test]
+fn test_defended_pawn_not_taken_at_probcut_depths() {
+    // The pawn on d5 is defended: Qxd5 exd5 throws the queen away.
+    let (best, score) = search("4k3/8/4p3/3p4/8/8/8/3QK3 w - - 0 1", 7);
+    assert_ne!(best, "d1d5");
+    assert!(score > 500, "white should keep the queen, got {score}");
+}
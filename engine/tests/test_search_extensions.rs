@@ -0,0 +1,42 @@
+use bitboard::movegen::SimpleMoveGen;
+use bitboard::position::Position;
+use engine::Engine;
+use engine::MaterialEvaluator;
+use engine::search::MATE_SCORE;
+
+fn search(fen: &str, depth: usize) -> (String, i32) {
+    let pos = Position::from_fen(fen);
+    let mut engine = Engine::new(65_536, SimpleMoveGen, MaterialEvaluator);
+    engine.set_num_threads(1);
+    let (best, score) = engine.search(&pos, depth, None, None);
+    (best.to_string(), score)
+}
+
+#[test]
+fn test_check_extension_finds_smothered_mate_at_shallow_depth() {
+    // Philidor's legacy: Nf7+ Kg8 Nh6++ Kh8 Qg8+ Rxg8 Nf7#. Nine plies, and
+    // only the checks being extended lets a depth-5 search see the end.
+    let (best, score) = search("5r1k/6pp/8/4N3/2Q5/8/8/6K1 w - - 0 1", 5);
+    assert_eq!(best, "e5f7");
+    assert!(
+        score > MATE_SCORE - 100,
+        "expected a mate score, got {score}"
+    );
+}
+
+#[test]
+fn test_extensions_keep_search_sound_in_busy_position() {
+    // Kiwipete at a depth where singular verification and multi-cut kick in:
+    // the search must still return a legal move with a sane score.
+    let fen = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+    let pos = Position::from_fen(fen);
+    let (best, score) = search(fen, 7);
+    assert!(
+        pos.parse_uci_move(&best).is_some(),
+        "illegal best move {best}"
+    );
+    assert!(
+        score.abs() < MATE_SCORE - 100,
+        "unexpected mate score {score}"
+    );
+}
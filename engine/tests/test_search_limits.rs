@@ -0,0 +1,178 @@
+use bitboard::mov::ChessMove;
+use bitboard::movegen::SimpleMoveGen;
+use bitboard::movegen::generate_legal_moves;
+use bitboard::position::Position;
+use engine::Engine;
+use engine::MaterialEvaluator;
+use engine::search::limits::SearchLimits;
+use engine::search::timeman::TimeControl;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+fn find_move(pos: &Position, uci: &str) -> ChessMove {
+    generate_legal_moves(pos)
+        .iter()
+        .copied()
+        .find(|m| m.to_string() == uci)
+        .unwrap_or_else(|| panic!("{uci} is not legal in {}", pos.to_fen()))
+}
+
+#[test]
+fn test_node_limit_stops_search_and_is_reproducible() {
+    let pos =
+        Position::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+    let limits = SearchLimits {
+        nodes: Some(20_000),
+        ..SearchLimits::default()
+    };
+
+    let run = || {
+        let mut engine = Engine::new(65_536, SimpleMoveGen, MaterialEvaluator);
+        let (best_move, score) = engine.search_with_limits(&pos, &limits, None);
+        (best_move, score, engine.node_count())
+    };
+
+    let (first_move, first_score, first_nodes) = run();
+    let (second_move, second_score, second_nodes) = run();
+
+    assert!(generate_legal_moves(&pos).contains(&first_move));
+    // One flush batch of slack for the thread-local node counter.
+    assert!(first_nodes < 20_000 + 2_048, "searched {first_nodes} nodes");
+    assert_eq!(
+        (first_move, first_score, first_nodes),
+        (second_move, second_score, second_nodes)
+    );
+}
+
+#[test]
+fn test_searchmoves_restricts_root() {
+    // Rxd5 wins the queen, but only the king moves are allowed.
+    let pos = Position::from_fen("4k3/8/8/3q4/8/8/8/3RK3 w - - 0 1");
+    let allowed = vec![find_move(&pos, "e1e2"), find_move(&pos, "e1f2")];
+    let limits = SearchLimits {
+        depth: 4,
+        search_moves: allowed.clone(),
+        ..SearchLimits::default()
+    };
+
+    let mut engine = Engine::new(65_536, SimpleMoveGen, MaterialEvaluator);
+    let (best_move, _) = engine.search_with_limits(&pos, &limits, None);
+    assert!(allowed.contains(&best_move), "got {best_move}");
+}
+
+#[test]
+fn test_searchmoves_without_legal_entries_searches_everything() {
+    let pos = Position::from_fen("4k3/8/8/3q4/8/8/8/3RK3 w - - 0 1");
+    let black_move = {
+        let black = Position::from_fen("4k3/8/8/3q4/8/8/8/3RK3 b - - 0 1");
+        find_move(&black, "d5a2")
+    };
+    let limits = SearchLimits {
+        depth: 4,
+        search_moves: vec![black_move],
+        ..SearchLimits::default()
+    };
+
+    let mut engine = Engine::new(65_536, SimpleMoveGen, MaterialEvaluator);
+    let (best_move, _) = engine.search_with_limits(&pos, &limits, None);
+    assert_eq!(best_move.to_string(), "d1d5");
+}
+
+#[test]
+fn test_mate_limit_stops_once_mate_is_proven() {
+    // Back-rank mate in one; without a depth limit the search would run to
+    // the default ceiling.
+    let pos = Position::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1");
+    let limits = SearchLimits {
+        mate: Some(1),
+        ..SearchLimits::default()
+    };
+
+    let mut engine = Engine::new(65_536, SimpleMoveGen, MaterialEvaluator);
+    let started = std::time::Instant::now();
+    let (best_move, score) = engine.search_with_limits(&pos, &limits, None);
+
+    assert_eq!(best_move.to_string(), "a1a8");
+    assert!(score > engine::search::MATE_SCORE - 100);
+    assert!(started.elapsed().as_secs() < 10);
+}
+
+#[test]
+fn test_clock_search_respects_hard_deadline() {
+    let pos =
+        Position::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+    let limits = SearchLimits {
+        clock: Some(TimeControl {
+            remaining_ms: 2_000,
+            increment_ms: 0,
+            moves_to_go: None,
+        }),
+        ..SearchLimits::default()
+    };
+
+    let mut engine = Engine::new(65_536, SimpleMoveGen, MaterialEvaluator);
+    engine.set_move_overhead_ms(50);
+    let started = std::time::Instant::now();
+    let (best_move, _) = engine.search_with_limits(&pos, &limits, None);
+
+    assert!(generate_legal_moves(&pos).contains(&best_move));
+    // Hard limit is at most 75% of the clock; allow slack for a slow machine.
+    assert!(
+        started.elapsed().as_millis() < 1_900,
+        "took {:?}",
+        started.elapsed()
+    );
+}
+
+#[test]
+fn test_clock_search_plays_forced_move_immediately() {
+    // Black is in check and Kh7 is the only reply.
+    let pos = Position::from_fen("R6k/5K2/8/8/8/8/8/8 b - - 0 1");
+    assert_eq!(generate_legal_moves(&pos).len(), 1);
+    let limits = SearchLimits {
+        clock: Some(TimeControl {
+            remaining_ms: 600_000,
+            increment_ms: 0,
+            moves_to_go: None,
+        }),
+        ..SearchLimits::default()
+    };
+
+    let mut engine = Engine::new(65_536, SimpleMoveGen, MaterialEvaluator);
+    let started = std::time::Instant::now();
+    engine.search_with_limits(&pos, &limits, None);
+    assert!(started.elapsed().as_millis() < 1_000);
+}
+
+#[test]
+fn test_ponder_search_starts_clock_on_ponderhit() {
+    let pos =
+        Position::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+    let limits = SearchLimits {
+        clock: Some(TimeControl {
+            remaining_ms: 200,
+            increment_ms: 0,
+            moves_to_go: None,
+        }),
+        ponder: true,
+        ..SearchLimits::default()
+    };
+
+    let mut engine = Engine::new(65_536, SimpleMoveGen, MaterialEvaluator);
+    let pondering = engine.ponder_flag();
+    pondering.store(true, Ordering::Relaxed);
+    let started = std::time::Instant::now();
+    let (best_move, _) = std::thread::scope(|s| {
+        s.spawn(|| {
+            std::thread::sleep(Duration::from_millis(500));
+            pondering.store(false, Ordering::Relaxed);
+        });
+        engine.search_with_limits(&pos, &limits, None)
+    });
+
+    assert!(generate_legal_moves(&pos).contains(&best_move));
+    // The 200 ms clock is ignored while pondering and exhausted on ponderhit.
+    let elapsed = started.elapsed();
+    assert!(elapsed >= Duration::from_millis(500), "took {elapsed:?}");
+    assert!(elapsed < Duration::from_secs(3), "took {elapsed:?}");
+}
@@ -0,0 +1,102 @@
+use bitboard::movegen::SimpleMoveGen;
+use bitboard::movegen::generate_legal_moves;
+use bitboard::position::Position;
+use engine::Engine;
+use engine::MaterialEvaluator;
+use engine::search::ScoreBound;
+use engine::search::limits::SearchLimits;
+use engine::search::listener::SearchListener;
+use engine::search::listener::SearchResult;
+use engine::search::listener::SearchUpdate;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use std::time::Instant;
+
+/// Keeps what an embedding program would see.
+#[derive(Default)]
+struct Recorder {
+    depths: Vec<usize>,
+    last_pv_len: usize,
+    results: Vec<SearchResult>,
+}
+
+impl SearchListener for Recorder {
+    fn on_update(&mut self, update: &SearchUpdate<'_>) {
+        if update.bound == ScoreBound::Exact {
+            self.depths.push(update.depth);
+        }
+        self.last_pv_len = update.pv.len();
+    }
+
+    fn on_result(&mut self, result: &SearchResult) {
+        self.results.push(result.clone());
+    }
+}
+
+#[test]
+fn test_analyse_reports_every_depth_and_the_result() {
+    let pos = Position::default();
+    let mut engine = Engine::new(65_536, SimpleMoveGen, MaterialEvaluator);
+    let mut recorder = Recorder::default();
+
+    let result = engine.analyse(&pos, SearchLimits::depth(4), &mut recorder);
+
+    assert_eq!(recorder.depths, vec![1, 2, 3, 4]);
+    assert!(recorder.last_pv_len >= 1);
+    assert_eq!(recorder.results, vec![result.clone()]);
+    assert_eq!(result.depth, 4);
+    assert!(result.nodes > 0);
+    assert_eq!(result.pv.first(), Some(&result.best_move));
+
+    // Every root move was searched; the best one leads with the best score.
+    assert_eq!(result.root_moves.len(), 20);
+    assert_eq!(result.root_moves[0].mv, result.best_move);
+    assert_eq!(result.root_moves[0].score, result.score);
+
+    let ponder = result.ponder_move.expect("a ponder move after depth 4");
+    let mut child = pos;
+    child.make_move(&result.best_move);
+    assert!(generate_legal_moves(&child).contains(&ponder));
+}
+
+#[test]
+fn test_analyse_ends_on_stop_flag() {
+    let pos = Position::default();
+    let mut engine = Engine::new(65_536, SimpleMoveGen, MaterialEvaluator);
+    let stop = engine.stop_flag();
+    let started = Instant::now();
+
+    let result = std::thread::scope(|s| {
+        s.spawn(|| {
+            std::thread::sleep(Duration::from_millis(200));
+            stop.store(true, Ordering::Relaxed);
+        });
+        engine.analyse(&pos, SearchLimits::default(), &mut Recorder::default())
+    });
+
+    assert!(started.elapsed() < Duration::from_secs(10));
+    assert!(generate_legal_moves(&pos).contains(&result.best_move));
+}
+
+#[test]
+fn test_interrupted_iteration_is_not_a_completed_depth() {
+    let pos = Position::default();
+    let mut saw_partial = false;
+    for nodes in [3_000, 7_000, 15_000, 40_000] {
+        let mut engine = Engine::new(65_536, SimpleMoveGen, MaterialEvaluator);
+        let mut recorder = Recorder::default();
+        let limits = SearchLimits {
+            nodes: Some(nodes),
+            ..SearchLimits::default()
+        };
+        let result = engine.analyse(&pos, limits, &mut recorder);
+
+        // A line reported from a cut-short iteration does not make its depth
+        // completed, and the root moves it never reached are still listed.
+        let reported = *recorder.depths.last().expect("at least depth 1");
+        assert!(result.depth <= reported);
+        saw_partial |= result.depth < reported;
+        assert_eq!(result.root_moves.len(), 20, "{nodes} nodes");
+    }
+    assert!(saw_partial);
+}
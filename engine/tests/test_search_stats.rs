@@ -0,0 +1,35 @@
+use bitboard::movegen::SimpleMoveGen;
+use bitboard::position::Position;
+use engine::Engine;
+use engine::MaterialEvaluator;
+
+#[test]
+fn test_stats_are_per_search_and_merged_over_threads() {
+    let pos =
+        Position::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+    let mut engine = Engine::new(65_536, SimpleMoveGen, MaterialEvaluator);
+
+    engine.search(&pos, 6, None, None);
+    let stats = engine.search_stats();
+    assert_eq!(stats.main_nodes, engine.node_count());
+    assert!(stats.qsearch_nodes > 0);
+    assert!(stats.tt_hits <= stats.tt_probes);
+    assert!(stats.first_move_cutoffs <= stats.beta_cutoffs);
+    assert!(stats.null_move_cutoffs <= stats.null_move_tries);
+
+    // A new search starts from zero.
+    engine.search(&pos, 1, None, None);
+    assert!(engine.search_stats().main_nodes < stats.main_nodes);
+
+    // With helpers, the merged count covers every thread's nodes. Each
+    // thread may still hold up to one unflushed batch of the node counter.
+    engine.set_num_threads(3);
+    engine.search(&pos, 6, None, None);
+    let merged = engine.search_stats().main_nodes;
+    let counted = engine.node_count();
+    assert!(merged >= counted, "merged {merged} < counted {counted}");
+    assert!(
+        merged <= counted + 3 * 1024,
+        "merged {merged} counted {counted}"
+    );
+}
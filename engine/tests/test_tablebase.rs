@@ -1,38 +1,79 @@
+use bitboard::movegen::SimpleMoveGen;
 use bitboard::movegen::generate_legal_moves;
 use bitboard::position::Position;
+use engine::Engine;
+use engine::MaterialEvaluator;
+use engine::search::MATE_SCORE;
+use engine::search::limits::SearchLimits;
+use engine::search::listener::SilentListener;
+use engine::search::tablebase::SyzygyTablebase;
+use engine::search::tablebase::TB_WIN_SCORE;
+use engine::search::tablebase::TbConfig;
 
-/// Optional integration test for local Syzygy installations.
+/// Optional integration tests for local Syzygy installations.
 ///
 /// Set `CODY_SYZYGY_PATH` to a valid tablebase directory (or ';'-separated
-/// directories) to run this test.
+/// directories) to run them.
+fn syzygy_path() -> Option<String> {
+    std::env::var("CODY_SYZYGY_PATH")
+        .ok()
+        .filter(|p| !p.trim().is_empty())
+}
+
 #[test]
-fn test_probe_root_best_move_with_local_syzygy() {
-    let path = match std::env::var("CODY_SYZYGY_PATH") {
-        Ok(p) if !p.trim().is_empty() => p,
-        _ => return, // Skip when no local tablebase path is configured.
+fn test_rank_root_moves_with_local_syzygy() {
+    let Some(path) = syzygy_path() else {
+        return; // Skip when no local tablebase path is configured.
     };
 
-    engine::search::tablebase::set_syzygy_path(&path)
-        .expect("failed to load syzygy path from CODY_SYZYGY_PATH");
+    let tablebase = SyzygyTablebase::open(&path)
+        .expect("failed to load syzygy path from CODY_SYZYGY_PATH")
+        .expect("CODY_SYZYGY_PATH holds no tables");
 
     // Simple 3-man position that is tablebase-resolved and should be winning
     // for White.
     let pos = Position::from_fen("6k1/8/8/8/8/8/5K2/6Q1 w - - 0 1");
 
-    let root_score = engine::search::tablebase::probe_wdl_cp(&pos)
-        .expect("expected WDL probe result for 3-man position");
-    assert!(
-        root_score > 0,
-        "KQvK should probe as winning for side to move, got {root_score}"
-    );
+    let ranking = tablebase
+        .rank_root_moves(&pos, TbConfig::default())
+        .expect("expected a root ranking for a 3-man position");
+    assert_eq!(ranking.score(), TB_WIN_SCORE);
+    assert_eq!(ranking.moves.len(), generate_legal_moves(&pos).len());
+
+    // Hanging the queen is never among the moves that keep the win.
+    let best = ranking.best_moves();
+    assert!(!best.is_empty());
+    assert!(best.iter().all(|m| m.to_string() != "g1g7"));
+}
 
-    let best_move = engine::search::tablebase::probe_root_best_move(&pos)
-        .expect("expected tablebase best move at root");
-    let legal_moves = generate_legal_moves(&pos);
+#[test]
+fn test_search_keeps_tablebase_win_with_local_syzygy() {
+    let Some(path) = syzygy_path() else {
+        return;
+    };
+
+    let pos = Position::from_fen("6k1/8/8/8/8/8/5K2/6Q1 w - - 0 1");
+    let mut engine = Engine::new(65_536, SimpleMoveGen, MaterialEvaluator);
+    engine
+        .set_tablebase_path(&path)
+        .expect("failed to load syzygy path from CODY_SYZYGY_PATH");
+    let ranking = engine
+        .tablebase()
+        .and_then(|tb| tb.rank_root_moves(&pos, TbConfig::default()))
+        .expect("expected a root ranking for a 3-man position");
+
+    let limits = SearchLimits {
+        depth: 6,
+        ..SearchLimits::default()
+    };
+    let result = engine.analyse(&pos, limits, &mut SilentListener);
 
+    // The search picks among the winning moves and reports the tablebase
+    // win, unless it has found the mate itself.
+    assert!(ranking.best_moves().contains(&result.best_move));
     assert!(
-        legal_moves.contains(&best_move),
-        "tablebase best move must be legal, got {}",
-        best_move
+        result.score == TB_WIN_SCORE || result.score > MATE_SCORE - 128,
+        "score {}",
+        result.score
     );
 }
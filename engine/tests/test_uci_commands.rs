@@ -7,11 +7,27 @@ use bitboard::position::Position;
 /// protocol interactions.
 use engine::api::uciapi::CodyApi;
 use engine::search::evaluator::MaterialEvaluator;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+/// Output of a background search, readable while it runs.
+fn search_output() -> Arc<Mutex<Vec<u8>>> {
+    Arc::new(Mutex::new(Vec::new()))
+}
+
+fn bestmove_lines(out: &Mutex<Vec<u8>>) -> usize {
+    String::from_utf8_lossy(&out.lock().unwrap())
+        .lines()
+        .filter(|l| l.starts_with("bestmove "))
+        .count()
+}
 
 #[test]
 fn test_uci_command() {
     // ...existing code...
-    let mut api = CodyApi::new(MaterialEvaluator::default());
+    let mut api = CodyApi::new(MaterialEvaluator);
     let mut output = Vec::new();
 
     api.handle_uci(&mut output);
@@ -22,15 +38,24 @@ fn test_uci_command() {
     assert!(output_str.contains("option name Hash type spin"));
     assert!(output_str.contains("option name Clear Hash type button"));
     assert!(output_str.contains("option name Threads type spin"));
+    assert!(output_str.contains(&format!(
+        "min 1 max {}",
+        engine::search::engine::MAX_THREADS
+    )));
+    assert!(output_str.contains("option name MultiPV type spin default 1"));
+    assert!(output_str.contains("option name Move Overhead type spin"));
     assert!(output_str.contains("option name Ponder type check"));
     assert!(output_str.contains("option name Verbose type check"));
+    assert!(output_str.contains("option name SyzygyProbeDepth type spin default 1"));
+    assert!(output_str.contains("option name SyzygyProbeLimit type spin default 7 min 0 max 7"));
+    assert!(output_str.contains("option name Syzygy50MoveRule type check default true"));
     assert!(output_str.contains("uciok"));
 }
 
 #[test]
 fn test_isready_command() {
     // ...existing code...
-    let mut api = CodyApi::new(MaterialEvaluator::default());
+    let mut api = CodyApi::new(MaterialEvaluator);
     let mut output = Vec::new();
 
     api.handle_isready(&mut output);
@@ -111,6 +136,26 @@ fn test_setoption_threads() {
     // but we can verify the command doesn't panic
 }
 
+#[test]
+fn test_setoption_multipv() {
+    let mut api: CodyApi<MaterialEvaluator> = CodyApi::new(Default::default());
+    let mut output = Vec::new();
+
+    api.handle_setoption("setoption name MultiPV value 3");
+    api.handle_position("position startpos", &mut output);
+    output.clear();
+    api.handle_go("go depth 3", &mut output);
+
+    let output_str = String::from_utf8(output).unwrap();
+    let bestmove = output_str
+        .lines()
+        .find_map(|l| l.strip_prefix("bestmove "))
+        .and_then(|rest| rest.split_whitespace().next())
+        .expect("bestmove line");
+    let legal = generate_legal_moves(&Position::default());
+    assert!(legal.iter().any(|m| m.to_string() == bestmove));
+}
+
 #[test]
 fn test_setoption_verbose() {
     // ...existing code...
@@ -256,6 +301,35 @@ fn test_go_ponder() {
     assert!(output_str.contains("bestmove"));
 }
 
+#[test]
+fn test_bestmove_names_legal_ponder_move() {
+    let mut api: CodyApi<MaterialEvaluator> = CodyApi::new(Default::default());
+    let mut output = Vec::new();
+
+    api.handle_position("position startpos", &mut output);
+    output.clear();
+    api.handle_go("go depth 4", &mut output);
+
+    let output_str = String::from_utf8(output).unwrap();
+    let tokens: Vec<&str> = output_str
+        .lines()
+        .find(|l| l.starts_with("bestmove "))
+        .expect("bestmove line")
+        .split_whitespace()
+        .collect();
+    assert_eq!(tokens.len(), 4, "{tokens:?}");
+    assert_eq!(tokens[2], "ponder");
+
+    let mut pos = Position::default();
+    let best = pos.parse_uci_move(tokens[1]).expect("legal bestmove");
+    pos.make_move(&best);
+    assert!(
+        generate_legal_moves(&pos)
+            .iter()
+            .any(|m| m.to_string() == tokens[3])
+    );
+}
+
 #[test]
 fn test_newgame_command() {
     // ...existing code...
@@ -290,6 +364,65 @@ fn test_bench_command() {
     assert!(output_str.contains("Total time (ms)"));
     assert!(output_str.contains("Nodes searched"));
     assert!(output_str.contains("Nodes/second"));
+    assert!(output_str.contains("info string stats nodes"));
+}
+
+fn bench_signature(output: &str) -> u64 {
+    let line = output.lines().last().expect("bench prints a report");
+    let nodes = line
+        .strip_prefix("Nodes searched  : ")
+        .expect("bench ends with the node signature");
+    nodes.parse().unwrap()
+}
+
+#[test]
+fn test_bench_is_deterministic_and_ends_with_signature() {
+    let mut api: CodyApi<MaterialEvaluator> = CodyApi::new(Default::default());
+    let mut first = Vec::new();
+    let mut second = Vec::new();
+
+    api.handle_bench("bench 3", &mut first);
+    api.handle_bench("bench 3 1 16 default", &mut second);
+
+    let first = bench_signature(&String::from_utf8(first).unwrap());
+    assert!(first > 0);
+    assert_eq!(first, bench_signature(&String::from_utf8(second).unwrap()));
+}
+
+#[test]
+fn test_bench_reads_fen_file_and_prints_json() {
+    let path = std::env::temp_dir().join(format!("cody_bench_{}.fen", std::process::id()));
+    std::fs::write(
+        &path,
+        "# two positions\n6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1\n\n4k3/8/8/8/8/8/4P3/4K3 w - - 0 1\n",
+    )
+    .unwrap();
+    let mut api: CodyApi<MaterialEvaluator> = CodyApi::new(Default::default());
+    let mut output = Vec::new();
+
+    api.handle_bench(
+        &format!("bench 3 1 16 {} json", path.display()),
+        &mut output,
+    );
+    std::fs::remove_file(&path).ok();
+
+    let output_str = String::from_utf8(output).unwrap();
+    let json = output_str.trim();
+    assert_eq!(json.lines().count(), 1, "json mode prints one line: {json}");
+    assert!(json.starts_with("{\"depth\":3,\"threads\":1,\"hash_mb\":16,\"nodes\":"));
+    assert_eq!(json.matches("\"fen\":").count(), 2);
+    assert!(json.contains("\"bestmove\":\"a1a8\""));
+}
+
+#[test]
+fn test_bench_reports_unreadable_fen_file() {
+    let mut api: CodyApi<MaterialEvaluator> = CodyApi::new(Default::default());
+    let mut output = Vec::new();
+
+    api.handle_bench("bench 3 1 16 /nonexistent/cody.fen", &mut output);
+
+    let output_str = String::from_utf8(output).unwrap();
+    assert!(output_str.starts_with("info string bench: cannot read /nonexistent/cody.fen"));
 }
 
 #[test]
@@ -450,7 +583,7 @@ fn test_go_with_black_time_controls() {
 #[test]
 fn test_multiple_searches_same_position() {
     // ...existing code...
-    let mut api = CodyApi::new(MaterialEvaluator::default());
+    let mut api = CodyApi::new(MaterialEvaluator);
     let mut output = Vec::new();
 
     api.handle_position("position startpos", &mut output);
@@ -468,7 +601,7 @@ fn test_multiple_searches_same_position() {
 #[test]
 fn test_go_emits_legal_bestmove_in_reported_move31_position() {
     // ...existing code...
-    let mut api = CodyApi::new(MaterialEvaluator::default());
+    let mut api = CodyApi::new(MaterialEvaluator);
     let mut output = Vec::new();
 
     let fen = "1r1k4/6pp/5b2/p4p2/b2P3P/1p2NB2/3R2P1/2K4R b - - 0 31";
@@ -505,7 +638,7 @@ fn test_go_emits_legal_bestmove_in_reported_move31_position() {
 #[test]
 fn test_newgame_clears_state() {
     // ...existing code...
-    let mut api = CodyApi::new(MaterialEvaluator::default());
+    let mut api = CodyApi::new(MaterialEvaluator);
     let mut output = Vec::new();
 
     // Execute a search to populate engine state
@@ -534,7 +667,7 @@ fn test_round169_illegal_move_a5h3() {
     // Regression test for illegal move a5h3 reported in Round 169
     // Game position after 45. h4, where Black illegally played a5h3
     // ...existing code...
-    let mut api = CodyApi::new(MaterialEvaluator::default());
+    let mut api = CodyApi::new(MaterialEvaluator);
     let mut output = Vec::new();
 
     let moves = "e2e4 d7d5 e4d5 g8f6 f1b5 c8d7 b5c4 b7b5 c4d3 e7e6 d5e6 d8e7 d1f3 e7e6 e1d1 e6c6 \
@@ -583,3 +716,79 @@ fn test_round169_illegal_move_a5h3() {
         bestmove_uci
     );
 }
+
+#[test]
+fn test_go_returns_immediately_and_isready_answers_during_search() {
+    let mut api: CodyApi<MaterialEvaluator> = CodyApi::new(Default::default());
+    api.handle_setoption("setoption name Threads value 1");
+    let out = search_output();
+
+    let started = Instant::now();
+    api.start_go("go infinite", out.clone());
+    assert!(started.elapsed() < Duration::from_secs(1));
+    assert!(api.is_searching());
+
+    let mut reply = Vec::new();
+    api.handle_isready(&mut reply);
+    assert_eq!(String::from_utf8(reply).unwrap(), "readyok\n");
+    assert!(api.is_searching());
+    assert_eq!(bestmove_lines(&out), 0);
+
+    api.handle_stop();
+    assert!(!api.is_searching());
+    assert_eq!(bestmove_lines(&out), 1);
+}
+
+#[test]
+fn test_stop_after_finished_search_sends_no_second_bestmove() {
+    let mut api: CodyApi<MaterialEvaluator> = CodyApi::new(Default::default());
+    let out = search_output();
+
+    api.start_go("go depth 1", out.clone());
+    let started = Instant::now();
+    while api.is_searching() {
+        assert!(started.elapsed() < Duration::from_secs(10));
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    api.handle_stop();
+    assert_eq!(bestmove_lines(&out), 1);
+}
+
+#[test]
+fn test_ponder_search_holds_bestmove_until_ponderhit_or_stop() {
+    let mut api: CodyApi<MaterialEvaluator> = CodyApi::new(Default::default());
+    api.handle_setoption("setoption name Ponder value true");
+
+    // A depth-1 ponder search is done long before the sleep ends, but the
+    // bestmove must wait for ponderhit.
+    let out = search_output();
+    api.start_go("go ponder depth 1", out.clone());
+    std::thread::sleep(Duration::from_millis(200));
+    assert!(api.is_searching());
+    assert_eq!(bestmove_lines(&out), 0);
+    api.handle_ponderhit();
+    api.wait_for_search();
+    assert_eq!(bestmove_lines(&out), 1);
+
+    let out = search_output();
+    api.start_go("go ponder wtime 60000 btime 60000", out.clone());
+    std::thread::sleep(Duration::from_millis(200));
+    assert_eq!(bestmove_lines(&out), 0);
+    api.handle_stop();
+    assert_eq!(bestmove_lines(&out), 1);
+}
+
+#[test]
+fn test_setoption_during_search_does_not_block() {
+    let mut api: CodyApi<MaterialEvaluator> = CodyApi::new(Default::default());
+    let out = search_output();
+
+    api.start_go("go infinite", out.clone());
+    let started = Instant::now();
+    api.handle_setoption("setoption name MultiPV value 2");
+    assert!(started.elapsed() < Duration::from_secs(1));
+    assert!(api.is_searching());
+
+    api.handle_stop();
+    assert_eq!(bestmove_lines(&out), 1);
+}